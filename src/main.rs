@@ -1,7 +1,9 @@
 use nightshade::ecs::camera::commands::spawn_pan_orbit_camera;
+use nightshade::ecs::camera::components::{OrthographicCamera, PerspectiveCamera, Projection};
+use nightshade::ecs::camera::queries::query_active_camera_matrices;
 use nightshade::ecs::camera::systems::pan_orbit_camera_system;
 use nightshade::ecs::graphics::resources::PbrDebugMode;
-use nightshade::ecs::prefab::resources::mesh_cache_insert;
+use nightshade::ecs::prefab::resources::{mesh_cache_get, mesh_cache_insert, mesh_cache_remove};
 use nightshade::ecs::text::components::{HudAnchor, TextProperties};
 use nightshade::prelude::*;
 use std::path::PathBuf;
@@ -9,6 +11,21 @@ use std::path::PathBuf;
 const DEFAULT_HDR_BYTES: &[u8] = include_bytes!("../assets/sky/moonrise.hdr");
 const DEFAULT_GLTF_BYTES: &[u8] = include_bytes!("../assets/gltf/DamagedHelmet.glb");
 
+const BOX_EDGES: [(usize, usize); 12] = [
+    (0, 1),
+    (1, 3),
+    (3, 2),
+    (2, 0),
+    (4, 5),
+    (5, 7),
+    (7, 6),
+    (6, 4),
+    (0, 4),
+    (1, 5),
+    (2, 6),
+    (3, 7),
+];
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     launch(ViewerState::default())
 }
@@ -18,1301 +35,12859 @@ struct CustomSkybox {
     path: PathBuf,
 }
 
-#[cfg(not(target_arch = "wasm32"))]
+struct ModelInstance {
+    name: String,
+    path: Option<PathBuf>,
+    entities: Vec<Entity>,
+    resources: SceneResources,
+}
+
+/// Names registered in the engine's mesh cache, material registry and texture set on
+/// behalf of one owner, so they can be freed with it.
+#[derive(Default)]
+struct SceneResources {
+    meshes: Vec<String>,
+    materials: Vec<String>,
+    textures: Vec<String>,
+}
+
+impl SceneResources {
+    fn release(self, world: &mut World) {
+        for name in &self.meshes {
+            mesh_cache_remove(&mut world.resources.mesh_cache, name);
+        }
+        for name in &self.materials {
+            nightshade::ecs::material::resources::material_registry_remove(
+                &mut world.resources.material_registry,
+                name,
+            );
+        }
+        for name in self.textures {
+            world.queue_command(WorldCommand::UnloadTexture { name });
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum AssetType {
-    Model,
-    Animation,
-    Skybox,
+enum GizmoAxis {
+    X,
+    Y,
+    Z,
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-impl AssetType {
-    fn from_extension(extension: &str) -> Option<Self> {
-        match extension.to_lowercase().as_str() {
-            "gltf" | "glb" => Some(AssetType::Model),
-            "fbx" => Some(AssetType::Animation),
-            "hdr" => Some(AssetType::Skybox),
-            _ => None,
+impl GizmoAxis {
+    const ALL: [GizmoAxis; 3] = [GizmoAxis::X, GizmoAxis::Y, GizmoAxis::Z];
+
+    fn direction(&self) -> Vec3 {
+        match self {
+            GizmoAxis::X => Vec3::new(1.0, 0.0, 0.0),
+            GizmoAxis::Y => Vec3::new(0.0, 1.0, 0.0),
+            GizmoAxis::Z => Vec3::new(0.0, 0.0, 1.0),
         }
     }
 
-    fn name(&self) -> &'static str {
+    fn color(&self) -> egui::Color32 {
         match self {
-            AssetType::Model => "Model",
-            AssetType::Animation => "Animation",
-            AssetType::Skybox => "Skybox",
+            GizmoAxis::X => egui::Color32::from_rgb(230, 70, 70),
+            GizmoAxis::Y => egui::Color32::from_rgb(90, 210, 90),
+            GizmoAxis::Z => egui::Color32::from_rgb(80, 130, 240),
         }
     }
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-struct AssetEntry {
-    path: PathBuf,
-    name: String,
-    extension: String,
-    asset_type: AssetType,
-    tags: Vec<String>,
-    categories: Vec<String>,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GizmoMode {
+    Translate,
+    Rotate,
+    Scale,
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-struct AssetLibrary {
-    entries: Vec<AssetEntry>,
-    search_query: String,
-    filter_type: Option<AssetType>,
-    show_glb: bool,
-    show_gltf: bool,
-    show_fbx: bool,
-    show_hdr: bool,
-    sort_ascending: bool,
-    show_window: bool,
-    selected_index: Option<usize>,
-}
+impl GizmoMode {
+    const ALL: [GizmoMode; 3] = [GizmoMode::Translate, GizmoMode::Rotate, GizmoMode::Scale];
 
-#[cfg(not(target_arch = "wasm32"))]
-impl Default for AssetLibrary {
-    fn default() -> Self {
-        Self {
-            entries: Vec::new(),
-            search_query: String::new(),
-            filter_type: None,
-            show_glb: true,
-            show_gltf: true,
-            show_fbx: true,
-            show_hdr: true,
-            sort_ascending: true,
-            show_window: false,
-            selected_index: None,
+    fn name(&self) -> &'static str {
+        match self {
+            GizmoMode::Translate => "Translate",
+            GizmoMode::Rotate => "Rotate",
+            GizmoMode::Scale => "Scale",
         }
     }
 }
 
-struct ViewerState {
-    model_entities: Vec<Entity>,
-    camera_entity: Option<Entity>,
-    rotation_speed: f32,
-    loaded: bool,
-    left_arrow_was_pressed: bool,
-    right_arrow_was_pressed: bool,
-    #[cfg(not(target_arch = "wasm32"))]
-    up_arrow_was_pressed: bool,
-    #[cfg(not(target_arch = "wasm32"))]
-    down_arrow_was_pressed: bool,
-    previous_atmosphere: Atmosphere,
-    custom_skyboxes: Vec<CustomSkybox>,
-    selected_custom_skybox: Option<usize>,
-    drag_file_type: Option<String>,
-    sun_entity: Option<Entity>,
-    #[cfg(not(target_arch = "wasm32"))]
-    asset_library: AssetLibrary,
-    hint_text_entity: Option<Entity>,
-    hint_hide_time: u64,
-    r_was_pressed: bool,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GizmoSpace {
+    World,
+    Local,
 }
 
-impl Default for ViewerState {
-    fn default() -> Self {
-        Self {
-            model_entities: Vec::new(),
-            camera_entity: None,
-            rotation_speed: 0.0,
-            loaded: false,
-            left_arrow_was_pressed: false,
-            right_arrow_was_pressed: false,
-            #[cfg(not(target_arch = "wasm32"))]
-            up_arrow_was_pressed: false,
-            #[cfg(not(target_arch = "wasm32"))]
-            down_arrow_was_pressed: false,
-            previous_atmosphere: Atmosphere::Hdr,
-            custom_skyboxes: Vec::new(),
-            selected_custom_skybox: None,
-            drag_file_type: None,
-            sun_entity: None,
-            #[cfg(not(target_arch = "wasm32"))]
-            asset_library: AssetLibrary::default(),
-            hint_text_entity: None,
-            hint_hide_time: 0,
-            r_was_pressed: false,
+impl GizmoSpace {
+    fn name(&self) -> &'static str {
+        match self {
+            GizmoSpace::World => "World",
+            GizmoSpace::Local => "Local",
         }
     }
 }
 
-impl State for ViewerState {
-    fn title(&self) -> &str {
-        "glTF Viewer"
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GizmoHandle {
+    Axis(GizmoAxis),
+    Uniform,
+}
 
-    fn initialize(&mut self, world: &mut World) {
-        world.resources.user_interface.enabled = true;
-        world.resources.graphics.show_grid = false;
-        world.resources.graphics.atmosphere = Atmosphere::Hdr;
-        world.resources.graphics.use_fullscreen = false;
+struct TransformSnapshot {
+    entity: Entity,
+    parent_matrix: Mat4,
+    translation: Vec3,
+    rotation: Quat,
+    scale: Vec3,
+}
 
-        load_hdr_skybox(world, DEFAULT_HDR_BYTES.to_vec());
+struct CameraTargets {
+    focus: Vec3,
+    radius: f32,
+    yaw: f32,
+    pitch: f32,
+}
 
-        let sun = spawn_sun(world);
-        if let Some(light) = world.get_light_mut(sun) {
-            light.cast_shadows = true;
+struct GizmoDrag {
+    handle: GizmoHandle,
+    mode: GizmoMode,
+    pivot: Vec3,
+    axes: [Vec3; 3],
+    gizmo_length: f32,
+    start_pointer: egui::Pos2,
+    snapshots: Vec<TransformSnapshot>,
+    camera_targets: Option<CameraTargets>,
+}
+
+struct TransformGizmo {
+    enabled: bool,
+    mode: GizmoMode,
+    space: GizmoSpace,
+    snapping: bool,
+    translate_snap: f32,
+    rotate_snap_degrees: f32,
+    scale_snap: f32,
+    hovered: bool,
+    drag: Option<GizmoDrag>,
+}
+
+impl Default for TransformGizmo {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            mode: GizmoMode::Translate,
+            space: GizmoSpace::World,
+            snapping: false,
+            translate_snap: 0.1,
+            rotate_snap_degrees: 15.0,
+            scale_snap: 0.1,
+            hovered: false,
+            drag: None,
         }
-        self.sun_entity = Some(sun);
+    }
+}
 
-        self.rotation_speed = 0.0;
+struct RaycastHit {
+    entity: Entity,
+    root: Entity,
+    distance: f32,
+    point: Vec3,
+    triangle_index: usize,
+    primitive: Option<usize>,
+    triangle: [Vec3; 3],
+}
 
-        let camera_entity = spawn_pan_orbit_camera(
-            world,
-            Vec3::new(0.0, 0.0, 0.0),
-            3.0,
-            0.0,
-            0.3,
-            "Main Camera".to_string(),
-        );
+/// Bounding volume hierarchy over a cached mesh's triangles in mesh space, built once per
+/// mesh cache entry so hover picking only tests the triangles near the ray.
+struct MeshBvh {
+    triangles: Vec<(usize, [Vec3; 3])>,
+    nodes: Vec<MeshBvhNode>,
+    primitives: Vec<(usize, usize)>,
+}
 
-        self.camera_entity = Some(camera_entity);
-        world.resources.active_camera = Some(camera_entity);
+struct MeshBvhNode {
+    min: Vec3,
+    max: Vec3,
+    start: usize,
+    count: usize,
+    second_child: usize,
+}
 
-        self.load_gltf_from_bytes(world, DEFAULT_GLTF_BYTES);
+impl MeshBvh {
+    const LEAF_SIZE: usize = 8;
 
-        let hint_properties = TextProperties {
-            font_size: 20.0,
-            color: Vec4::new(1.0, 1.0, 1.0, 0.7),
-            ..Default::default()
+    /// `primitives` lists the first triangle of each source primitive in the mesh with
+    /// that primitive's index, as recorded by `index_mesh_primitives`.
+    fn new(mesh: &Mesh, primitives: Vec<(usize, usize)>) -> Self {
+        let position = |index: u32| {
+            mesh.vertices
+                .get(index as usize)
+                .map(|vertex| Vec3::from(vertex.position))
         };
-        let hint_entity = spawn_hud_text_with_properties(
-            world,
-            "R: reset camera",
-            HudAnchor::BottomCenter,
-            Vec2::new(0.0, 40.0),
-            hint_properties,
-        );
-        self.hint_text_entity = Some(hint_entity);
-        self.hint_hide_time = 5000;
+        let mut triangles: Vec<(usize, [Vec3; 3])> = (0..mesh_triangle_count(mesh))
+            .filter_map(|triangle_index| {
+                let base = triangle_index * 3;
+                let indices = if mesh.indices.is_empty() {
+                    [base as u32, base as u32 + 1, base as u32 + 2]
+                } else {
+                    [
+                        mesh.indices[base],
+                        mesh.indices[base + 1],
+                        mesh.indices[base + 2],
+                    ]
+                };
+                Some((
+                    triangle_index,
+                    [
+                        position(indices[0])?,
+                        position(indices[1])?,
+                        position(indices[2])?,
+                    ],
+                ))
+            })
+            .collect();
+
+        let mut nodes = Vec::new();
+        if !triangles.is_empty() {
+            Self::build(&mut triangles, 0, &mut nodes);
+        }
+        Self {
+            triangles,
+            nodes,
+            primitives,
+        }
     }
 
-    fn run_systems(&mut self, world: &mut World) {
-        escape_key_exit_system(world);
-        pan_orbit_camera_system(world);
-        self.atmosphere_switch_system(world);
-        #[cfg(not(target_arch = "wasm32"))]
-        self.asset_cycle_system(world);
-        self.hint_hide_system(world);
-        self.keyboard_shortcuts_system(world);
+    fn primitive(&self, triangle_index: usize) -> Option<usize> {
+        self.primitives
+            .iter()
+            .rev()
+            .find(|(start, _)| *start <= triangle_index)
+            .map(|(_, primitive)| *primitive)
+    }
 
-        if self.loaded && self.rotation_speed > 0.0 {
-            for entity in &self.model_entities {
-                if let Some(transform) = world.get_local_transform_mut(*entity) {
-                    let rotation = nalgebra_glm::quat_angle_axis(
-                        self.rotation_speed * 0.016,
-                        &nalgebra_glm::vec3(0.0, 1.0, 0.0),
-                    );
-                    transform.rotation = rotation * transform.rotation;
-                }
-                world.mark_local_transform_dirty(*entity);
-            }
+    fn build(triangles: &mut [(usize, [Vec3; 3])], start: usize, nodes: &mut Vec<MeshBvhNode>) {
+        let (min, max) = triangles.iter().flat_map(|(_, triangle)| triangle).fold(
+            (Vec3::repeat(f32::MAX), Vec3::repeat(f32::MIN)),
+            |(min, max), vertex| (min.inf(vertex), max.sup(vertex)),
+        );
+        let index = nodes.len();
+        nodes.push(MeshBvhNode {
+            min,
+            max,
+            start,
+            count: triangles.len(),
+            second_child: 0,
+        });
+        if triangles.len() <= Self::LEAF_SIZE {
+            return;
         }
+
+        let extent = max - min;
+        let axis = extent.imax();
+        let middle = triangles.len() / 2;
+        triangles.select_nth_unstable_by(middle, |(_, a), (_, b)| {
+            (a[0][axis] + a[1][axis] + a[2][axis])
+                .total_cmp(&(b[0][axis] + b[1][axis] + b[2][axis]))
+        });
+        let (first, second) = triangles.split_at_mut(middle);
+        Self::build(first, start, nodes);
+        nodes[index].count = 0;
+        nodes[index].second_child = nodes.len();
+        Self::build(second, start + middle, nodes);
     }
 
-    fn on_dropped_file(&mut self, world: &mut World, path: &std::path::Path) {
-        if let Some(extension) = path.extension() {
-            let ext = extension.to_string_lossy().to_lowercase();
-            if ext == "hdr" {
-                self.load_hdr_skybox(world, path);
-            } else if ext == "gltf" || ext == "glb" {
-                self.load_gltf_from_path(world, path);
+    fn raycast(&self, origin: &Vec3, direction: &Vec3) -> Option<(f32, usize, [Vec3; 3])> {
+        let mut closest: Option<(f32, usize, [Vec3; 3])> = None;
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let Some(node) = self.nodes.get(index) else {
+                continue;
+            };
+            let Some(entry) = ray_aabb_intersection(origin, direction, &node.min, &node.max) else {
+                continue;
+            };
+            if closest.is_some_and(|(distance, _, _)| entry > distance) {
+                continue;
             }
-            #[cfg(not(target_arch = "wasm32"))]
-            if ext == "fbx" {
-                self.load_fbx(world, path);
+            if node.count == 0 {
+                stack.push(node.second_child);
+                stack.push(index + 1);
+                continue;
+            }
+            for (triangle_index, [a, b, c]) in &self.triangles[node.start..node.start + node.count]
+            {
+                if let Some(distance) = ray_triangle_intersection(origin, direction, a, b, c)
+                    && closest.is_none_or(|(closest_distance, _, _)| distance < closest_distance)
+                {
+                    closest = Some((distance, *triangle_index, [*a, *b, *c]));
+                }
             }
         }
-        self.drag_file_type = None;
+        closest
     }
+}
 
-    fn on_dropped_file_data(&mut self, world: &mut World, name: &str, data: &[u8]) {
-        let lower_name = name.to_lowercase();
-        if lower_name.ends_with(".hdr") {
-            self.load_hdr_skybox_from_bytes(world, name, data);
-        } else if lower_name.ends_with(".gltf") || lower_name.ends_with(".glb") {
-            self.clear_scene(world);
-            self.load_gltf_from_bytes(world, data);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MeasurementTool {
+    Distance,
+    Angle,
+}
+
+impl MeasurementTool {
+    fn name(&self) -> &'static str {
+        match self {
+            MeasurementTool::Distance => "Distance",
+            MeasurementTool::Angle => "Angle",
         }
-        self.drag_file_type = None;
     }
+}
 
-    fn on_hovered_file(&mut self, _world: &mut World, path: &std::path::Path) {
-        if let Some(extension) = path.extension() {
-            let ext = extension.to_string_lossy().to_lowercase();
-            if ext == "hdr" {
-                self.drag_file_type = Some("HDR".to_string());
-            } else if ext == "gltf" || ext == "glb" {
-                self.drag_file_type = Some("glTF".to_string());
-            } else if ext == "fbx" {
-                #[cfg(not(target_arch = "wasm32"))]
-                {
-                    self.drag_file_type = Some("FBX".to_string());
-                }
-                #[cfg(target_arch = "wasm32")]
-                {
-                    self.drag_file_type = Some("Unsupported".to_string());
-                }
-            } else {
-                self.drag_file_type = Some("Unsupported".to_string());
-            }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SnapKind {
+    Vertex,
+    Edge,
+    Surface,
+}
+
+impl SnapKind {
+    fn color(&self) -> egui::Color32 {
+        match self {
+            SnapKind::Vertex => egui::Color32::from_rgb(80, 220, 255),
+            SnapKind::Edge => egui::Color32::from_rgb(255, 140, 220),
+            SnapKind::Surface => egui::Color32::WHITE,
         }
     }
+}
 
-    fn on_hovered_file_cancelled(&mut self, _world: &mut World) {
-        self.drag_file_type = None;
+struct Measurement {
+    enabled: bool,
+    tool: MeasurementTool,
+    snap_to_vertices: bool,
+    snap_to_edges: bool,
+    show_scene_bounds: bool,
+    points: Vec<Vec3>,
+    hover_point: Option<(Vec3, SnapKind)>,
+}
+
+impl Default for Measurement {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            tool: MeasurementTool::Distance,
+            snap_to_vertices: true,
+            snap_to_edges: true,
+            show_scene_bounds: false,
+            points: Vec::new(),
+            hover_point: None,
+        }
     }
+}
 
-    fn ui(&mut self, world: &mut World, ui_context: &egui::Context) {
-        if self.drag_file_type.is_some() {
-            self.drop_indicator_ui(ui_context);
+impl Measurement {
+    fn add_point(&mut self, point: Vec3) {
+        if self.tool == MeasurementTool::Angle && self.points.len() >= 3 {
+            self.points.clear();
         }
+        self.points.push(point);
+    }
+}
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SurfaceOverride {
+    None,
+    UvChecker,
+    TexelDensity,
+}
+
+impl SurfaceOverride {
+    const ALL: [SurfaceOverride; 3] = [
+        SurfaceOverride::None,
+        SurfaceOverride::UvChecker,
+        SurfaceOverride::TexelDensity,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            SurfaceOverride::None => "None",
+            SurfaceOverride::UvChecker => "UV Checker",
+            SurfaceOverride::TexelDensity => "Texel Density",
+        }
+    }
+}
+
+struct SurfaceDebug {
+    mode: SurfaceOverride,
+    uv_set: usize,
+    checker_scale: f32,
+    target_texels_per_meter: f32,
+    fallback_resolution: u32,
+    texture_sizes: std::collections::HashMap<String, (u32, u32)>,
+    applied: Option<SurfaceDebugKey>,
+    pending: Option<(SurfaceDebugKey, f32)>,
+    swapped: std::collections::HashMap<Entity, (String, Option<String>)>,
+    resources: SceneResources,
+    generation: u32,
+}
+
+impl SurfaceDebug {
+    /// Mesh and material names as loaded, looking through any override swapped in.
+    fn original_names(&self, world: &World, entity: Entity) -> (Option<String>, Option<String>) {
+        if let Some((mesh_name, material_name)) = self.swapped.get(&entity) {
+            return (Some(mesh_name.clone()), material_name.clone());
+        }
+        (
+            world
+                .get_render_mesh(entity)
+                .map(|render_mesh| render_mesh.name.clone()),
+            world
+                .get_material_ref(entity)
+                .map(|material_ref| material_ref.name.clone()),
+        )
+    }
+}
+
+#[derive(Clone, PartialEq)]
+struct SurfaceDebugKey {
+    mode: SurfaceOverride,
+    uv_set: usize,
+    checker_scale: f32,
+    target_texels_per_meter: f32,
+    fallback_resolution: u32,
+    entities: Vec<Entity>,
+}
+
+impl Default for SurfaceDebug {
+    fn default() -> Self {
+        Self {
+            mode: SurfaceOverride::None,
+            uv_set: 0,
+            checker_scale: 1.0,
+            target_texels_per_meter: 512.0,
+            fallback_resolution: 2048,
+            texture_sizes: std::collections::HashMap::new(),
+            applied: None,
+            pending: None,
+            swapped: std::collections::HashMap::new(),
+            resources: SceneResources::default(),
+            generation: 0,
+        }
+    }
+}
+
+// Overlays are painted with egui on top of the frame, so they have no depth test and
+// every segment costs a UI shape. The mesh overlays are capped to keep that bounded.
+const DEBUG_MAX_VECTORS: usize = 5_000;
+const DEBUG_MAX_WIREFRAME_TRIANGLES: usize = 20_000;
+
+struct DebugOverlays {
+    node_bounds: bool,
+    scene_bounds: bool,
+    skeleton: bool,
+    normals: bool,
+    tangents: bool,
+    vector_length: f32,
+    wireframe: bool,
+    limit_reached: bool,
+}
+
+impl Default for DebugOverlays {
+    fn default() -> Self {
+        Self {
+            node_bounds: false,
+            scene_bounds: false,
+            skeleton: false,
+            normals: false,
+            tangents: false,
+            vector_length: 0.05,
+            wireframe: false,
+            limit_reached: false,
+        }
+    }
+}
+
+impl DebugOverlays {
+    fn any_enabled(&self) -> bool {
+        self.node_bounds
+            || self.scene_bounds
+            || self.skeleton
+            || self.normals
+            || self.tangents
+            || self.wireframe
+    }
+}
+
+struct ScaleReference {
+    true_scale: bool,
+    show_grid: bool,
+    show_human: bool,
+    show_origin: bool,
+    layout: Option<ScaleReferenceLayout>,
+    entity: Option<Entity>,
+    human_entity: Option<Entity>,
+    resources: SceneResources,
+    generation: u32,
+}
+
+impl Default for ScaleReference {
+    fn default() -> Self {
+        Self {
+            true_scale: false,
+            show_grid: true,
+            show_human: true,
+            show_origin: true,
+            layout: None,
+            entity: None,
+            human_entity: None,
+            resources: SceneResources::default(),
+            generation: 0,
+        }
+    }
+}
+
+/// What the scale reference geometry currently shows.
+#[derive(Clone, Copy, PartialEq)]
+struct ScaleReferenceLayout {
+    grid: Option<(f32, i32)>,
+    human: Option<Vec3>,
+    origin: bool,
+}
+
+impl ScaleReferenceLayout {
+    /// The parts that need new meshes when they change; the human figure only moves.
+    fn geometry(&self) -> (Option<(f32, i32)>, bool, bool) {
+        (self.grid, self.human.is_some(), self.origin)
+    }
+}
+
+const BINDABLE_KEYS: [KeyCode; 80] = [
+    KeyCode::KeyA,
+    KeyCode::KeyB,
+    KeyCode::KeyC,
+    KeyCode::KeyD,
+    KeyCode::KeyE,
+    KeyCode::KeyF,
+    KeyCode::KeyG,
+    KeyCode::KeyH,
+    KeyCode::KeyI,
+    KeyCode::KeyJ,
+    KeyCode::KeyK,
+    KeyCode::KeyL,
+    KeyCode::KeyM,
+    KeyCode::KeyN,
+    KeyCode::KeyO,
+    KeyCode::KeyP,
+    KeyCode::KeyQ,
+    KeyCode::KeyR,
+    KeyCode::KeyS,
+    KeyCode::KeyT,
+    KeyCode::KeyU,
+    KeyCode::KeyV,
+    KeyCode::KeyW,
+    KeyCode::KeyX,
+    KeyCode::KeyY,
+    KeyCode::KeyZ,
+    KeyCode::Digit0,
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+    KeyCode::ArrowLeft,
+    KeyCode::ArrowRight,
+    KeyCode::ArrowUp,
+    KeyCode::ArrowDown,
+    KeyCode::Escape,
+    KeyCode::Space,
+    KeyCode::ShiftLeft,
+    KeyCode::ShiftRight,
+    KeyCode::Tab,
+    KeyCode::Enter,
+    KeyCode::Backspace,
+    KeyCode::Delete,
+    KeyCode::Home,
+    KeyCode::End,
+    KeyCode::PageUp,
+    KeyCode::PageDown,
+    KeyCode::Comma,
+    KeyCode::Period,
+    KeyCode::BracketLeft,
+    KeyCode::BracketRight,
+    KeyCode::Minus,
+    KeyCode::Equal,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::F12,
+    KeyCode::Numpad0,
+    KeyCode::Numpad1,
+    KeyCode::Numpad2,
+    KeyCode::Numpad3,
+    KeyCode::Numpad4,
+    KeyCode::Numpad5,
+    KeyCode::Numpad6,
+    KeyCode::Numpad7,
+    KeyCode::Numpad8,
+    KeyCode::Numpad9,
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum KeyAction {
+    ResetCamera,
+    FrameSelection,
+    FrameAll,
+    DeleteInstance,
+    NextAtmosphere,
+    PreviousAtmosphere,
+    NextAsset,
+    PreviousAsset,
+    Exit,
+    PlayPause,
+    PreviousFrame,
+    NextFrame,
+    JumpToStart,
+    JumpToEnd,
+    SetInPoint,
+    SetOutPoint,
+    ToggleFlyCamera,
+    FlyForward,
+    FlyBackward,
+    FlyLeft,
+    FlyRight,
+    FlyUp,
+    FlyDown,
+    FlyBoost,
+    ViewFront,
+    ViewBack,
+    ViewLeft,
+    ViewRight,
+    ViewTop,
+    ViewBottom,
+    ViewIsometric,
+    ToggleOrthographic,
+    Bookmark(usize),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum KeyContext {
+    Global,
+    Orbit,
+    Fly,
+}
+
+impl KeyAction {
+    const ALL: [KeyAction; 41] = [
+        KeyAction::ResetCamera,
+        KeyAction::FrameSelection,
+        KeyAction::FrameAll,
+        KeyAction::ToggleFlyCamera,
+        KeyAction::FlyForward,
+        KeyAction::FlyBackward,
+        KeyAction::FlyLeft,
+        KeyAction::FlyRight,
+        KeyAction::FlyUp,
+        KeyAction::FlyDown,
+        KeyAction::FlyBoost,
+        KeyAction::ViewFront,
+        KeyAction::ViewBack,
+        KeyAction::ViewLeft,
+        KeyAction::ViewRight,
+        KeyAction::ViewTop,
+        KeyAction::ViewBottom,
+        KeyAction::ViewIsometric,
+        KeyAction::ToggleOrthographic,
+        KeyAction::Bookmark(0),
+        KeyAction::Bookmark(1),
+        KeyAction::Bookmark(2),
+        KeyAction::Bookmark(3),
+        KeyAction::Bookmark(4),
+        KeyAction::Bookmark(5),
+        KeyAction::Bookmark(6),
+        KeyAction::Bookmark(7),
+        KeyAction::Bookmark(8),
+        KeyAction::PlayPause,
+        KeyAction::PreviousFrame,
+        KeyAction::NextFrame,
+        KeyAction::JumpToStart,
+        KeyAction::JumpToEnd,
+        KeyAction::SetInPoint,
+        KeyAction::SetOutPoint,
+        KeyAction::NextAtmosphere,
+        KeyAction::PreviousAtmosphere,
+        KeyAction::NextAsset,
+        KeyAction::PreviousAsset,
+        KeyAction::DeleteInstance,
+        KeyAction::Exit,
+    ];
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn id(self) -> String {
+        match self {
+            KeyAction::Bookmark(index) => format!("Bookmark{}", index + 1),
+            action => format!("{:?}", action),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.id() == id)
+    }
+
+    fn label(self) -> String {
+        match self {
+            KeyAction::ResetCamera => "Reset Camera".to_string(),
+            KeyAction::FrameSelection => "Frame Selection".to_string(),
+            KeyAction::FrameAll => "Frame All".to_string(),
+            KeyAction::DeleteInstance => "Remove Selected Instance".to_string(),
+            KeyAction::NextAtmosphere => "Next Atmosphere".to_string(),
+            KeyAction::PreviousAtmosphere => "Previous Atmosphere".to_string(),
+            KeyAction::NextAsset => "Next Library Asset".to_string(),
+            KeyAction::PreviousAsset => "Previous Library Asset".to_string(),
+            KeyAction::Exit => "Exit".to_string(),
+            KeyAction::PlayPause => "Play / Pause".to_string(),
+            KeyAction::PreviousFrame => "Previous Frame".to_string(),
+            KeyAction::NextFrame => "Next Frame".to_string(),
+            KeyAction::JumpToStart => "Jump to Start".to_string(),
+            KeyAction::JumpToEnd => "Jump to End".to_string(),
+            KeyAction::SetInPoint => "Set In Point".to_string(),
+            KeyAction::SetOutPoint => "Set Out Point".to_string(),
+            KeyAction::ToggleFlyCamera => "Toggle Fly Camera".to_string(),
+            KeyAction::FlyForward => "Fly Forward".to_string(),
+            KeyAction::FlyBackward => "Fly Backward".to_string(),
+            KeyAction::FlyLeft => "Fly Left".to_string(),
+            KeyAction::FlyRight => "Fly Right".to_string(),
+            KeyAction::FlyUp => "Fly Up".to_string(),
+            KeyAction::FlyDown => "Fly Down".to_string(),
+            KeyAction::FlyBoost => "Fly Faster".to_string(),
+            KeyAction::ViewFront => "Front View".to_string(),
+            KeyAction::ViewBack => "Back View".to_string(),
+            KeyAction::ViewLeft => "Left View".to_string(),
+            KeyAction::ViewRight => "Right View".to_string(),
+            KeyAction::ViewTop => "Top View".to_string(),
+            KeyAction::ViewBottom => "Bottom View".to_string(),
+            KeyAction::ViewIsometric => "Isometric View".to_string(),
+            KeyAction::ToggleOrthographic => "Toggle Orthographic".to_string(),
+            KeyAction::Bookmark(index) => format!("Recall Bookmark {}", index + 1),
+        }
+    }
+
+    fn category(self) -> &'static str {
+        match self {
+            KeyAction::ResetCamera
+            | KeyAction::FrameSelection
+            | KeyAction::FrameAll
+            | KeyAction::ToggleFlyCamera
+            | KeyAction::Bookmark(_) => "Camera",
+            KeyAction::FlyForward
+            | KeyAction::FlyBackward
+            | KeyAction::FlyLeft
+            | KeyAction::FlyRight
+            | KeyAction::FlyUp
+            | KeyAction::FlyDown
+            | KeyAction::FlyBoost => "Fly Camera",
+            KeyAction::ViewFront
+            | KeyAction::ViewBack
+            | KeyAction::ViewLeft
+            | KeyAction::ViewRight
+            | KeyAction::ViewTop
+            | KeyAction::ViewBottom
+            | KeyAction::ViewIsometric
+            | KeyAction::ToggleOrthographic => "Views",
+            KeyAction::PlayPause
+            | KeyAction::PreviousFrame
+            | KeyAction::NextFrame
+            | KeyAction::JumpToStart
+            | KeyAction::JumpToEnd
+            | KeyAction::SetInPoint
+            | KeyAction::SetOutPoint => "Animation",
+            KeyAction::NextAtmosphere
+            | KeyAction::PreviousAtmosphere
+            | KeyAction::NextAsset
+            | KeyAction::PreviousAsset
+            | KeyAction::DeleteInstance
+            | KeyAction::Exit => "Scene",
+        }
+    }
+
+    fn context(self) -> KeyContext {
+        match self.category() {
+            "Fly Camera" => KeyContext::Fly,
+            _ if matches!(self, KeyAction::FrameSelection | KeyAction::FrameAll) => {
+                KeyContext::Orbit
+            }
+            _ => KeyContext::Global,
+        }
+    }
+
+    fn default_bindings(self) -> Vec<KeyBinding> {
+        let (keys, ctrl): (&[KeyCode], bool) = match self {
+            KeyAction::ResetCamera => (&[KeyCode::KeyR], false),
+            KeyAction::FrameSelection => (&[KeyCode::KeyF], false),
+            KeyAction::FrameAll => (&[KeyCode::KeyA], false),
+            KeyAction::DeleteInstance => (&[KeyCode::Delete], false),
+            KeyAction::NextAtmosphere => (&[KeyCode::ArrowRight], false),
+            KeyAction::PreviousAtmosphere => (&[KeyCode::ArrowLeft], false),
+            KeyAction::NextAsset => (&[KeyCode::ArrowDown], false),
+            KeyAction::PreviousAsset => (&[KeyCode::ArrowUp], false),
+            KeyAction::Exit => (&[KeyCode::Escape, KeyCode::KeyQ], false),
+            KeyAction::PlayPause => (&[KeyCode::Space], false),
+            KeyAction::PreviousFrame => (&[KeyCode::Comma], false),
+            KeyAction::NextFrame => (&[KeyCode::Period], false),
+            KeyAction::JumpToStart => (&[KeyCode::Home], false),
+            KeyAction::JumpToEnd => (&[KeyCode::End], false),
+            KeyAction::SetInPoint => (&[KeyCode::KeyI], false),
+            KeyAction::SetOutPoint => (&[KeyCode::KeyO], false),
+            KeyAction::ToggleFlyCamera => (&[KeyCode::KeyV], false),
+            KeyAction::FlyForward => (&[KeyCode::KeyW], false),
+            KeyAction::FlyBackward => (&[KeyCode::KeyS], false),
+            KeyAction::FlyLeft => (&[KeyCode::KeyA], false),
+            KeyAction::FlyRight => (&[KeyCode::KeyD], false),
+            KeyAction::FlyUp => (&[KeyCode::KeyE], false),
+            KeyAction::FlyDown => (&[KeyCode::KeyC], false),
+            KeyAction::FlyBoost => (&[KeyCode::ShiftLeft, KeyCode::ShiftRight], false),
+            KeyAction::ViewFront => (&[KeyCode::Numpad1], false),
+            KeyAction::ViewBack => (&[KeyCode::Numpad1], true),
+            KeyAction::ViewLeft => (&[KeyCode::Numpad3], true),
+            KeyAction::ViewRight => (&[KeyCode::Numpad3], false),
+            KeyAction::ViewTop => (&[KeyCode::Numpad7], false),
+            KeyAction::ViewBottom => (&[KeyCode::Numpad7], true),
+            KeyAction::ViewIsometric => (&[KeyCode::Numpad0], false),
+            KeyAction::ToggleOrthographic => (&[KeyCode::Numpad5], false),
+            KeyAction::Bookmark(index) => (
+                BOOKMARK_KEYS
+                    .get(index)
+                    .map(std::slice::from_ref)
+                    .unwrap_or_default(),
+                false,
+            ),
+        };
+        keys.iter()
+            .map(|key| KeyBinding { key: *key, ctrl })
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct KeyBinding {
+    key: KeyCode,
+    ctrl: bool,
+}
+
+impl KeyBinding {
+    fn label(&self) -> String {
+        let name = format!("{:?}", self.key);
+        let name = name
+            .strip_prefix("Key")
+            .or_else(|| name.strip_prefix("Digit"))
+            .or_else(|| name.strip_prefix("Arrow"))
+            .unwrap_or(&name);
+        if self.ctrl {
+            format!("Ctrl+{}", name)
+        } else {
+            name.to_string()
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn id(&self) -> String {
+        if self.ctrl {
+            format!("Ctrl+{:?}", self.key)
+        } else {
+            format!("{:?}", self.key)
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn from_id(id: &str) -> Option<Self> {
+        let (name, ctrl) = match id.strip_prefix("Ctrl+") {
+            Some(name) => (name, true),
+            None => (id, false),
+        };
+        BINDABLE_KEYS
+            .into_iter()
+            .find(|key| format!("{:?}", key) == name)
+            .map(|key| Self { key, ctrl })
+    }
+}
+
+struct KeyBindings {
+    bindings: std::collections::HashMap<KeyAction, Vec<KeyBinding>>,
+    held: Vec<KeyAction>,
+    pressed: Vec<KeyAction>,
+    capture: Option<KeyAction>,
+    capture_keys_down: Vec<KeyCode>,
+    show_window: bool,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            bindings: KeyAction::ALL
+                .into_iter()
+                .map(|action| (action, action.default_bindings()))
+                .collect(),
+            held: Vec::new(),
+            pressed: Vec::new(),
+            capture: None,
+            capture_keys_down: Vec::new(),
+            show_window: false,
+        }
+    }
+}
+
+impl KeyBindings {
+    fn bindings(&self, action: KeyAction) -> &[KeyBinding] {
+        self.bindings
+            .get(&action)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    fn label(&self, action: KeyAction) -> String {
+        let labels: Vec<String> = self
+            .bindings(action)
+            .iter()
+            .map(KeyBinding::label)
+            .collect();
+        if labels.is_empty() {
+            "Unbound".to_string()
+        } else {
+            labels.join("/")
+        }
+    }
+
+    fn held(&self, action: KeyAction) -> bool {
+        self.held.contains(&action)
+    }
+
+    fn pressed(&self, action: KeyAction) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    fn update(&mut self, is_key_pressed: impl Fn(KeyCode) -> bool) {
+        let ctrl = is_key_pressed(KeyCode::ControlLeft) || is_key_pressed(KeyCode::ControlRight);
+        let held: Vec<KeyAction> = KeyAction::ALL
+            .into_iter()
+            .filter(|action| {
+                self.bindings(*action)
+                    .iter()
+                    .any(|binding| binding.ctrl == ctrl && is_key_pressed(binding.key))
+            })
+            .collect();
+        self.pressed = held
+            .iter()
+            .copied()
+            .filter(|action| !self.held.contains(action))
+            .collect();
+        self.held = held;
+    }
+
+    fn conflicts(&self, action: KeyAction, binding: &KeyBinding) -> Vec<KeyAction> {
+        let context = action.context();
+        KeyAction::ALL
+            .into_iter()
+            .filter(|other| *other != action)
+            .filter(|other| {
+                let other_context = other.context();
+                context == KeyContext::Global
+                    || other_context == KeyContext::Global
+                    || context == other_context
+            })
+            .filter(|other| self.bindings(*other).contains(binding))
+            .collect()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn to_settings(&self) -> std::collections::BTreeMap<String, Vec<String>> {
+        KeyAction::ALL
+            .into_iter()
+            .map(|action| {
+                let keys = self.bindings(action).iter().map(KeyBinding::id).collect();
+                (action.id(), keys)
+            })
+            .collect()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn apply_settings(&mut self, settings: &std::collections::BTreeMap<String, Vec<String>>) {
+        for (id, keys) in settings {
+            let Some(action) = KeyAction::from_id(id) else {
+                tracing::warn!("Ignoring key binding for unknown action {}", id);
+                continue;
+            };
+            let bindings = keys
+                .iter()
+                .filter_map(|key| {
+                    let binding = KeyBinding::from_id(key);
+                    if binding.is_none() {
+                        tracing::warn!("Ignoring unknown key {} for {}", key, id);
+                    }
+                    binding
+                })
+                .collect();
+            self.bindings.insert(action, bindings);
+        }
+
+        for action in KeyAction::ALL {
+            for binding in self.bindings(action) {
+                for other in self.conflicts(action, binding) {
+                    if action.id() < other.id() {
+                        tracing::warn!(
+                            "Key {} is bound to both {} and {}",
+                            binding.label(),
+                            action.label(),
+                            other.label()
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+struct Settings {
+    #[serde(default)]
+    key_bindings: std::collections::BTreeMap<String, Vec<String>>,
+}
+
+struct TimelineTrack {
+    label: String,
+    keyframes: Vec<f32>,
+}
+
+struct AnimationTimeline {
+    show_window: bool,
+    frame_rate: f32,
+    in_point: f32,
+    out_point: Option<f32>,
+    loop_range: bool,
+}
+
+impl Default for AnimationTimeline {
+    fn default() -> Self {
+        Self {
+            show_window: false,
+            frame_rate: 30.0,
+            in_point: 0.0,
+            out_point: None,
+            loop_range: false,
+        }
+    }
+}
+
+impl AnimationTimeline {
+    fn range(&self, duration: f32) -> (f32, f32) {
+        let out_point = self.out_point.unwrap_or(duration).clamp(0.0, duration);
+        (self.in_point.clamp(0.0, out_point), out_point)
+    }
+
+    fn playback_range(&self, duration: f32) -> (f32, f32) {
+        if self.loop_range {
+            self.range(duration)
+        } else {
+            (0.0, duration)
+        }
+    }
+
+    fn frame_at(&self, time: f32) -> i32 {
+        (time * self.frame_rate).round() as i32
+    }
+
+    fn time_at(&self, frame: i32) -> f32 {
+        frame as f32 / self.frame_rate
+    }
+
+    fn clear_markers(&mut self) {
+        self.in_point = 0.0;
+        self.out_point = None;
+        self.loop_range = false;
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct NodePose {
+    translation: Vec3,
+    rotation: Quat,
+    scale: Vec3,
+}
+
+impl NodePose {
+    fn of(transform: &LocalTransform) -> Self {
+        Self {
+            translation: transform.translation,
+            rotation: transform.rotation,
+            scale: transform.scale,
+        }
+    }
+
+    fn matches(&self, transform: &LocalTransform) -> bool {
+        self.translation == transform.translation
+            && self.rotation == transform.rotation
+            && self.scale == transform.scale
+    }
+
+    fn matrix(&self) -> Mat4 {
+        nalgebra_glm::translation(&self.translation)
+            * nalgebra_glm::quat_to_mat4(&self.rotation)
+            * nalgebra_glm::scaling(&self.scale)
+    }
+
+    /// Splits a matrix back into a pose. Shear, which a pose cannot hold, is dropped.
+    fn from_matrix(matrix: &Mat4) -> Self {
+        let basis = nalgebra_glm::mat4_to_mat3(matrix);
+        Self {
+            translation: Vec3::new(matrix[(0, 3)], matrix[(1, 3)], matrix[(2, 3)]),
+            rotation: matrix_rotation(matrix),
+            scale: Vec3::new(
+                basis.column(0).norm(),
+                basis.column(1).norm(),
+                basis.column(2).norm(),
+            ),
+        }
+    }
+}
+
+/// Where a model root's authored transform sits in the scene. Animation channels are
+/// authored, so animated root poses are placed through this to keep the instance's
+/// placement, fit and gizmo edits.
+struct RootPlacement {
+    matrix: Mat4,
+    written: Option<(NodePose, NodePose)>,
+}
+
+impl RootPlacement {
+    fn new(current: &LocalTransform, authored: &NodePose) -> Self {
+        Self {
+            matrix: NodePose::of(current).matrix()
+                * authored
+                    .matrix()
+                    .try_inverse()
+                    .unwrap_or_else(Mat4::identity),
+            written: None,
+        }
+    }
+
+    /// Places an animated root pose, first folding in any edit made to the root since the
+    /// last placed pose was written.
+    fn place(&mut self, current: Option<&LocalTransform>, pose: NodePose) -> NodePose {
+        if let (Some((last_pose, last_placed)), Some(current)) = (self.written, current)
+            && !last_placed.matches(current)
+        {
+            *self = Self::new(current, &last_pose);
+        }
+        let placed = NodePose::from_matrix(&(self.matrix * pose.matrix()));
+        self.written = Some((pose, placed));
+        placed
+    }
+}
+
+struct Crossfade {
+    from_clip: usize,
+    from_time: f32,
+    to_clip: usize,
+    to_time: f32,
+    elapsed: f32,
+}
+
+/// Blend state of a single animation player. Bone names are resolved within the
+/// player's own hierarchy so instances sharing a skeleton never drive each other.
+#[derive(Default)]
+struct PlayerBlend {
+    crossfade: Option<Crossfade>,
+    preview: bool,
+    time: f32,
+    clip_a: usize,
+    clip_b: usize,
+    additive_clip: Option<usize>,
+    nodes: std::collections::HashMap<String, Entity>,
+    rest_pose: std::collections::HashMap<Entity, NodePose>,
+    placements: std::collections::HashMap<Entity, RootPlacement>,
+}
+
+impl PlayerBlend {
+    fn is_driving_pose(&self) -> bool {
+        self.preview || self.crossfade.is_some()
+    }
+}
+
+struct AnimationBlend {
+    crossfade_duration: f32,
+    weight: f32,
+    sync_phase: bool,
+    additive_weight: f32,
+    players: std::collections::HashMap<Entity, PlayerBlend>,
+}
+
+impl Default for AnimationBlend {
+    fn default() -> Self {
+        Self {
+            crossfade_duration: 0.3,
+            weight: 0.5,
+            sync_phase: true,
+            additive_weight: 1.0,
+            players: std::collections::HashMap::new(),
+        }
+    }
+}
+
+impl AnimationBlend {
+    fn player(&self, entity: Entity) -> Option<&PlayerBlend> {
+        self.players.get(&entity)
+    }
+
+    /// Model roots are placed, fitted and moved after loading, so their rest pose is the
+    /// authored one from `authored_roots` and their current placement is kept alongside.
+    fn capture_rest_pose(
+        &mut self,
+        world: &World,
+        entity: Entity,
+        authored_roots: &std::collections::HashMap<Entity, NodePose>,
+    ) -> &mut PlayerBlend {
+        let state = self.players.entry(entity).or_default();
+        let driving = state.is_driving_pose();
+        for (node, _, _) in collect_global_matrices(world, &[entity]) {
+            if let Some(authored) = authored_roots.get(&node) {
+                if !driving && let Some(transform) = world.get_local_transform(node) {
+                    state
+                        .placements
+                        .insert(node, RootPlacement::new(transform, authored));
+                }
+                state.rest_pose.insert(node, *authored);
+            } else if !state.rest_pose.contains_key(&node)
+                && let Some(transform) = world.get_local_transform(node)
+            {
+                state.rest_pose.insert(node, NodePose::of(transform));
+            }
+            if let Some(name) = world.get_name(node)
+                && !name.0.is_empty()
+            {
+                state.nodes.entry(name.0.clone()).or_insert(node);
+            }
+        }
+        state
+    }
+
+    fn reset(&mut self) {
+        self.players.clear();
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const HUMANOID_BONES: [[&str; 3]; 22] = [
+    ["Hips", "pelvis", "Hips"],
+    ["Spine", "spine_01", "Spine"],
+    ["Spine1", "spine_02", "Chest"],
+    ["Spine2", "spine_03", "UpperChest"],
+    ["Neck", "neck_01", "Neck"],
+    ["Head", "head", "Head"],
+    ["LeftShoulder", "clavicle_l", "LeftShoulder"],
+    ["LeftArm", "upperarm_l", "LeftUpperArm"],
+    ["LeftForeArm", "lowerarm_l", "LeftLowerArm"],
+    ["LeftHand", "hand_l", "LeftHand"],
+    ["RightShoulder", "clavicle_r", "RightShoulder"],
+    ["RightArm", "upperarm_r", "RightUpperArm"],
+    ["RightForeArm", "lowerarm_r", "RightLowerArm"],
+    ["RightHand", "hand_r", "RightHand"],
+    ["LeftUpLeg", "thigh_l", "LeftUpperLeg"],
+    ["LeftLeg", "calf_l", "LeftLowerLeg"],
+    ["LeftFoot", "foot_l", "LeftFoot"],
+    ["LeftToeBase", "ball_l", "LeftToes"],
+    ["RightUpLeg", "thigh_r", "RightUpperLeg"],
+    ["RightLeg", "calf_r", "RightLowerLeg"],
+    ["RightFoot", "foot_r", "RightFoot"],
+    ["RightToeBase", "ball_r", "RightToes"],
+];
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+enum SkeletonPreset {
+    #[default]
+    Custom,
+    Mixamo,
+    Unreal,
+    Unity,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl SkeletonPreset {
+    const ALL: [SkeletonPreset; 4] = [
+        SkeletonPreset::Custom,
+        SkeletonPreset::Mixamo,
+        SkeletonPreset::Unreal,
+        SkeletonPreset::Unity,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            SkeletonPreset::Custom => "Custom",
+            SkeletonPreset::Mixamo => "Mixamo",
+            SkeletonPreset::Unreal => "Unreal",
+            SkeletonPreset::Unity => "Unity",
+        }
+    }
+
+    fn column(self) -> Option<usize> {
+        match self {
+            SkeletonPreset::Custom => None,
+            SkeletonPreset::Mixamo => Some(0),
+            SkeletonPreset::Unreal => Some(1),
+            SkeletonPreset::Unity => Some(2),
+        }
+    }
+
+    fn humanoid_index(self, bone: &str) -> Option<usize> {
+        let column = self.column()?;
+        let bone = strip_bone_namespace(bone);
+        HUMANOID_BONES
+            .iter()
+            .position(|names| names[column].eq_ignore_ascii_case(bone))
+    }
+
+    fn detect(bones: &[String]) -> SkeletonPreset {
+        SkeletonPreset::ALL
+            .into_iter()
+            .map(|preset| {
+                let matches = bones
+                    .iter()
+                    .filter(|bone| preset.humanoid_index(bone).is_some())
+                    .count();
+                (preset, matches)
+            })
+            .filter(|(_, matches)| *matches > 0)
+            .max_by_key(|(_, matches)| *matches)
+            .map(|(preset, _)| preset)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+struct RetargetProfile {
+    source_preset: SkeletonPreset,
+    target_preset: SkeletonPreset,
+    mapping: std::collections::BTreeMap<String, String>,
+    rest_pose_correction: bool,
+    scale_compensation: bool,
+    root_translation_only: bool,
+    root_bone: Option<String>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct RetargetSession {
+    clips: Vec<AnimationClip>,
+    target_player: Entity,
+    source_bones: Vec<String>,
+    target_bones: Vec<String>,
+    source_rest: std::collections::HashMap<String, NodePose>,
+    target_rest: std::collections::HashMap<String, NodePose>,
+    profile: RetargetProfile,
+    scale: f32,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl RetargetSession {
+    fn new(
+        world: &World,
+        target_player: Entity,
+        clips: Vec<AnimationClip>,
+        source_rest: std::collections::HashMap<String, NodePose>,
+        rest_pose: &std::collections::HashMap<Entity, NodePose>,
+    ) -> Self {
+        let mut source_bones: Vec<String> = clips
+            .iter()
+            .flat_map(|clip| clip.channels.iter())
+            .map(|channel| channel.target_node.clone())
+            .collect();
+        source_bones.sort();
+        source_bones.dedup();
+
+        let mut target_bones = Vec::new();
+        let mut target_rest = std::collections::HashMap::new();
+        for (node, _, _) in collect_global_matrices(world, &[target_player]) {
+            let Some(name) = world.get_name(node).map(|name| name.0.clone()) else {
+                continue;
+            };
+            if name.is_empty() || target_rest.contains_key(&name) {
+                continue;
+            }
+            let pose = rest_pose.get(&node).copied().or_else(|| {
+                world.get_local_transform(node).map(|transform| NodePose {
+                    translation: transform.translation,
+                    rotation: transform.rotation,
+                    scale: transform.scale,
+                })
+            });
+            if let Some(pose) = pose {
+                target_rest.insert(name.clone(), pose);
+                target_bones.push(name);
+            }
+        }
+
+        let mut session = Self {
+            clips,
+            target_player,
+            source_bones,
+            target_bones,
+            source_rest,
+            target_rest,
+            profile: RetargetProfile::default(),
+            scale: 1.0,
+        };
+
+        match session
+            .profile_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str::<RetargetProfile>(&content).ok())
+        {
+            Some(profile) => session.profile = profile,
+            None => {
+                session.profile.source_preset = SkeletonPreset::detect(&session.source_bones);
+                session.profile.target_preset = SkeletonPreset::detect(&session.target_bones);
+                session.profile.rest_pose_correction = !session.source_rest.is_empty();
+                session.profile.scale_compensation = true;
+                session.profile.root_translation_only = true;
+                session.apply_presets();
+            }
+        }
+        session.scale = session.auto_scale();
+        session
+    }
+
+    fn profile_path(&self) -> Option<PathBuf> {
+        let key = format!(
+            "{}\0{}",
+            self.source_bones.join("\n"),
+            self.target_bones.join("\n")
+        );
+        Some(
+            config_directory()?
+                .join("retarget")
+                .join(format!("{:016x}.json", hash_bytes(key.as_bytes()))),
+        )
+    }
+
+    fn save_profile(&self) {
+        let Some(path) = self.profile_path() else {
+            tracing::warn!("No configuration directory available to save the bone mapping");
+            return;
+        };
+        let result = path
+            .parent()
+            .map(std::fs::create_dir_all)
+            .transpose()
+            .map_err(|error| error.to_string())
+            .and_then(|_| {
+                serde_json::to_string_pretty(&self.profile).map_err(|error| error.to_string())
+            })
+            .and_then(|content| std::fs::write(&path, content).map_err(|error| error.to_string()));
+        match result {
+            Ok(()) => tracing::info!("Saved bone mapping to {}", path.display()),
+            Err(error) => tracing::error!("Failed to save bone mapping: {}", error),
+        }
+    }
+
+    fn apply_presets(&mut self) {
+        let (source_preset, target_preset) =
+            (self.profile.source_preset, self.profile.target_preset);
+        self.profile.mapping.clear();
+        self.profile.root_bone = None;
+
+        for source in &self.source_bones {
+            let stripped = strip_bone_namespace(source);
+            let target = source_preset
+                .humanoid_index(source)
+                .and_then(|index| {
+                    self.target_bones
+                        .iter()
+                        .find(|target| target_preset.humanoid_index(target) == Some(index))
+                })
+                .or_else(|| {
+                    self.target_bones
+                        .iter()
+                        .find(|target| strip_bone_namespace(target).eq_ignore_ascii_case(stripped))
+                });
+            if let Some(target) = target {
+                self.profile.mapping.insert(source.clone(), target.clone());
+            }
+            if source_preset.humanoid_index(source) == Some(0) {
+                self.profile.root_bone = Some(source.clone());
+            }
+        }
+    }
+
+    fn source_translation(&self, bone: &str) -> Option<Vec3> {
+        if let Some(rest) = self.source_rest.get(bone) {
+            return Some(rest.translation);
+        }
+        self.clips
+            .iter()
+            .flat_map(|clip| clip.channels.iter())
+            .filter(|channel| channel.target_node == bone)
+            .find(|channel| channel.target_property == AnimationProperty::Translation)
+            .and_then(|channel| sample_channel(channel, 0.0))
+            .map(|values| nalgebra_glm::vec3(values[0], values[1], values[2]))
+    }
+
+    fn auto_scale(&self) -> f32 {
+        let Some(root) = &self.profile.root_bone else {
+            return 1.0;
+        };
+        let source = self
+            .source_translation(root)
+            .map(|translation| translation.norm());
+        let target = self
+            .profile
+            .mapping
+            .get(root)
+            .and_then(|target| self.target_rest.get(target))
+            .map(|pose| pose.translation.norm());
+        match (source, target) {
+            (Some(source), Some(target)) if source > f32::EPSILON && target > f32::EPSILON => {
+                target / source
+            }
+            _ => 1.0,
+        }
+    }
+
+    fn retargeted_clips(&self) -> Vec<AnimationClip> {
+        let profile = &self.profile;
+        self.clips
+            .iter()
+            .map(|clip| {
+                let mut clip = clip.clone();
+                clip.channels = clip
+                    .channels
+                    .drain(..)
+                    .filter_map(|mut channel| {
+                        let target = profile.mapping.get(&channel.target_node)?.clone();
+                        let is_root = profile.root_bone.as_ref() == Some(&channel.target_node);
+
+                        match channel.target_property {
+                            AnimationProperty::Translation => {
+                                if profile.root_translation_only && !is_root {
+                                    return None;
+                                }
+                                if profile.scale_compensation {
+                                    for value in &mut channel.sampler.output {
+                                        *value *= self.scale;
+                                    }
+                                }
+                            }
+                            AnimationProperty::Rotation if profile.rest_pose_correction => {
+                                if let (Some(target_rest), Some(source_rest)) = (
+                                    self.target_rest.get(&target),
+                                    self.source_rest.get(&channel.target_node),
+                                ) {
+                                    // Left-multiplying by a constant also maps spline tangents.
+                                    let correction = target_rest.rotation
+                                        * nalgebra_glm::quat_inverse(&source_rest.rotation);
+                                    for value in channel.sampler.output.chunks_exact_mut(4) {
+                                        let rotation = correction
+                                            * nalgebra_glm::quat(
+                                                value[0], value[1], value[2], value[3],
+                                            );
+                                        value.copy_from_slice(rotation.coords.as_slice());
+                                    }
+                                }
+                            }
+                            _ => {}
+                        }
+
+                        channel.target_node = target;
+                        Some(channel)
+                    })
+                    .collect();
+                clip
+            })
+            .collect()
+    }
+}
+
+const BOOKMARK_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+#[derive(Clone, Copy)]
+struct CameraPose {
+    focus: Vec3,
+    radius: f32,
+    yaw: f32,
+    pitch: f32,
+}
+
+impl CameraPose {
+    fn interpolate(&self, to: &CameraPose, factor: f32) -> CameraPose {
+        let yaw_delta = (to.yaw - self.yaw + std::f32::consts::PI)
+            .rem_euclid(std::f32::consts::TAU)
+            - std::f32::consts::PI;
+        CameraPose {
+            focus: nalgebra_glm::lerp(&self.focus, &to.focus, factor),
+            radius: self.radius + (to.radius - self.radius) * factor,
+            yaw: self.yaw + yaw_delta * factor,
+            pitch: self.pitch + (to.pitch - self.pitch) * factor,
+        }
+    }
+}
+
+#[cfg_attr(
+    not(target_arch = "wasm32"),
+    derive(serde::Serialize, serde::Deserialize)
+)]
+#[derive(Clone)]
+struct CameraBookmark {
+    name: String,
+    focus: [f32; 3],
+    radius: f32,
+    yaw: f32,
+    pitch: f32,
+}
+
+struct CameraTransition {
+    from: CameraPose,
+    to: CameraPose,
+    elapsed: f32,
+}
+
+struct CameraBookmarks {
+    bookmarks: Vec<CameraBookmark>,
+    new_name: String,
+    transition_duration: f32,
+    transition: Option<CameraTransition>,
+    model_path: Option<PathBuf>,
+}
+
+impl Default for CameraBookmarks {
+    fn default() -> Self {
+        Self {
+            bookmarks: Vec::new(),
+            new_name: String::new(),
+            transition_duration: 0.6,
+            transition: None,
+            model_path: None,
+        }
+    }
+}
+
+const FLY_MOVEMENT_ACTIONS: [(KeyAction, [f32; 3]); 6] = [
+    (KeyAction::FlyForward, [0.0, 0.0, 1.0]),
+    (KeyAction::FlyBackward, [0.0, 0.0, -1.0]),
+    (KeyAction::FlyRight, [1.0, 0.0, 0.0]),
+    (KeyAction::FlyLeft, [-1.0, 0.0, 0.0]),
+    (KeyAction::FlyUp, [0.0, 1.0, 0.0]),
+    (KeyAction::FlyDown, [0.0, -1.0, 0.0]),
+];
+
+const FLY_PIVOT_DISTANCE: f32 = 0.01;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum ViewPreset {
+    Front,
+    Back,
+    Left,
+    Right,
+    Top,
+    Bottom,
+    Isometric,
+}
+
+impl ViewPreset {
+    const ALL: [ViewPreset; 7] = [
+        ViewPreset::Front,
+        ViewPreset::Back,
+        ViewPreset::Left,
+        ViewPreset::Right,
+        ViewPreset::Top,
+        ViewPreset::Bottom,
+        ViewPreset::Isometric,
+    ];
+
+    fn yaw_pitch(self) -> (f32, f32) {
+        let vertical = std::f32::consts::FRAC_PI_2 - 0.001;
+        match self {
+            ViewPreset::Front => (0.0, 0.0),
+            ViewPreset::Back => (std::f32::consts::PI, 0.0),
+            ViewPreset::Left => (-std::f32::consts::FRAC_PI_2, 0.0),
+            ViewPreset::Right => (std::f32::consts::FRAC_PI_2, 0.0),
+            ViewPreset::Top => (0.0, vertical),
+            ViewPreset::Bottom => (0.0, -vertical),
+            ViewPreset::Isometric => (
+                std::f32::consts::FRAC_PI_4,
+                std::f32::consts::FRAC_1_SQRT_2.atan(),
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "gamepad")]
+const GAMEPAD_BUTTONS: [GamepadButton; 15] = [
+    GamepadButton::South,
+    GamepadButton::East,
+    GamepadButton::North,
+    GamepadButton::West,
+    GamepadButton::LeftTrigger,
+    GamepadButton::RightTrigger,
+    GamepadButton::LeftTrigger2,
+    GamepadButton::RightTrigger2,
+    GamepadButton::Select,
+    GamepadButton::Start,
+    GamepadButton::RightThumb,
+    GamepadButton::DPadUp,
+    GamepadButton::DPadDown,
+    GamepadButton::DPadLeft,
+    GamepadButton::DPadRight,
+];
+
+#[cfg(feature = "gamepad")]
+const GAMEPAD_ACTIONS: [(GamepadButton, KeyAction); 9] = [
+    (GamepadButton::LeftTrigger, KeyAction::PreviousAsset),
+    (GamepadButton::RightTrigger, KeyAction::NextAsset),
+    (GamepadButton::LeftTrigger2, KeyAction::PreviousAtmosphere),
+    (GamepadButton::RightTrigger2, KeyAction::NextAtmosphere),
+    (GamepadButton::South, KeyAction::PlayPause),
+    (GamepadButton::West, KeyAction::PreviousFrame),
+    (GamepadButton::North, KeyAction::NextFrame),
+    (GamepadButton::East, KeyAction::JumpToStart),
+    (GamepadButton::Select, KeyAction::ResetCamera),
+];
+
+#[cfg(feature = "gamepad")]
+struct GamepadControls {
+    buttons_down: Vec<GamepadButton>,
+    navigating: bool,
+    navigation_presses: Vec<GamepadButton>,
+    right_stick_zoom: bool,
+    sensitivity: f32,
+    invert_y: bool,
+    dead_zone: f32,
+}
+
+#[cfg(feature = "gamepad")]
+impl Default for GamepadControls {
+    fn default() -> Self {
+        Self {
+            buttons_down: Vec::new(),
+            navigating: false,
+            navigation_presses: Vec::new(),
+            right_stick_zoom: false,
+            sensitivity: 1.0,
+            invert_y: false,
+            dead_zone: 0.15,
+        }
+    }
+}
+
+#[cfg(feature = "openxr")]
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct XrHandInput {
+    tracked: bool,
+    position: [f32; 3],
+    orientation: [f32; 4],
+    squeeze: f32,
+    trigger: f32,
+    primary: bool,
+    secondary: bool,
+    thumbstick_click: bool,
+}
+
+#[cfg(feature = "openxr")]
+impl Default for XrHandInput {
+    fn default() -> Self {
+        Self {
+            tracked: false,
+            position: [0.0; 3],
+            orientation: [0.0, 0.0, 0.0, 1.0],
+            squeeze: 0.0,
+            trigger: 0.0,
+            primary: false,
+            secondary: false,
+            thumbstick_click: false,
+        }
+    }
+}
+
+#[cfg(feature = "openxr")]
+impl XrHandInput {
+    fn from_controller(controller: &XrController) -> Self {
+        let orientation = controller.grip_orientation.coords;
+        Self {
+            tracked: controller.tracked,
+            position: controller.grip_position.into(),
+            orientation: [orientation.x, orientation.y, orientation.z, orientation.w],
+            squeeze: controller.squeeze,
+            trigger: controller.trigger,
+            primary: controller.primary_button,
+            secondary: controller.secondary_button,
+            thumbstick_click: controller.thumbstick_click,
+        }
+    }
+
+    fn position(&self) -> Vec3 {
+        Vec3::from(self.position)
+    }
+
+    fn orientation(&self) -> Quat {
+        let [x, y, z, w] = self.orientation;
+        nalgebra_glm::quat_normalize(&nalgebra_glm::quat(x, y, z, w))
+    }
+
+    fn grabbing(&self) -> bool {
+        self.tracked && self.squeeze > 0.6
+    }
+}
+
+#[cfg(feature = "openxr")]
+#[derive(Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct XrFrameInput {
+    head: XrHandInput,
+    left: XrHandInput,
+    right: XrHandInput,
+}
+
+#[cfg(feature = "openxr")]
+impl XrFrameInput {
+    fn buttons(&self) -> [bool; 6] {
+        [
+            self.left.primary,
+            self.left.secondary,
+            self.right.primary,
+            self.right.secondary,
+            self.left.thumbstick_click,
+            self.right.thumbstick_click,
+        ]
+    }
+}
+
+#[cfg(feature = "openxr")]
+enum XrInputSource {
+    Runtime,
+    Mock {
+        frames: Vec<XrFrameInput>,
+        index: usize,
+    },
+}
+
+#[cfg(feature = "openxr")]
+impl XrInputSource {
+    fn from_environment() -> Self {
+        let Some(path) = std::env::var_os("GLTF_VIEWER_XR_MOCK") else {
+            return XrInputSource::Runtime;
+        };
+        let frames = std::fs::read_to_string(&path)
+            .map_err(|error| error.to_string())
+            .and_then(|content| {
+                serde_json::from_str::<Vec<XrFrameInput>>(&content)
+                    .map_err(|error| error.to_string())
+            });
+        match frames {
+            Ok(frames) if !frames.is_empty() => {
+                tracing::info!(
+                    "Replaying {} mock XR frames from {}",
+                    frames.len(),
+                    PathBuf::from(&path).display()
+                );
+                XrInputSource::Mock { frames, index: 0 }
+            }
+            Ok(_) => XrInputSource::Runtime,
+            Err(error) => {
+                tracing::error!(
+                    "Failed to load mock XR input {}: {}",
+                    PathBuf::from(&path).display(),
+                    error
+                );
+                XrInputSource::Runtime
+            }
+        }
+    }
+
+    fn next_frame(&mut self, xr: Option<&XrState>) -> Option<XrFrameInput> {
+        match self {
+            XrInputSource::Runtime => {
+                let xr = xr?;
+                let head = xr.head_orientation.coords;
+                Some(XrFrameInput {
+                    head: XrHandInput {
+                        tracked: true,
+                        position: xr.head_position.into(),
+                        orientation: [head.x, head.y, head.z, head.w],
+                        ..Default::default()
+                    },
+                    left: XrHandInput::from_controller(&xr.left_controller),
+                    right: XrHandInput::from_controller(&xr.right_controller),
+                })
+            }
+            XrInputSource::Mock { frames, index } => {
+                let frame = frames.get(*index).or(frames.last()).copied();
+                *index += 1;
+                frame
+            }
+        }
+    }
+}
+
+#[cfg(feature = "openxr")]
+struct XrGrab {
+    hands: [bool; 2],
+    start: [(Vec3, Quat); 2],
+    roots: Vec<(Entity, Vec3, Quat, Vec3)>,
+    model_scale: f32,
+}
+
+#[cfg(feature = "openxr")]
+impl XrGrab {
+    fn motion(&self, poses: [(Vec3, Quat); 2]) -> Option<XrGrabMotion> {
+        let [
+            (left_start, left_start_rotation),
+            (right_start, right_start_rotation),
+        ] = self.start;
+        let [(left, left_rotation), (right, right_rotation)] = poses;
+        let motion = match self.hands {
+            [true, true] => {
+                let start_span = right_start - left_start;
+                let span = right - left;
+                let scale =
+                    nalgebra_glm::length(&span) / nalgebra_glm::length(&start_span).max(0.01);
+                XrGrabMotion {
+                    pivot_start: (left_start + right_start) * 0.5,
+                    pivot: (left + right) * 0.5,
+                    rotation: nalgebra_glm::quat_rotation(
+                        &nalgebra_glm::normalize(&start_span),
+                        &nalgebra_glm::normalize(&span),
+                    ),
+                    scale: (self.model_scale * scale).clamp(0.01, 100.0) / self.model_scale,
+                }
+            }
+            [true, false] => XrGrabMotion {
+                pivot_start: left_start,
+                pivot: left,
+                rotation: left_rotation * nalgebra_glm::quat_inverse(&left_start_rotation),
+                scale: 1.0,
+            },
+            [false, true] => XrGrabMotion {
+                pivot_start: right_start,
+                pivot: right,
+                rotation: right_rotation * nalgebra_glm::quat_inverse(&right_start_rotation),
+                scale: 1.0,
+            },
+            [false, false] => return None,
+        };
+        Some(motion)
+    }
+}
+
+#[cfg(feature = "openxr")]
+struct XrGrabMotion {
+    pivot_start: Vec3,
+    pivot: Vec3,
+    rotation: Quat,
+    scale: f32,
+}
+
+#[cfg(feature = "openxr")]
+impl XrGrabMotion {
+    fn apply(&self, translation: Vec3, rotation: Quat, scale: Vec3) -> (Vec3, Quat, Vec3) {
+        (
+            self.pivot
+                + nalgebra_glm::quat_rotate_vec3(
+                    &self.rotation,
+                    &((translation - self.pivot_start) * self.scale),
+                ),
+            self.rotation * rotation,
+            scale * self.scale,
+        )
+    }
+}
+
+#[cfg(feature = "openxr")]
+struct XrInteraction {
+    source: XrInputSource,
+    active: bool,
+    head: Option<(Vec3, Quat)>,
+    grab: Option<XrGrab>,
+    model_scale: f32,
+    tracked_roots: Vec<Entity>,
+    buttons_down: [bool; 6],
+    panels_visible: bool,
+    pinned_panel: Option<(Vec3, Quat)>,
+    panel_anchor: Option<(Vec3, Quat)>,
+    place_requested: bool,
+}
+
+#[cfg(feature = "openxr")]
+impl Default for XrInteraction {
+    fn default() -> Self {
+        Self {
+            source: XrInputSource::from_environment(),
+            active: false,
+            head: None,
+            grab: None,
+            model_scale: 1.0,
+            tracked_roots: Vec::new(),
+            buttons_down: [false; 6],
+            panels_visible: true,
+            pinned_panel: None,
+            panel_anchor: None,
+            place_requested: false,
+        }
+    }
+}
+
+#[derive(Default)]
+struct ViewPresets {
+    orthographic: bool,
+    perspective: Option<PerspectiveCamera>,
+}
+
+struct FlyCamera {
+    pose: Option<CameraPose>,
+    speed: f32,
+    look_sensitivity: f32,
+    invert_y: bool,
+    orbit_radius: f32,
+}
+
+impl Default for FlyCamera {
+    fn default() -> Self {
+        Self {
+            pose: None,
+            speed: 2.0,
+            look_sensitivity: 0.2,
+            invert_y: false,
+            orbit_radius: 3.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum RotationPivot {
+    RootOrigins,
+    BoundsCenter,
+}
+
+struct AutoRotation {
+    axis: GizmoAxis,
+    pivot: RotationPivot,
+    rotate_camera: bool,
+    ping_pong: bool,
+    ping_pong_degrees: f32,
+    angle: f32,
+    direction: f32,
+    pivot_point: Option<Vec3>,
+    pivot_roots: Vec<(Entity, NodePose)>,
+}
+
+impl Default for AutoRotation {
+    fn default() -> Self {
+        Self {
+            axis: GizmoAxis::Y,
+            pivot: RotationPivot::RootOrigins,
+            rotate_camera: false,
+            ping_pong: false,
+            ping_pong_degrees: 45.0,
+            angle: 0.0,
+            direction: 1.0,
+            pivot_point: None,
+            pivot_roots: Vec::new(),
+        }
+    }
+}
+
+impl AutoRotation {
+    fn reset(&mut self) {
+        self.angle = 0.0;
+        self.direction = 1.0;
+        self.pivot_point = None;
+        self.pivot_roots.clear();
+    }
+
+    /// Drops the cached pivot when instances were added, removed or moved since the
+    /// rotation last wrote the roots.
+    fn track_roots(&mut self, roots: Vec<(Entity, NodePose)>) {
+        if roots != self.pivot_roots {
+            self.pivot_point = None;
+        }
+        self.pivot_roots = roots;
+    }
+
+    fn advance(&mut self, step: f32) -> f32 {
+        let mut step = step * self.direction;
+        if self.ping_pong {
+            let limit = self.ping_pong_degrees.to_radians();
+            let target = self.angle + step;
+            if target.abs() > limit {
+                step = target.clamp(-limit, limit) - self.angle;
+                self.direction = -self.direction;
+            }
+        }
+        self.angle += step;
+        step
+    }
+}
+
+#[derive(Default)]
+struct MorphTargets {
+    override_while_paused: bool,
+    overrides: std::collections::HashMap<Entity, Vec<f32>>,
+    target_names: std::collections::HashMap<String, Vec<String>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SequenceSource {
+    Turntable,
+    AnimationRange,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SequenceOutput {
+    ImageSequence,
+    Video,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct SequenceRun {
+    frame: u32,
+    frame_count: u32,
+    awaiting_capture: bool,
+    initial_rotations: Vec<(Entity, Quat)>,
+    animation: Option<(Entity, bool)>,
+    frames: std::sync::mpsc::SyncSender<(PathBuf, nightshade::ecs::world::CapturedFrame)>,
+    pending: Option<(PathBuf, nightshade::ecs::world::CapturedFrame)>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+enum SequenceEvent {
+    FrameWritten,
+    FrameFailed(String),
+    Encoding,
+    Finished(Result<PathBuf, String>),
+}
+
+/// Background side of a sequence render. A single writer thread encodes captured
+/// frames in order and then runs ffmpeg, reporting progress back over a channel.
+#[cfg(not(target_arch = "wasm32"))]
+struct SequenceJob {
+    events: std::sync::mpsc::Receiver<SequenceEvent>,
+    cancel: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    frame_count: u32,
+    frames_written: u32,
+    encoding: bool,
+    errors: Vec<String>,
+    result: Option<Result<PathBuf, String>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct SequenceExport {
+    show_window: bool,
+    source: SequenceSource,
+    output: SequenceOutput,
+    width: u32,
+    height: u32,
+    frame_rate: u32,
+    turntable_seconds: f32,
+    range_start: f32,
+    range_end: f32,
+    directory: PathBuf,
+    file_prefix: String,
+    keep_frames: bool,
+    run: Option<SequenceRun>,
+    job: Option<SequenceJob>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for SequenceExport {
+    fn default() -> Self {
+        Self {
+            show_window: false,
+            source: SequenceSource::Turntable,
+            output: SequenceOutput::ImageSequence,
+            width: 1920,
+            height: 1080,
+            frame_rate: 30,
+            turntable_seconds: 6.0,
+            range_start: 0.0,
+            range_end: 1.0,
+            directory: PathBuf::from("renders"),
+            file_prefix: "frame".to_string(),
+            keep_frames: true,
+            run: None,
+            job: None,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl SequenceExport {
+    fn frame_count(&self) -> u32 {
+        match self.source {
+            SequenceSource::Turntable => (self.turntable_seconds * self.frame_rate as f32)
+                .round()
+                .max(1.0) as u32,
+            SequenceSource::AnimationRange => {
+                ((self.range_end - self.range_start).max(0.0) * self.frame_rate as f32).floor()
+                    as u32
+                    + 1
+            }
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct ScreenshotRestore {
+    atmosphere: Atmosphere,
+    clear_color: [f32; 4],
+    show_grid: bool,
+    ui_enabled: bool,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct PendingScreenshot {
+    path: PathBuf,
+    supersampling: u32,
+    requested: bool,
+    restore: ScreenshotRestore,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct ScreenshotOptions {
+    show_window: bool,
+    width: u32,
+    height: u32,
+    supersampling: u32,
+    transparent_background: bool,
+    hide_ui: bool,
+    hide_grid: bool,
+    directory: PathBuf,
+    filename_template: String,
+    counter: u32,
+    pending: Option<PendingScreenshot>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for ScreenshotOptions {
+    fn default() -> Self {
+        Self {
+            show_window: false,
+            width: 3840,
+            height: 2160,
+            supersampling: 2,
+            transparent_background: true,
+            hide_ui: true,
+            hide_grid: true,
+            directory: PathBuf::from("screenshots"),
+            filename_template: "{model}_{width}x{height}_{index}".to_string(),
+            counter: 1,
+            pending: None,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ScreenshotOptions {
+    fn file_name(&self, model: &str) -> String {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        let name = self
+            .filename_template
+            .replace("{model}", model)
+            .replace("{width}", &self.width.to_string())
+            .replace("{height}", &self.height.to_string())
+            .replace("{index}", &format!("{:04}", self.counter))
+            .replace("{timestamp}", &timestamp.to_string());
+        if name.to_lowercase().ends_with(".png") {
+            name
+        } else {
+            format!("{}.png", name)
+        }
+    }
+}
+
+struct ViewportProjection {
+    view_projection: Mat4,
+    inverse_view_projection: Mat4,
+    camera_position: Vec3,
+    rect: egui::Rect,
+}
+
+impl ViewportProjection {
+    fn new(world: &World, rect: egui::Rect) -> Option<Self> {
+        let matrices = query_active_camera_matrices(world)?;
+        let view_projection = matrices.projection * matrices.view;
+        let inverse_view_projection = view_projection.try_inverse()?;
+
+        Some(Self {
+            view_projection,
+            inverse_view_projection,
+            camera_position: matrices.camera_position,
+            rect,
+        })
+    }
+
+    fn world_to_screen(&self, point: &Vec3) -> Option<egui::Pos2> {
+        let clip = self.view_projection * Vec4::new(point.x, point.y, point.z, 1.0);
+        if clip.w <= f32::EPSILON {
+            return None;
+        }
+
+        let ndc_x = clip.x / clip.w;
+        let ndc_y = clip.y / clip.w;
+
+        Some(egui::pos2(
+            self.rect.left() + (ndc_x + 1.0) * 0.5 * self.rect.width(),
+            self.rect.top() + (1.0 - ndc_y) * 0.5 * self.rect.height(),
+        ))
+    }
+
+    fn unproject(&self, position: egui::Pos2, depth: f32) -> Vec3 {
+        let ndc_x = (position.x - self.rect.left()) / self.rect.width() * 2.0 - 1.0;
+        let ndc_y = 1.0 - (position.y - self.rect.top()) / self.rect.height() * 2.0;
+        let world = self.inverse_view_projection * Vec4::new(ndc_x, ndc_y, depth, 1.0);
+        Vec3::new(world.x, world.y, world.z) / world.w
+    }
+
+    fn screen_ray(&self, position: egui::Pos2) -> (Vec3, Vec3) {
+        let first = self.unproject(position, 0.25);
+        let second = self.unproject(position, 0.75);
+
+        let (near, far) = if nalgebra_glm::distance(&first, &self.camera_position)
+            <= nalgebra_glm::distance(&second, &self.camera_position)
+        {
+            (first, second)
+        } else {
+            (second, first)
+        };
+
+        (near, nalgebra_glm::normalize(&(far - near)))
+    }
+
+    fn pixels_per_unit(&self, point: &Vec3, direction: &Vec3) -> Option<(egui::Vec2, f32)> {
+        let start = self.world_to_screen(point)?;
+        let end = self.world_to_screen(&(point + direction))?;
+        let screen_direction = end - start;
+        let length = screen_direction.length();
+        if length <= f32::EPSILON {
+            return None;
+        }
+        Some((screen_direction / length, length))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AssetType {
+    Model,
+    Animation,
+    Skybox,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl AssetType {
+    fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_lowercase().as_str() {
+            "gltf" | "glb" => Some(AssetType::Model),
+            "fbx" => Some(AssetType::Animation),
+            "hdr" => Some(AssetType::Skybox),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            AssetType::Model => "Model",
+            AssetType::Animation => "Animation",
+            AssetType::Skybox => "Skybox",
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AssetSortKey {
+    Name,
+    FileSize,
+    Modified,
+    TriangleCount,
+    TextureMemory,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl AssetSortKey {
+    const ALL: &'static [AssetSortKey] = &[
+        AssetSortKey::Name,
+        AssetSortKey::FileSize,
+        AssetSortKey::Modified,
+        AssetSortKey::TriangleCount,
+        AssetSortKey::TextureMemory,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            AssetSortKey::Name => "Name",
+            AssetSortKey::FileSize => "File Size",
+            AssetSortKey::Modified => "Modified",
+            AssetSortKey::TriangleCount => "Triangles",
+            AssetSortKey::TextureMemory => "Texture Memory",
+        }
+    }
+
+    fn direction_label(&self, ascending: bool) -> &'static str {
+        match (self, ascending) {
+            (AssetSortKey::Name, true) => "A-Z",
+            (AssetSortKey::Name, false) => "Z-A",
+            (AssetSortKey::Modified, true) => "Oldest",
+            (AssetSortKey::Modified, false) => "Newest",
+            (_, true) => "Low-High",
+            (_, false) => "High-Low",
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AssetGrouping {
+    None,
+    Directory,
+    Category,
+    AssetType,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl AssetGrouping {
+    const ALL: &'static [AssetGrouping] = &[
+        AssetGrouping::None,
+        AssetGrouping::Directory,
+        AssetGrouping::Category,
+        AssetGrouping::AssetType,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            AssetGrouping::None => "None",
+            AssetGrouping::Directory => "Directory",
+            AssetGrouping::Category => "Category",
+            AssetGrouping::AssetType => "Asset Type",
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AssetLibraryView {
+    Entries,
+    Duplicates,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DuplicateKind {
+    Exact,
+    SameContent,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl DuplicateKind {
+    fn name(&self) -> &'static str {
+        match self {
+            DuplicateKind::Exact => "Exact duplicates",
+            DuplicateKind::SameContent => "Same meshes and textures",
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct DuplicateGroup {
+    kind: DuplicateKind,
+    paths: Vec<PathBuf>,
+    file_size: u64,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Default, Clone, Copy)]
+struct AssetStatistics {
+    triangle_count: Option<u64>,
+    texture_memory: Option<u64>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct AssetEntry {
+    path: PathBuf,
+    name: String,
+    extension: String,
+    asset_type: AssetType,
+    tags: Vec<String>,
+    categories: Vec<String>,
+    directory: String,
+    file_size: u64,
+    modified: Option<std::time::SystemTime>,
+    statistics: AssetStatistics,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct AssetLibrary {
+    entries: Vec<AssetEntry>,
+    search_query: String,
+    filter_type: Option<AssetType>,
+    show_glb: bool,
+    show_gltf: bool,
+    show_fbx: bool,
+    show_hdr: bool,
+    sort_key: AssetSortKey,
+    sort_ascending: bool,
+    grouping: AssetGrouping,
+    view: AssetLibraryView,
+    duplicate_groups: Vec<DuplicateGroup>,
+    add_to_scene: bool,
+    show_window: bool,
+    selected_index: Option<usize>,
+    pending_statistics: Option<std::sync::mpsc::Receiver<(PathBuf, AssetStatistics)>>,
+    pending_duplicates: Option<std::sync::mpsc::Receiver<Vec<DuplicateGroup>>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for AssetLibrary {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            search_query: String::new(),
+            filter_type: None,
+            show_glb: true,
+            show_gltf: true,
+            show_fbx: true,
+            show_hdr: true,
+            sort_key: AssetSortKey::Name,
+            sort_ascending: true,
+            grouping: AssetGrouping::None,
+            view: AssetLibraryView::Entries,
+            duplicate_groups: Vec::new(),
+            add_to_scene: false,
+            show_window: false,
+            selected_index: None,
+            pending_statistics: None,
+            pending_duplicates: None,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl AssetLibrary {
+    /// Empties the library and drops the receivers of any scans still running, so their
+    /// results never land in a later library.
+    fn clear_entries(&mut self) {
+        self.entries.clear();
+        self.duplicate_groups.clear();
+        self.selected_index = None;
+        self.pending_statistics = None;
+        self.pending_duplicates = None;
+    }
+
+    fn receive_statistics(&mut self) {
+        let Some(receiver) = &self.pending_statistics else {
+            return;
+        };
+        let mut indices: Option<std::collections::HashMap<PathBuf, usize>> = None;
+        loop {
+            match receiver.try_recv() {
+                Ok((path, statistics)) => {
+                    let indices = indices.get_or_insert_with(|| {
+                        self.entries
+                            .iter()
+                            .enumerate()
+                            .map(|(index, entry)| (entry.path.clone(), index))
+                            .collect()
+                    });
+                    if let Some(&index) = indices.get(&path) {
+                        self.entries[index].statistics = statistics;
+                    }
+                }
+                Err(std::sync::mpsc::TryRecvError::Empty) => return,
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    self.pending_statistics = None;
+                    if matches!(
+                        self.sort_key,
+                        AssetSortKey::TriangleCount | AssetSortKey::TextureMemory
+                    ) {
+                        self.sort_entries();
+                    }
+                    return;
+                }
+            }
+        }
+    }
+
+    fn sort_entries(&mut self) {
+        let sort_key = self.sort_key;
+        let ascending = self.sort_ascending;
+
+        self.entries.sort_by(|a, b| {
+            let ordering = match sort_key {
+                AssetSortKey::Name => std::cmp::Ordering::Equal,
+                AssetSortKey::FileSize => a.file_size.cmp(&b.file_size),
+                AssetSortKey::Modified => a.modified.cmp(&b.modified),
+                AssetSortKey::TriangleCount => a
+                    .statistics
+                    .triangle_count
+                    .cmp(&b.statistics.triangle_count),
+                AssetSortKey::TextureMemory => a
+                    .statistics
+                    .texture_memory
+                    .cmp(&b.statistics.texture_memory),
+            }
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+
+            if ascending {
+                ordering
+            } else {
+                ordering.reverse()
+            }
+        });
+    }
+
+    fn find_duplicates(&mut self) {
+        let files: Vec<(PathBuf, u64, AssetType)> = self
+            .entries
+            .iter()
+            .map(|entry| (entry.path.clone(), entry.file_size, entry.asset_type))
+            .collect();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(find_duplicate_groups(files));
+        });
+        self.pending_duplicates = Some(receiver);
+        self.duplicate_groups.clear();
+        self.view = AssetLibraryView::Duplicates;
+    }
+
+    fn receive_duplicates(&mut self) {
+        let Some(receiver) = &self.pending_duplicates else {
+            return;
+        };
+        match receiver.try_recv() {
+            Ok(duplicate_groups) => {
+                tracing::info!("Found {} duplicate group(s)", duplicate_groups.len());
+                self.duplicate_groups = duplicate_groups;
+                self.pending_duplicates = None;
+            }
+            Err(std::sync::mpsc::TryRecvError::Empty) => {}
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                self.pending_duplicates = None;
+            }
+        }
+    }
+
+    fn is_entry_visible(&self, entry: &AssetEntry, search_query_lower: &str) -> bool {
+        let show_ext = match entry.extension.as_str() {
+            "glb" => self.show_glb,
+            "gltf" => self.show_gltf,
+            "fbx" => self.show_fbx,
+            "hdr" => self.show_hdr,
+            _ => true,
+        };
+        if !show_ext {
+            return false;
+        }
+
+        if let Some(filter) = self.filter_type
+            && entry.asset_type != filter
+        {
+            return false;
+        }
+
+        if !search_query_lower.is_empty() {
+            let name_matches = entry.name.to_lowercase().contains(search_query_lower);
+            let tag_matches = entry
+                .tags
+                .iter()
+                .any(|t| t.to_lowercase().contains(search_query_lower));
+            let category_matches = entry
+                .categories
+                .iter()
+                .any(|c| c.to_lowercase().contains(search_query_lower));
+
+            if !name_matches && !tag_matches && !category_matches {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn grouped_entries(&self, search_query_lower: &str) -> Vec<(String, Vec<usize>)> {
+        let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
+
+        for (index, entry) in self.entries.iter().enumerate() {
+            if !self.is_entry_visible(entry, search_query_lower) {
+                continue;
+            }
+
+            let group_names = match self.grouping {
+                AssetGrouping::None => vec![String::new()],
+                AssetGrouping::Directory => vec![entry.directory.clone()],
+                AssetGrouping::Category => {
+                    if entry.categories.is_empty() {
+                        vec!["Uncategorized".to_string()]
+                    } else {
+                        entry.categories.clone()
+                    }
+                }
+                AssetGrouping::AssetType => vec![entry.asset_type.name().to_string()],
+            };
+
+            for group_name in group_names {
+                match groups.iter_mut().find(|(name, _)| *name == group_name) {
+                    Some((_, indices)) => indices.push(index),
+                    None => groups.push((group_name, vec![index])),
+                }
+            }
+        }
+
+        if self.grouping != AssetGrouping::None {
+            groups.sort_by(|a, b| a.0.to_lowercase().cmp(&b.0.to_lowercase()));
+        }
+
+        groups
+    }
+}
+
+struct ViewerState {
+    model_entities: Vec<Entity>,
+    camera_entity: Option<Entity>,
+    rotation_speed: f32,
+    auto_rotation: AutoRotation,
+    camera_bookmarks: CameraBookmarks,
+    fly_camera: FlyCamera,
+    view_presets: ViewPresets,
+    key_bindings: KeyBindings,
+    #[cfg(feature = "gamepad")]
+    gamepad: GamepadControls,
+    #[cfg(feature = "openxr")]
+    xr: XrInteraction,
+    loaded: bool,
+    previous_atmosphere: Atmosphere,
+    custom_skyboxes: Vec<CustomSkybox>,
+    selected_custom_skybox: Option<usize>,
+    drag_file_type: Option<String>,
+    sun_entity: Option<Entity>,
+    #[cfg(not(target_arch = "wasm32"))]
+    asset_library: AssetLibrary,
+    #[cfg(not(target_arch = "wasm32"))]
+    retarget: Option<RetargetSession>,
+    #[cfg(not(target_arch = "wasm32"))]
+    texture_sources: std::collections::HashMap<String, (Vec<u8>, u32, u32)>,
+    #[cfg(not(target_arch = "wasm32"))]
+    sequence_export: SequenceExport,
+    #[cfg(not(target_arch = "wasm32"))]
+    screenshot: ScreenshotOptions,
+    hint_text_entity: Option<Entity>,
+    hint_hide_time: u64,
+    instances: Vec<ModelInstance>,
+    spawned_instance_count: usize,
+    selected_instance: Option<usize>,
+    selected_entity: Option<Entity>,
+    hovered_hit: Option<RaycastHit>,
+    mesh_bvhs: std::collections::HashMap<String, MeshBvh>,
+    mesh_primitives: std::collections::HashMap<String, Vec<(usize, usize)>>,
+    authored_root_poses: std::collections::HashMap<Entity, NodePose>,
+    show_hierarchy: bool,
+    reveal_in_hierarchy: bool,
+    measurement: Measurement,
+    transform_gizmo: TransformGizmo,
+    scene_scale: f32,
+    scene_center: Vec3,
+    scale_reference: ScaleReference,
+    debug_overlays: DebugOverlays,
+    surface_debug: SurfaceDebug,
+    animation_players: Vec<Entity>,
+    selected_animation_player: Option<Entity>,
+    sync_animation_players: bool,
+    animation_timeline: AnimationTimeline,
+    morph_targets: MorphTargets,
+    animation_blend: AnimationBlend,
+}
+
+impl Default for ViewerState {
+    fn default() -> Self {
+        Self {
+            model_entities: Vec::new(),
+            camera_entity: None,
+            rotation_speed: 0.0,
+            auto_rotation: AutoRotation::default(),
+            camera_bookmarks: CameraBookmarks::default(),
+            fly_camera: FlyCamera::default(),
+            view_presets: ViewPresets::default(),
+            key_bindings: KeyBindings::default(),
+            #[cfg(feature = "gamepad")]
+            gamepad: GamepadControls::default(),
+            #[cfg(feature = "openxr")]
+            xr: XrInteraction::default(),
+            loaded: false,
+            previous_atmosphere: Atmosphere::Hdr,
+            custom_skyboxes: Vec::new(),
+            selected_custom_skybox: None,
+            drag_file_type: None,
+            sun_entity: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            asset_library: AssetLibrary::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            retarget: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            texture_sources: std::collections::HashMap::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            sequence_export: SequenceExport::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            screenshot: ScreenshotOptions::default(),
+            hint_text_entity: None,
+            hint_hide_time: 0,
+            instances: Vec::new(),
+            spawned_instance_count: 0,
+            selected_instance: None,
+            selected_entity: None,
+            hovered_hit: None,
+            mesh_bvhs: std::collections::HashMap::new(),
+            mesh_primitives: std::collections::HashMap::new(),
+            authored_root_poses: std::collections::HashMap::new(),
+            show_hierarchy: false,
+            reveal_in_hierarchy: false,
+            measurement: Measurement::default(),
+            transform_gizmo: TransformGizmo::default(),
+            scene_scale: 1.0,
+            scene_center: Vec3::zeros(),
+            scale_reference: ScaleReference::default(),
+            debug_overlays: DebugOverlays::default(),
+            surface_debug: SurfaceDebug::default(),
+            animation_players: Vec::new(),
+            selected_animation_player: None,
+            sync_animation_players: false,
+            animation_timeline: AnimationTimeline::default(),
+            morph_targets: MorphTargets::default(),
+            animation_blend: AnimationBlend::default(),
+        }
+    }
+}
+
+impl State for ViewerState {
+    fn title(&self) -> &str {
+        "glTF Viewer"
+    }
+
+    fn initialize(&mut self, world: &mut World) {
+        world.resources.user_interface.enabled = true;
+        world.resources.graphics.show_grid = false;
+        world.resources.graphics.atmosphere = Atmosphere::Hdr;
+        world.resources.graphics.use_fullscreen = false;
+
+        load_hdr_skybox(world, DEFAULT_HDR_BYTES.to_vec());
+
+        let sun = spawn_sun(world);
+        if let Some(light) = world.get_light_mut(sun) {
+            light.cast_shadows = true;
+        }
+        self.sun_entity = Some(sun);
+
+        self.rotation_speed = 0.0;
+
+        let camera_entity = spawn_pan_orbit_camera(
+            world,
+            Vec3::new(0.0, 0.0, 0.0),
+            3.0,
+            0.0,
+            0.3,
+            "Main Camera".to_string(),
+        );
+
+        self.camera_entity = Some(camera_entity);
+        world.resources.active_camera = Some(camera_entity);
+
+        self.load_gltf_from_bytes(world, "DamagedHelmet", DEFAULT_GLTF_BYTES);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        self.load_key_bindings();
+
+        let hint_properties = TextProperties {
+            font_size: 20.0,
+            color: Vec4::new(1.0, 1.0, 1.0, 0.7),
+            ..Default::default()
+        };
+        let hint_entity = spawn_hud_text_with_properties(
+            world,
+            &format!(
+                "{}: reset camera",
+                self.key_bindings.label(KeyAction::ResetCamera)
+            ),
+            HudAnchor::BottomCenter,
+            Vec2::new(0.0, 40.0),
+            hint_properties,
+        );
+        self.hint_text_entity = Some(hint_entity);
+        self.hint_hide_time = 5000;
+    }
+
+    fn run_systems(&mut self, world: &mut World) {
+        self.key_bindings_system(world);
+        #[cfg(feature = "gamepad")]
+        self.gamepad_system(world);
+        #[cfg(feature = "openxr")]
+        self.xr_system(world);
+        pan_orbit_camera_system(world);
+        self.gizmo_camera_lock_system(world);
+        self.fly_camera_system(world);
+        self.view_presets_system(world);
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.sequence_export_system(world) {
+            return;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        self.screenshot_system(world);
+        self.atmosphere_switch_system(world);
+        #[cfg(not(target_arch = "wasm32"))]
+        self.asset_cycle_system(world);
+        self.hint_hide_system(world);
+        self.keyboard_shortcuts_system(world);
+        self.camera_bookmarks_system(world);
+        self.animation_timeline_system(world);
+        self.animation_sync_system(world);
+        self.animation_blend_system(world);
+        self.morph_override_system(world);
+        self.scale_reference_system(world);
+        self.surface_debug_system(world);
+
+        self.auto_rotation_system(world);
+    }
+
+    fn on_dropped_file(&mut self, world: &mut World, path: &std::path::Path) {
+        if let Some(extension) = path.extension() {
+            let ext = extension.to_string_lossy().to_lowercase();
+            if ext == "hdr" {
+                self.load_hdr_skybox(world, path);
+            } else if ext == "gltf" || ext == "glb" {
+                self.load_gltf_from_path(world, path);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            if ext == "fbx" {
+                self.load_fbx(world, path);
+            }
+        }
+        self.drag_file_type = None;
+    }
+
+    fn on_dropped_file_data(&mut self, world: &mut World, name: &str, data: &[u8]) {
+        let lower_name = name.to_lowercase();
+        if lower_name.ends_with(".hdr") {
+            self.load_hdr_skybox_from_bytes(world, name, data);
+        } else if lower_name.ends_with(".gltf") || lower_name.ends_with(".glb") {
+            self.clear_scene(world);
+            self.load_gltf_from_bytes(world, name, data);
+        }
+        self.drag_file_type = None;
+    }
+
+    fn on_hovered_file(&mut self, _world: &mut World, path: &std::path::Path) {
+        if let Some(extension) = path.extension() {
+            let ext = extension.to_string_lossy().to_lowercase();
+            if ext == "hdr" {
+                self.drag_file_type = Some("HDR".to_string());
+            } else if ext == "gltf" || ext == "glb" {
+                self.drag_file_type = Some("glTF".to_string());
+            } else if ext == "fbx" {
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    self.drag_file_type = Some("FBX".to_string());
+                }
+                #[cfg(target_arch = "wasm32")]
+                {
+                    self.drag_file_type = Some("Unsupported".to_string());
+                }
+            } else {
+                self.drag_file_type = Some("Unsupported".to_string());
+            }
+        }
+    }
+
+    fn on_hovered_file_cancelled(&mut self, _world: &mut World) {
+        self.drag_file_type = None;
+    }
+
+    fn ui(&mut self, world: &mut World, ui_context: &egui::Context) {
+        #[cfg(feature = "gamepad")]
+        self.gamepad_navigation_ui(ui_context);
+
+        if self.drag_file_type.is_some() {
+            self.drop_indicator_ui(ui_context);
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        self.asset_library_ui(world, ui_context);
+        #[cfg(not(target_arch = "wasm32"))]
+        self.retarget_ui(world, ui_context);
+        self.key_bindings_ui(world, ui_context);
+        #[cfg(not(target_arch = "wasm32"))]
+        self.sequence_export_ui(world, ui_context);
+        #[cfg(not(target_arch = "wasm32"))]
+        self.sequence_progress_ui(ui_context);
+        #[cfg(not(target_arch = "wasm32"))]
+        self.screenshot_ui(world, ui_context);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        self.scene_drop_ui(world, ui_context);
+
+        self.hierarchy_ui(world, ui_context);
+
+        self.scale_reference_overlay_ui(world, ui_context);
+        self.debug_overlay_ui(world, ui_context);
+
+        self.transform_gizmo_ui(world, ui_context);
+        self.selection_ui(world, ui_context);
+        self.measurement_overlay_ui(world, ui_context);
+        self.timeline_ui(world, ui_context);
+
+        egui::Window::new("Settings")
+            .default_pos(egui::pos2(10.0, 10.0))
+            .default_width(300.0)
+            .show(ui_context, |ui| {
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    ui.horizontal(|ui| {
+                        if ui.button("Asset Library").clicked() {
+                            self.asset_library.show_window = true;
+                        }
+
+                        if ui
+                            .button("Take Screenshot")
+                            .on_hover_text("Save screenshot to screenshots/ folder")
+                            .clicked()
+                        {
+                            nightshade::ecs::world::capture_screenshot(world);
+                        }
+
+                        if ui
+                            .button("Screenshot...")
+                            .on_hover_text("High-resolution screenshot with transparency options")
+                            .clicked()
+                        {
+                            self.screenshot.show_window = true;
+                        }
+
+                        if ui
+                            .add_enabled(
+                                !self.model_entities.is_empty(),
+                                egui::Button::new("Render Sequence"),
+                            )
+                            .on_hover_text(
+                                "Render a turntable or animation range to frames or video",
+                            )
+                            .clicked()
+                        {
+                            self.sequence_export.show_window = true;
+                        }
+
+                        if ui
+                            .add_enabled(
+                                !self.model_entities.is_empty(),
+                                egui::Button::new("Export GLB"),
+                            )
+                            .on_hover_text(
+                                "Save the current scene with its animations as binary glTF",
+                            )
+                            .clicked()
+                        {
+                            let file_name = self
+                                .instances
+                                .first()
+                                .map(|instance| format!("{}.glb", instance.name))
+                                .unwrap_or_else(|| "scene.glb".to_string());
+                            if let Some(path) = rfd::FileDialog::new()
+                                .add_filter("Binary glTF", &["glb"])
+                                .set_file_name(&file_name)
+                                .save_file()
+                            {
+                                match self.export_glb(world, &path) {
+                                    Ok(()) => {
+                                        tracing::info!("Exported scene to {}", path.display())
+                                    }
+                                    Err(error) => {
+                                        tracing::error!("Failed to export GLB: {}", error)
+                                    }
+                                }
+                            }
+                        }
+                    });
+                    ui.separator();
+                }
+
+                if ui.button("Key Bindings...").clicked() {
+                    self.key_bindings.show_window = true;
+                }
+
+                #[cfg(feature = "openxr")]
+                self.xr_settings_ui(ui);
+
+                #[cfg(feature = "gamepad")]
+                self.gamepad_settings_ui(ui);
+
+                ui.collapsing("Skybox", |ui| {
+                    let current_is_default = self.selected_custom_skybox.is_none()
+                        && world.resources.graphics.atmosphere == Atmosphere::Hdr;
+
+                    if ui
+                        .selectable_label(current_is_default, "Default (Moonrise)")
+                        .clicked()
+                    {
+                        load_hdr_skybox(world, DEFAULT_HDR_BYTES.to_vec());
+                        world.resources.graphics.atmosphere = Atmosphere::Hdr;
+                        self.selected_custom_skybox = None;
+                    }
+
+                    for (index, skybox) in self.custom_skyboxes.iter().enumerate() {
+                        if skybox.path.as_os_str().is_empty() {
+                            continue;
+                        }
+                        let is_selected = self.selected_custom_skybox == Some(index);
+                        if ui.selectable_label(is_selected, &skybox.name).clicked() {
+                            load_hdr_skybox_from_path(world, skybox.path.clone());
+                            world.resources.graphics.atmosphere = Atmosphere::Hdr;
+                            self.selected_custom_skybox = Some(index);
+                        }
+                    }
+
+                    ui.separator();
+
+                    ui.label("Procedural Atmospheres:");
+                    for atmosphere in Atmosphere::ALL {
+                        if atmosphere.is_procedural() {
+                            let is_selected = world.resources.graphics.atmosphere == *atmosphere
+                                && self.selected_custom_skybox.is_none();
+                            if ui
+                                .selectable_label(is_selected, format!("{:?}", atmosphere))
+                                .clicked()
+                            {
+                                world.resources.graphics.atmosphere = *atmosphere;
+                                self.selected_custom_skybox = None;
+                                capture_procedural_atmosphere_ibl(world, *atmosphere, 0.0);
+                            }
+                        }
+                    }
+                });
+
+                ui.collapsing("Color Grading", |ui| {
+                    let color_grading = &mut world.resources.graphics.color_grading;
+
+                    ui.horizontal(|ui| {
+                        ui.label("Preset:");
+                        ui.label(color_grading.preset.name());
+                    });
+
+                    ui.horizontal_wrapped(|ui| {
+                        for preset in ColorGradingPreset::ALL {
+                            if *preset == ColorGradingPreset::Custom {
+                                continue;
+                            }
+                            let is_selected = color_grading.preset == *preset;
+                            if ui.selectable_label(is_selected, preset.name()).clicked() {
+                                *color_grading = preset.to_color_grading();
+                            }
+                        }
+                    });
+
+                    ui.add_space(4.0);
+
+                    ui.horizontal(|ui| {
+                        ui.label("Tonemap:");
+                        egui::ComboBox::from_id_salt("tonemap_algorithm")
+                            .selected_text(color_grading.tonemap_algorithm.name())
+                            .show_ui(ui, |ui| {
+                                for algorithm in TonemapAlgorithm::ALL {
+                                    if ui
+                                        .selectable_value(
+                                            &mut color_grading.tonemap_algorithm,
+                                            *algorithm,
+                                            algorithm.name(),
+                                        )
+                                        .changed()
+                                    {
+                                        color_grading.preset = ColorGradingPreset::Custom;
+                                    }
+                                }
+                            });
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label("Gamma:");
+                        if ui
+                            .add(
+                                egui::Slider::new(&mut color_grading.gamma, 1.0..=3.0)
+                                    .fixed_decimals(2),
+                            )
+                            .changed()
+                        {
+                            color_grading.preset = ColorGradingPreset::Custom;
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label("Saturation:");
+                        if ui
+                            .add(
+                                egui::Slider::new(&mut color_grading.saturation, 0.0..=2.0)
+                                    .fixed_decimals(2),
+                            )
+                            .changed()
+                        {
+                            color_grading.preset = ColorGradingPreset::Custom;
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label("Brightness:");
+                        if ui
+                            .add(
+                                egui::Slider::new(&mut color_grading.brightness, -0.5..=0.5)
+                                    .fixed_decimals(2),
+                            )
+                            .changed()
+                        {
+                            color_grading.preset = ColorGradingPreset::Custom;
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label("Contrast:");
+                        if ui
+                            .add(
+                                egui::Slider::new(&mut color_grading.contrast, 0.5..=2.0)
+                                    .fixed_decimals(2),
+                            )
+                            .changed()
+                        {
+                            color_grading.preset = ColorGradingPreset::Custom;
+                        }
+                    });
+                });
+
+                ui.collapsing("Model", |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Rotation Speed:");
+                        ui.add(
+                            egui::Slider::new(&mut self.rotation_speed, 0.0..=2.0)
+                                .fixed_decimals(2)
+                                .suffix(" rad/s"),
+                        );
+                    });
+
+                    let rotation = &mut self.auto_rotation;
+                    let mut changed = false;
+                    ui.horizontal(|ui| {
+                        ui.label("Axis:");
+                        for axis in GizmoAxis::ALL {
+                            let enabled = !rotation.rotate_camera || axis != GizmoAxis::Z;
+                            ui.add_enabled_ui(enabled, |ui| {
+                                changed |= ui
+                                    .selectable_value(
+                                        &mut rotation.axis,
+                                        axis,
+                                        format!("{:?}", axis),
+                                    )
+                                    .changed();
+                            });
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Pivot:");
+                        changed |= ui
+                            .selectable_value(
+                                &mut rotation.pivot,
+                                RotationPivot::RootOrigins,
+                                "Model Origin",
+                            )
+                            .changed();
+                        changed |= ui
+                            .selectable_value(
+                                &mut rotation.pivot,
+                                RotationPivot::BoundsCenter,
+                                "Bounds Center",
+                            )
+                            .changed();
+                    });
+                    if ui
+                        .checkbox(&mut rotation.rotate_camera, "Rotate Camera Instead")
+                        .changed()
+                    {
+                        if rotation.rotate_camera && rotation.axis == GizmoAxis::Z {
+                            rotation.axis = GizmoAxis::Y;
+                        }
+                        changed = true;
+                    }
+                    ui.horizontal(|ui| {
+                        changed |= ui.checkbox(&mut rotation.ping_pong, "Ping-Pong").changed();
+                        ui.add_enabled(
+                            rotation.ping_pong,
+                            egui::DragValue::new(&mut rotation.ping_pong_degrees)
+                                .speed(1.0)
+                                .range(1.0..=180.0)
+                                .prefix("±")
+                                .suffix("°"),
+                        );
+                    });
+                    if changed {
+                        rotation.reset();
+                    }
+
+                    if ui.button("Reset Camera").clicked() {
+                        self.reset_camera(world);
+                    }
+
+                    ui.separator();
+
+                    let mut true_scale = self.scale_reference.true_scale;
+                    if ui
+                        .checkbox(&mut true_scale, "True Scale")
+                        .on_hover_text("Keep the authored scale and origin instead of auto-fitting")
+                        .changed()
+                    {
+                        self.set_true_scale(world, true_scale);
+                    }
+
+                    if self.scale_reference.true_scale {
+                        ui.checkbox(&mut self.scale_reference.show_grid, "Metric Grid");
+                        ui.checkbox(
+                            &mut self.scale_reference.show_human,
+                            "Human Reference (1.8 m)",
+                        );
+                        ui.checkbox(&mut self.scale_reference.show_origin, "Origin Marker");
+                    }
+                });
+
+                self.camera_ui(world, ui);
+
+                self.camera_bookmarks_ui(world, ui);
+
+                self.instances_ui(world, ui);
+
+                self.transform_ui(world, ui);
+
+                self.measurement_ui(world, ui);
+
+                self.animation_ui(world, ui);
+
+                self.morph_targets_ui(world, ui);
+
+                ui.collapsing("Debug", |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("PBR Debug:");
+                        egui::ComboBox::from_id_salt("pbr_debug")
+                            .selected_text(world.resources.graphics.pbr_debug_mode.name())
+                            .show_ui(ui, |ui| {
+                                for mode in PbrDebugMode::ALL {
+                                    ui.selectable_value(
+                                        &mut world.resources.graphics.pbr_debug_mode,
+                                        *mode,
+                                        mode.name(),
+                                    );
+                                }
+                            });
+                    });
+
+                    self.surface_debug_settings_ui(ui);
+
+                    ui.horizontal(|ui| {
+                        ui.label("Texture Stripes:");
+                        ui.checkbox(
+                            &mut world.resources.graphics.texture_debug_stripes,
+                            "Enabled",
+                        );
+                    });
+
+                    if world.resources.graphics.texture_debug_stripes {
+                        ui.horizontal(|ui| {
+                            ui.label("Stripe Speed:");
+                            ui.add(
+                                egui::Slider::new(
+                                    &mut world.resources.graphics.texture_debug_stripes_speed,
+                                    0.0..=500.0,
+                                )
+                                .suffix(" px/s"),
+                            );
+                        });
+                    }
+
+                    ui.checkbox(&mut world.resources.graphics.show_grid, "Show Grid");
+
+                    ui.separator();
+
+                    let overlays = &mut self.debug_overlays;
+                    ui.label("Overlays:");
+                    ui.checkbox(&mut overlays.node_bounds, "Node OBBs");
+                    ui.checkbox(&mut overlays.scene_bounds, "Scene AABB");
+                    ui.checkbox(&mut overlays.skeleton, "Skeleton");
+                    ui.checkbox(&mut overlays.wireframe, "Wireframe");
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut overlays.normals, "Normals");
+                        ui.checkbox(&mut overlays.tangents, "Tangents");
+                    });
+                    if overlays.normals || overlays.tangents {
+                        ui.horizontal(|ui| {
+                            ui.label("Vector Length:");
+                            ui.add(
+                                egui::Slider::new(&mut overlays.vector_length, 0.001..=0.5)
+                                    .logarithmic(true),
+                            );
+                        });
+                    }
+                    if overlays.wireframe || overlays.normals || overlays.tangents {
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "Drawn over the model without depth testing. Wireframe shows at \
+                                 most {} front-facing triangles, normals and tangents at most {} \
+                                 vertices.",
+                                DEBUG_MAX_WIREFRAME_TRIANGLES, DEBUG_MAX_VECTORS
+                            ))
+                            .small()
+                            .weak(),
+                        );
+                        if overlays.limit_reached {
+                            ui.label(
+                                egui::RichText::new("Limit reached; some geometry is not shown")
+                                    .small()
+                                    .color(egui::Color32::from_rgb(255, 170, 50)),
+                            );
+                        }
+                    }
+                });
+            });
+    }
+}
+
+impl ViewerState {
+    fn drop_indicator_ui(&self, ui_context: &egui::Context) {
+        egui::Area::new(egui::Id::new("drop_indicator"))
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ui_context, |ui| {
+                let frame = egui::Frame::default()
+                    .fill(egui::Color32::from_rgba_premultiplied(30, 30, 30, 220))
+                    .corner_radius(12.0)
+                    .stroke(egui::Stroke::new(
+                        2.0,
+                        egui::Color32::from_rgb(100, 150, 250),
+                    ))
+                    .inner_margin(40.0);
+
+                frame.show(ui, |ui| {
+                    if let Some(file_type) = &self.drag_file_type {
+                        match file_type.as_str() {
+                            "HDR" => {
+                                ui.heading("Drop HDR file to use as skybox");
+                            }
+                            "glTF" => {
+                                ui.heading("Drop glTF/GLB file to load model");
+                            }
+                            "FBX" => {
+                                ui.heading("Drop FBX file to add animations");
+                            }
+                            _ => {
+                                ui.heading("Unsupported file type");
+                            }
+                        }
+                    }
+                });
+            });
+    }
+
+    fn refresh_animation_state(&mut self, world: &World) {
+        self.animation_players = collect_global_matrices(world, &self.model_entities)
+            .into_iter()
+            .map(|(entity, _, _)| entity)
+            .filter(|entity| world.entity_has_animation_player(*entity))
+            .collect();
+        if self
+            .selected_animation_player
+            .is_some_and(|entity| !self.animation_players.contains(&entity))
+        {
+            self.selected_animation_player = None;
+        }
+        let players = &self.animation_players;
+        self.animation_blend
+            .players
+            .retain(|entity, _| players.contains(entity));
+        for entity in &self.animation_players {
+            self.animation_blend
+                .capture_rest_pose(world, *entity, &self.authored_root_poses);
+        }
+    }
+
+    fn animation_entity(&self, world: &World) -> Option<Entity> {
+        self.selected_animation_player
+            .or_else(|| self.animation_players.first().copied())
+            .filter(|entity| world.entity_has_animation_player(*entity))
+    }
+
+    fn select_animation_player(&mut self, world: &mut World, entity: Entity) {
+        if let Some(previous) = self.animation_entity(world)
+            && previous != entity
+        {
+            if self
+                .animation_blend
+                .player(previous)
+                .is_some_and(|state| state.preview)
+            {
+                self.set_blend_preview(world, previous, false);
+            }
+            self.animation_timeline.clear_markers();
+        }
+        self.selected_animation_player = Some(entity);
+    }
+
+    fn animation_players_ui(&mut self, world: &mut World, ui: &mut egui::Ui) {
+        if self.animation_players.len() < 2 {
+            return;
+        }
+
+        let active = self.animation_entity(world);
+        let mut selected = None;
+        let mut toggled = None;
+
+        ui.label(format!(
+            "{} animation players",
+            self.animation_players.len()
+        ));
+        egui::ScrollArea::vertical()
+            .id_salt("animation_players")
+            .max_height(160.0)
+            .show(ui, |ui| {
+                for entity in &self.animation_players {
+                    let Some(player) = world.get_animation_player(*entity) else {
+                        continue;
+                    };
+                    let clip_name = player
+                        .current_clip
+                        .and_then(|index| player.clips.get(index))
+                        .map(|clip| clip.name.as_str())
+                        .unwrap_or("None");
+
+                    ui.horizontal(|ui| {
+                        if ui.button(if player.playing { "⏸" } else { "▶" }).clicked() {
+                            toggled = Some(*entity);
+                        }
+                        let label =
+                            format!("{} ({})", entity_display_name(world, *entity), clip_name);
+                        if ui
+                            .selectable_label(active == Some(*entity), label)
+                            .clicked()
+                        {
+                            selected = Some(*entity);
+                        }
+                    });
+                }
+            });
+
+        ui.checkbox(&mut self.sync_animation_players, "Sync All Players")
+            .on_hover_text("Drive every player from the selected one, matching clips by name");
+        ui.separator();
+
+        if let Some(entity) = selected {
+            self.select_animation_player(world, entity);
+        }
+        if let Some(entity) = toggled {
+            let targets = if self.sync_animation_players {
+                self.animation_players.clone()
+            } else {
+                vec![entity]
+            };
+            let play = world
+                .get_animation_player(entity)
+                .is_some_and(|player| !player.playing);
+            for target in targets {
+                if let Some(player) = world.get_animation_player_mut(target) {
+                    if play {
+                        player.resume();
+                    } else {
+                        player.pause();
+                    }
+                }
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn animation_import_targets(&self, world: &World, animations: &[AnimationClip]) -> Vec<Entity> {
+        let target_nodes: std::collections::HashSet<&str> = animations
+            .iter()
+            .flat_map(|clip| clip.channels.iter())
+            .map(|channel| channel.target_node.as_str())
+            .collect();
+
+        let matching: Vec<Entity> = self
+            .animation_players
+            .iter()
+            .copied()
+            .filter(|entity| {
+                collect_global_matrices(world, &[*entity])
+                    .iter()
+                    .any(|(node, _, _)| {
+                        world
+                            .get_name(*node)
+                            .is_some_and(|name| target_nodes.contains(name.0.as_str()))
+                    })
+            })
+            .collect();
+
+        matching
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn retarget_ui(&mut self, world: &mut World, ui_context: &egui::Context) {
+        let Some(session) = &mut self.retarget else {
+            return;
+        };
+
+        let mut open = true;
+        let mut apply = false;
+        let mut cancel = false;
+
+        egui::Window::new("Retarget Animation")
+            .open(&mut open)
+            .default_width(420.0)
+            .default_height(520.0)
+            .show(ui_context, |ui| {
+                ui.label(format!(
+                    "{} clip(s), {} source bones → {}",
+                    session.clips.len(),
+                    session.source_bones.len(),
+                    entity_display_name(world, session.target_player)
+                ));
+
+                let mut presets_changed = false;
+                ui.horizontal(|ui| {
+                    for (label, id, preset) in [
+                        ("Source:", "retarget_source_preset", &mut session.profile.source_preset),
+                        ("Target:", "retarget_target_preset", &mut session.profile.target_preset),
+                    ] {
+                        ui.label(label);
+                        egui::ComboBox::from_id_salt(id)
+                            .selected_text(preset.name())
+                            .show_ui(ui, |ui| {
+                                for option in SkeletonPreset::ALL {
+                                    presets_changed |= ui
+                                        .selectable_value(preset, option, option.name())
+                                        .changed();
+                                }
+                            });
+                    }
+                });
+                if presets_changed {
+                    session.apply_presets();
+                    session.scale = session.auto_scale();
+                }
+
+                ui.add_enabled(
+                    !session.source_rest.is_empty(),
+                    egui::Checkbox::new(
+                        &mut session.profile.rest_pose_correction,
+                        "Rest-Pose Correction",
+                    ),
+                )
+                .on_hover_text(
+                    "Rebase rotations from the source skeleton's rest pose onto the target rest pose",
+                )
+                .on_disabled_hover_text(
+                    "The source file has no skeleton, so its rest pose is unknown",
+                );
+                ui.checkbox(
+                    &mut session.profile.root_translation_only,
+                    "Root Translation Only",
+                );
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut session.profile.scale_compensation, "Scale Compensation");
+                    ui.add_enabled(
+                        session.profile.scale_compensation,
+                        egui::DragValue::new(&mut session.scale)
+                            .speed(0.001)
+                            .range(0.0001..=1000.0)
+                            .max_decimals(4),
+                    );
+                    if ui.button("Auto").clicked() {
+                        session.scale = session.auto_scale();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Root Bone:");
+                    let mut root_changed = false;
+                    egui::ComboBox::from_id_salt("retarget_root_bone")
+                        .selected_text(session.profile.root_bone.as_deref().unwrap_or("None"))
+                        .show_ui(ui, |ui| {
+                            root_changed |= ui
+                                .selectable_value(&mut session.profile.root_bone, None, "None")
+                                .changed();
+                            for bone in &session.source_bones {
+                                root_changed |= ui
+                                    .selectable_value(
+                                        &mut session.profile.root_bone,
+                                        Some(bone.clone()),
+                                        bone,
+                                    )
+                                    .changed();
+                            }
+                        });
+                    if root_changed {
+                        session.scale = session.auto_scale();
+                    }
+                });
+
+                ui.separator();
+                let mapped = session
+                    .source_bones
+                    .iter()
+                    .filter(|bone| session.profile.mapping.contains_key(*bone))
+                    .count();
+                ui.label(format!(
+                    "{} of {} bones mapped (unmapped bones are dropped)",
+                    mapped,
+                    session.source_bones.len()
+                ));
+
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("retarget_mapping")
+                            .num_columns(2)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for (index, source) in session.source_bones.iter().enumerate() {
+                                    let target = session.profile.mapping.get(source).cloned();
+                                    let label = egui::RichText::new(source);
+                                    ui.label(if target.is_some() {
+                                        label
+                                    } else {
+                                        label.color(egui::Color32::from_rgb(230, 150, 80))
+                                    });
+
+                                    let mut selected = target.clone();
+                                    egui::ComboBox::from_id_salt(("retarget_bone", index))
+                                        .width(180.0)
+                                        .selected_text(target.as_deref().unwrap_or("Unmapped"))
+                                        .show_ui(ui, |ui| {
+                                            ui.selectable_value(&mut selected, None, "Unmapped");
+                                            for bone in &session.target_bones {
+                                                ui.selectable_value(
+                                                    &mut selected,
+                                                    Some(bone.clone()),
+                                                    bone,
+                                                );
+                                            }
+                                        });
+                                    if selected != target {
+                                        match selected {
+                                            Some(bone) => {
+                                                session.profile.mapping.insert(source.clone(), bone);
+                                            }
+                                            None => {
+                                                session.profile.mapping.remove(source);
+                                            }
+                                        }
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Apply").clicked() {
+                        apply = true;
+                    }
+                    if ui.button("Save Mapping").clicked() {
+                        session.save_profile();
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if apply {
+            session.save_profile();
+            let clips = session.retargeted_clips();
+            let count = clips.len();
+            if let Some(player) = world.get_animation_player_mut(session.target_player) {
+                player.add_clips(clips);
+                if player.current_clip.is_none() && !player.clips.is_empty() {
+                    player.play(0);
+                }
+                tracing::info!("Added {} retargeted animation(s)", count);
+            }
+        }
+        if apply || cancel || !open {
+            self.retarget = None;
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn open_retarget(
+        &mut self,
+        world: &World,
+        clips: Vec<AnimationClip>,
+        source_rest: std::collections::HashMap<String, NodePose>,
+    ) {
+        let Some(entity) = self.animation_entity(world) else {
+            tracing::warn!("Model does not have an AnimationPlayer component");
+            return;
+        };
+        let state =
+            self.animation_blend
+                .capture_rest_pose(world, entity, &self.authored_root_poses);
+        let session = RetargetSession::new(world, entity, clips, source_rest, &state.rest_pose);
+        self.retarget = Some(session);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pick_retarget_source(&mut self, world: &World) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("FBX", &["fbx"])
+            .pick_file()
+        else {
+            return;
+        };
+        match nightshade::ecs::prefab::import_fbx_from_path(&path) {
+            Ok(result) if result.animations.is_empty() => {
+                tracing::warn!("{} contains no animations", path.display());
+            }
+            Ok(result) => {
+                let source_rest = prefab_rest_pose(&result.prefabs);
+                self.open_retarget(world, result.animations, source_rest);
+            }
+            Err(error) => tracing::error!("Failed to load FBX {}: {}", path.display(), error),
+        }
+    }
+
+    fn animation_sync_system(&mut self, world: &mut World) {
+        if !self.sync_animation_players {
+            return;
+        }
+        let Some(primary) = self.animation_entity(world) else {
+            return;
+        };
+        let Some(player) = world.get_animation_player(primary) else {
+            return;
+        };
+        // A crossfading or previewing player has no current clip of its own; follow the
+        // clip its blend state is heading to instead, and leave previews alone.
+        let primary_blend = self.animation_blend.player(primary);
+        if primary_blend.is_some_and(|state| state.preview) {
+            return;
+        }
+        let Some((clip, time)) = primary_blend
+            .and_then(|state| state.crossfade.as_ref())
+            .map(|crossfade| (crossfade.to_clip, crossfade.to_time))
+            .or_else(|| player.current_clip.map(|clip| (clip, player.time)))
+        else {
+            return;
+        };
+        let Some(clip_name) = player.clips.get(clip).map(|clip| clip.name.clone()) else {
+            return;
+        };
+        let (speed, looping, playing) = (player.speed, player.looping, player.playing);
+
+        for entity in self.animation_players.clone() {
+            if entity == primary
+                || self
+                    .animation_blend
+                    .player(entity)
+                    .is_some_and(|state| state.preview)
+            {
+                continue;
+            }
+            let Some(other) = world.get_animation_player(entity) else {
+                continue;
+            };
+            let Some(index) = other.clips.iter().position(|clip| clip.name == clip_name) else {
+                continue;
+            };
+            let target = self
+                .animation_blend
+                .player(entity)
+                .and_then(|state| state.crossfade.as_ref())
+                .map(|crossfade| crossfade.to_clip)
+                .or(other.current_clip);
+            if target != Some(index) {
+                self.play_clip(world, entity, index);
+            }
+
+            let Some(other) = world.get_animation_player_mut(entity) else {
+                continue;
+            };
+            let duration = other.clips[index].duration.max(f32::EPSILON);
+            let time = if looping {
+                wrap_clip_time(time, duration)
+            } else {
+                time.min(duration)
+            };
+            match self
+                .animation_blend
+                .players
+                .get_mut(&entity)
+                .and_then(|state| state.crossfade.as_mut())
+            {
+                Some(crossfade) => crossfade.to_time = time,
+                None => other.time = time,
+            }
+            other.speed = speed;
+            other.looping = looping;
+            if other.playing != playing {
+                if playing {
+                    other.resume();
+                } else {
+                    other.pause();
+                }
+            }
+        }
+    }
+
+    fn animation_ui(&mut self, world: &mut World, ui: &mut egui::Ui) {
+        if self.animation_entity(world).is_none() {
+            return;
+        }
+
+        ui.collapsing("Animation", |ui| {
+            self.animation_players_ui(world, ui);
+            self.animation_controls_ui(world, ui);
+        });
+    }
+
+    fn animation_controls_ui(&mut self, world: &mut World, ui: &mut egui::Ui) {
+        let Some(entity) = self.animation_entity(world) else {
+            return;
+        };
+
+        let mut clip_to_play = None;
+        let mut clear_animations = false;
+        #[cfg(not(target_arch = "wasm32"))]
+        let mut retarget = false;
+        let mut preview_toggled = None;
+        let (blending, previewing) = self
+            .animation_blend
+            .player(entity)
+            .map(|state| (state.crossfade.is_some(), state.preview))
+            .unwrap_or_default();
+        let timeline = &mut self.animation_timeline;
+        let blend = &mut self.animation_blend;
+
+        ui.vertical(|ui| {
+            if let Some(player) = world.get_animation_player_mut(entity) {
+                if player.clips.is_empty() {
+                    ui.label("No animations (drop FBX to add)");
+                    return;
+                }
+
+                ui.label(format!("{} clip(s) loaded", player.clips.len()));
+
+                ui.horizontal(|ui| {
+                    ui.label("Clip:");
+                    egui::ComboBox::from_id_salt("animation_clip")
+                        .selected_text(
+                            player
+                                .current_clip
+                                .and_then(|index| player.clips.get(index))
+                                .map(|clip| clip.name.as_str())
+                                .unwrap_or("None"),
+                        )
+                        .show_ui(ui, |ui| {
+                            for (index, clip) in player.clips.iter().enumerate() {
+                                let is_selected = player.current_clip == Some(index);
+                                if ui.selectable_label(is_selected, &clip.name).clicked() {
+                                    clip_to_play = Some(index);
+                                }
+                            }
+                        });
+                });
+
+                if let Some(clip_index) = player.current_clip
+                    && let Some(clip) = player.clips.get(clip_index)
+                {
+                    ui.label(format!("Duration: {:.2}s", clip.duration));
+
+                    ui.horizontal(|ui| {
+                        ui.label("Time:");
+                        ui.add(
+                            egui::Slider::new(&mut player.time, 0.0..=clip.duration)
+                                .fixed_decimals(2)
+                                .suffix("s"),
+                        );
+                    });
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Speed:");
+                    ui.add(
+                        egui::DragValue::new(&mut player.speed)
+                            .speed(0.1)
+                            .range(-5.0..=5.0)
+                            .fixed_decimals(1),
+                    );
+                });
+
+                ui.checkbox(&mut player.looping, "Loop");
+
+                ui.horizontal(|ui| {
+                    ui.label("Crossfade:");
+                    ui.add(
+                        egui::DragValue::new(&mut blend.crossfade_duration)
+                            .speed(0.01)
+                            .range(0.0..=5.0)
+                            .fixed_decimals(2)
+                            .suffix("s"),
+                    )
+                    .on_hover_text("Transition time when switching clips (0 cuts instantly)");
+                    if blending {
+                        ui.label("Blending...");
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Frame Rate:");
+                    ui.add(
+                        egui::DragValue::new(&mut timeline.frame_rate)
+                            .speed(1.0)
+                            .range(1.0..=240.0)
+                            .suffix(" fps"),
+                    );
+                    ui.checkbox(&mut timeline.show_window, "Show Timeline");
+                });
+
+                ui.horizontal(|ui| {
+                    if player.playing {
+                        if ui.button("Pause").clicked() {
+                            player.pause();
+                        }
+                    } else if ui.button("Play").clicked() {
+                        player.resume();
+                    }
+
+                    if ui.button("Stop").clicked() {
+                        player.stop();
+                    }
+                });
+
+                ui.collapsing("Blend Preview", |ui| {
+                    let mut preview = previewing;
+                    if ui.checkbox(&mut preview, "Enabled").changed() {
+                        preview_toggled = Some(preview);
+                    }
+
+                    let state = blend.players.entry(entity).or_default();
+                    let clip_names: Vec<&str> =
+                        player.clips.iter().map(|clip| clip.name.as_str()).collect();
+                    let clip_combo = |ui: &mut egui::Ui, id: &str, index: &mut usize| {
+                        egui::ComboBox::from_id_salt(id)
+                            .selected_text(clip_names.get(*index).copied().unwrap_or("None"))
+                            .show_ui(ui, |ui| {
+                                for (clip_index, name) in clip_names.iter().enumerate() {
+                                    ui.selectable_value(index, clip_index, *name);
+                                }
+                            });
+                    };
+
+                    ui.horizontal(|ui| {
+                        ui.label("Clip A:");
+                        clip_combo(ui, "blend_clip_a", &mut state.clip_a);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Clip B:");
+                        clip_combo(ui, "blend_clip_b", &mut state.clip_b);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Weight:");
+                        ui.add(egui::Slider::new(&mut blend.weight, 0.0..=1.0).text("A → B"));
+                    });
+                    ui.checkbox(&mut blend.sync_phase, "Sync Phase")
+                        .on_hover_text("Stretch clip B to the length of clip A");
+
+                    ui.separator();
+
+                    ui.horizontal(|ui| {
+                        ui.label("Additive:");
+                        egui::ComboBox::from_id_salt("blend_additive_clip")
+                            .selected_text(
+                                state
+                                    .additive_clip
+                                    .and_then(|index| clip_names.get(index).copied())
+                                    .unwrap_or("None"),
+                            )
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut state.additive_clip, None, "None");
+                                for (clip_index, name) in clip_names.iter().enumerate() {
+                                    ui.selectable_value(
+                                        &mut state.additive_clip,
+                                        Some(clip_index),
+                                        *name,
+                                    );
+                                }
+                            });
+                    });
+                    if state.additive_clip.is_some() {
+                        ui.horizontal(|ui| {
+                            ui.label("Additive Weight:");
+                            ui.add(egui::Slider::new(&mut blend.additive_weight, 0.0..=1.0));
+                        });
+                    }
+                });
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    if ui.button("Clear Animations").clicked() {
+                        clear_animations = true;
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if ui
+                        .button("Retarget...")
+                        .on_hover_text("Map animations from another skeleton onto this model")
+                        .clicked()
+                    {
+                        retarget = true;
+                    }
+                });
+            }
+        });
+
+        if let Some(enabled) = preview_toggled {
+            self.set_blend_preview(world, entity, enabled);
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if retarget {
+            self.pick_retarget_source(world);
+        }
+
+        if let Some(index) = clip_to_play {
+            self.play_clip(world, entity, index);
+            self.animation_timeline.clear_markers();
+        }
+
+        if clear_animations && let Some(player) = world.get_animation_player_mut(entity) {
+            self.animation_blend.players.remove(&entity);
+            player.clips.clear();
+            player.current_clip = None;
+            player.playing = false;
+            player.time = 0.0;
+            self.animation_timeline.clear_markers();
+        }
+    }
+
+    fn play_clip(&mut self, world: &mut World, entity: Entity, index: usize) {
+        if self
+            .animation_blend
+            .player(entity)
+            .is_some_and(|state| state.preview)
+        {
+            if let Some(state) = self.animation_blend.players.get_mut(&entity) {
+                state.clip_a = index;
+            }
+            return;
+        }
+
+        let crossfade_duration = self.animation_blend.crossfade_duration;
+        let state =
+            self.animation_blend
+                .capture_rest_pose(world, entity, &self.authored_root_poses);
+        let Some(player) = world.get_animation_player_mut(entity) else {
+            return;
+        };
+        let from = state
+            .crossfade
+            .take()
+            .map(|crossfade| (crossfade.to_clip, crossfade.to_time))
+            .or_else(|| player.current_clip.map(|clip| (clip, player.time)));
+
+        match from {
+            Some((from_clip, from_time)) if crossfade_duration > 0.0 && from_clip != index => {
+                player.current_clip = None;
+                player.playing = true;
+                state.crossfade = Some(Crossfade {
+                    from_clip,
+                    from_time,
+                    to_clip: index,
+                    to_time: 0.0,
+                    elapsed: 0.0,
+                });
+            }
+            _ => player.play(index),
+        }
+    }
+
+    fn set_blend_preview(&mut self, world: &mut World, entity: Entity, enabled: bool) {
+        let state =
+            self.animation_blend
+                .capture_rest_pose(world, entity, &self.authored_root_poses);
+        let Some(player) = world.get_animation_player_mut(entity) else {
+            return;
+        };
+
+        if enabled {
+            let current = state
+                .crossfade
+                .take()
+                .map(|crossfade| (crossfade.to_clip, crossfade.to_time))
+                .or_else(|| player.current_clip.map(|clip| (clip, player.time)));
+            player.current_clip = None;
+            state.preview = true;
+            if let Some((clip, time)) = current {
+                state.time = time;
+                state.clip_a = clip;
+            }
+        } else {
+            state.preview = false;
+            let (time, clip_a) = (state.time, state.clip_a);
+            let playing = player.playing;
+            if clip_a < player.clips.len() {
+                player.play(clip_a);
+                player.time = time % player.clips[clip_a].duration.max(f32::EPSILON);
+                if !playing {
+                    player.pause();
+                }
+            }
+        }
+    }
+
+    fn animation_blend_system(&mut self, world: &mut World) {
+        let delta_time = world.resources.window.timing.delta_time;
+        let blend = &mut self.animation_blend;
+        blend
+            .players
+            .retain(|entity, _| world.entity_has_animation_player(*entity));
+
+        for (entity, state) in &mut blend.players {
+            if !state.is_driving_pose() {
+                continue;
+            }
+            let Some(player) = world.get_animation_player_mut(*entity) else {
+                continue;
+            };
+            let step = if player.playing {
+                delta_time * player.speed
+            } else {
+                0.0
+            };
+
+            let (pose, animated) = if let Some(crossfade) = &mut state.crossfade {
+                let (Some(from), Some(to)) = (
+                    player.clips.get(crossfade.from_clip),
+                    player.clips.get(crossfade.to_clip),
+                ) else {
+                    state.crossfade = None;
+                    continue;
+                };
+
+                let animated = animated_properties([from, to], &state.nodes);
+                crossfade.elapsed += delta_time;
+                crossfade.from_time = wrap_clip_time(crossfade.from_time + step, from.duration);
+                crossfade.to_time = wrap_clip_time(crossfade.to_time + step, to.duration);
+                let factor =
+                    (crossfade.elapsed / blend.crossfade_duration.max(f32::EPSILON)).min(1.0);
+
+                let pose = blend_poses(
+                    &sample_clip_pose(from, crossfade.from_time, &state.nodes, &state.rest_pose),
+                    &sample_clip_pose(to, crossfade.to_time, &state.nodes, &state.rest_pose),
+                    &state.rest_pose,
+                    factor,
+                );
+
+                if factor >= 1.0 {
+                    let (to_clip, to_time) = (crossfade.to_clip, crossfade.to_time);
+                    state.crossfade = None;
+                    let playing = player.playing;
+                    player.play(to_clip);
+                    player.time = to_time;
+                    if !playing {
+                        player.pause();
+                    }
+                }
+                (pose, animated)
+            } else {
+                let (Some(clip_a), Some(clip_b)) = (
+                    player.clips.get(state.clip_a),
+                    player.clips.get(state.clip_b),
+                ) else {
+                    continue;
+                };
+
+                state.time = wrap_clip_time(state.time + step, clip_a.duration);
+                let time_b = if blend.sync_phase {
+                    state.time / clip_a.duration.max(f32::EPSILON) * clip_b.duration
+                } else {
+                    wrap_clip_time(state.time, clip_b.duration)
+                };
+
+                let mut animated = animated_properties([clip_a, clip_b], &state.nodes);
+                let mut pose = blend_poses(
+                    &sample_clip_pose(clip_a, state.time, &state.nodes, &state.rest_pose),
+                    &sample_clip_pose(clip_b, time_b, &state.nodes, &state.rest_pose),
+                    &state.rest_pose,
+                    blend.weight,
+                );
+
+                if let Some(additive) = state
+                    .additive_clip
+                    .and_then(|index| player.clips.get(index))
+                {
+                    for (node, properties) in animated_properties([additive], &state.nodes) {
+                        let entry = animated.entry(node).or_default();
+                        for (animated, property) in entry.iter_mut().zip(properties) {
+                            *animated |= property;
+                        }
+                    }
+                    let additive_time = wrap_clip_time(state.time, additive.duration);
+                    apply_additive_pose(
+                        &mut pose,
+                        &sample_clip_pose(additive, 0.0, &state.nodes, &state.rest_pose),
+                        &sample_clip_pose(additive, additive_time, &state.nodes, &state.rest_pose),
+                        &state.rest_pose,
+                        blend.additive_weight,
+                    );
+                }
+                (pose, animated)
+            };
+
+            // Roots are written whole since their placement mixes every property; other
+            // nodes keep whatever the clips do not animate, such as gizmo edits.
+            for (node, node_pose) in pose {
+                let (node_pose, [translation, rotation, scale]) =
+                    match state.placements.get_mut(&node) {
+                        Some(placement) => (
+                            placement.place(world.get_local_transform(node), node_pose),
+                            [true; 3],
+                        ),
+                        None => (node_pose, animated.get(&node).copied().unwrap_or_default()),
+                    };
+                if let Some(transform) = world.get_local_transform_mut(node) {
+                    if translation {
+                        transform.translation = node_pose.translation;
+                    }
+                    if rotation {
+                        transform.rotation = node_pose.rotation;
+                    }
+                    if scale {
+                        transform.scale = node_pose.scale;
+                    }
+                    world.mark_local_transform_dirty(node);
+                }
+            }
+        }
+    }
+
+    fn animation_timeline_system(&mut self, world: &mut World) {
+        let just_pressed: Vec<KeyAction> = self.key_bindings.pressed.clone();
+
+        let Some(entity) = self.animation_entity(world) else {
+            return;
+        };
+
+        let wants_keyboard = world
+            .resources
+            .user_interface
+            .state
+            .as_ref()
+            .is_some_and(|gui_state| gui_state.egui_ctx().wants_keyboard_input());
+        if !wants_keyboard {
+            for action in just_pressed {
+                match action {
+                    KeyAction::PlayPause => {
+                        if let Some(player) = world.get_animation_player_mut(entity) {
+                            if player.playing {
+                                player.pause();
+                            } else {
+                                player.resume();
+                            }
+                        }
+                    }
+                    KeyAction::PreviousFrame => self.step_animation_frame(world, entity, -1),
+                    KeyAction::NextFrame => self.step_animation_frame(world, entity, 1),
+                    KeyAction::JumpToStart | KeyAction::JumpToEnd => {
+                        if let Some(player) = world.get_animation_player_mut(entity)
+                            && let Some(duration) = current_clip_duration(player)
+                        {
+                            let (start, end) = self.animation_timeline.playback_range(duration);
+                            player.time = if action == KeyAction::JumpToStart {
+                                start
+                            } else {
+                                end
+                            };
+                        }
+                    }
+                    KeyAction::SetInPoint | KeyAction::SetOutPoint => {
+                        if let Some(player) = world.get_animation_player(entity) {
+                            self.set_timeline_marker(player.time, action == KeyAction::SetInPoint);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if !self.animation_timeline.loop_range {
+            return;
+        }
+
+        let Some(player) = world.get_animation_player_mut(entity) else {
+            return;
+        };
+        let Some(duration) = current_clip_duration(player) else {
+            return;
+        };
+        let (start, end) = self.animation_timeline.range(duration);
+        if !player.playing || end - start <= f32::EPSILON {
+            return;
+        }
+
+        if player.time > end || player.time < start {
+            if player.looping {
+                player.time = if player.speed >= 0.0 { start } else { end };
+            } else {
+                player.time = player.time.clamp(start, end);
+                player.pause();
+            }
+        }
+    }
+
+    fn step_animation_frame(&mut self, world: &mut World, entity: Entity, frames: i32) {
+        let Some(player) = world.get_animation_player_mut(entity) else {
+            return;
+        };
+        let Some(duration) = current_clip_duration(player) else {
+            return;
+        };
+
+        let timeline = &self.animation_timeline;
+        let (start, end) = timeline.playback_range(duration);
+        let time = timeline.time_at(timeline.frame_at(player.time) + frames);
+        player.time = if time > end + f32::EPSILON {
+            if player.looping { start } else { end }
+        } else if time < start - f32::EPSILON {
+            if player.looping { end } else { start }
+        } else {
+            time.clamp(start, end)
+        };
+        player.pause();
+    }
+
+    fn set_timeline_marker(&mut self, time: f32, in_point: bool) {
+        let timeline = &mut self.animation_timeline;
+        if in_point {
+            timeline.in_point = time;
+            if timeline.out_point.is_some_and(|out_point| out_point < time) {
+                timeline.out_point = None;
+            }
+        } else {
+            timeline.out_point = Some(time);
+            if timeline.in_point > time {
+                timeline.in_point = 0.0;
+            }
+        }
+        timeline.loop_range = true;
+    }
+
+    fn timeline_ui(&mut self, world: &mut World, ui_context: &egui::Context) {
+        if !self.animation_timeline.show_window {
+            return;
+        }
+        let Some(entity) = self.animation_entity(world) else {
+            return;
+        };
+        let Some(player) = world.get_animation_player(entity) else {
+            return;
+        };
+        let Some(clip) = player
+            .current_clip
+            .and_then(|index| player.clips.get(index))
+        else {
+            return;
+        };
+
+        let duration = clip.duration.max(f32::EPSILON);
+        let mut time = player.time;
+        let playing = player.playing;
+        let mut summary = Vec::new();
+        let mut tracks: Vec<TimelineTrack> = Vec::new();
+        for channel in &clip.channels {
+            summary.extend_from_slice(&channel.sampler.input);
+            tracks.push(TimelineTrack {
+                label: format!("{} {:?}", channel.target_node, channel.target_property),
+                keyframes: channel.sampler.input.clone(),
+            });
+        }
+        tracks.sort_by(|a, b| a.label.cmp(&b.label));
+        summary.sort_by(f32::total_cmp);
+        summary.dedup_by(|a, b| (*a - *b).abs() < 1e-4);
+        tracks.insert(
+            0,
+            TimelineTrack {
+                label: "All Channels".to_string(),
+                keyframes: summary,
+            },
+        );
+
+        let mut open = true;
+        let mut scrubbed = false;
+        let mut toggle_playback = false;
+        let mut step = 0;
+        let mut marker = None;
+        let timeline = &mut self.animation_timeline;
+        let (range_start, range_end) = timeline.range(duration);
+
+        egui::Window::new("Timeline")
+            .open(&mut open)
+            .default_pos(egui::pos2(10.0, ui_context.content_rect().bottom() - 320.0))
+            .default_width(720.0)
+            .show(ui_context, |ui| {
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("|<")
+                        .on_hover_text("Jump to start (Home)")
+                        .clicked()
+                    {
+                        time = timeline.playback_range(duration).0;
+                        scrubbed = true;
+                    }
+                    if ui.button("<").on_hover_text("Previous frame (,)").clicked() {
+                        step = -1;
+                    }
+                    if ui
+                        .button(if playing { "Pause" } else { "Play" })
+                        .on_hover_text("Space")
+                        .clicked()
+                    {
+                        toggle_playback = true;
+                    }
+                    if ui.button(">").on_hover_text("Next frame (.)").clicked() {
+                        step = 1;
+                    }
+                    if ui.button(">|").on_hover_text("Jump to end (End)").clicked() {
+                        time = timeline.playback_range(duration).1;
+                        scrubbed = true;
+                    }
+                    ui.separator();
+                    ui.label(format!(
+                        "Frame {} / {}  ({:.3}s)",
+                        timeline.frame_at(time),
+                        timeline.frame_at(duration),
+                        time
+                    ));
+                    ui.separator();
+                    ui.add(
+                        egui::DragValue::new(&mut timeline.frame_rate)
+                            .speed(1.0)
+                            .range(1.0..=240.0)
+                            .suffix(" fps"),
+                    );
+                });
+
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut timeline.loop_range, "Loop Range");
+                    ui.label("In:");
+                    let mut in_point = range_start;
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut in_point)
+                                .speed(0.01)
+                                .range(0.0..=range_end)
+                                .fixed_decimals(3)
+                                .suffix("s"),
+                        )
+                        .changed()
+                    {
+                        timeline.in_point = in_point;
+                    }
+                    if ui.button("Set In").on_hover_text("I").clicked() {
+                        marker = Some(true);
+                    }
+                    ui.label("Out:");
+                    let mut out_point = range_end;
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut out_point)
+                                .speed(0.01)
+                                .range(range_start..=duration)
+                                .fixed_decimals(3)
+                                .suffix("s"),
+                        )
+                        .changed()
+                    {
+                        timeline.out_point = Some(out_point);
+                    }
+                    if ui.button("Set Out").on_hover_text("O").clicked() {
+                        marker = Some(false);
+                    }
+                    if ui.button("Clear").clicked() {
+                        timeline.clear_markers();
+                    }
+                });
+
+                ui.separator();
+
+                let label_width = 180.0;
+                let row_height = 18.0;
+                let highlight_range = timeline.loop_range;
+                let frame_rate = timeline.frame_rate;
+
+                let mut track_row = |ui: &mut egui::Ui, track: Option<&TimelineTrack>| {
+                    ui.horizontal(|ui| {
+                        let label = track.map(|track| track.label.as_str()).unwrap_or("");
+                        ui.add_sized(
+                            [label_width, row_height],
+                            egui::Label::new(label).truncate(),
+                        );
+                        let (rect, response) = ui.allocate_exact_size(
+                            egui::vec2(ui.available_width().max(100.0), row_height),
+                            egui::Sense::click_and_drag(),
+                        );
+                        let painter = ui.painter_at(rect);
+                        let x_at =
+                            |t: f32| rect.left() + rect.width() * (t / duration).clamp(0.0, 1.0);
+
+                        painter.rect_filled(rect, 2.0, egui::Color32::from_gray(32));
+                        if highlight_range {
+                            painter.rect_filled(
+                                egui::Rect::from_x_y_ranges(
+                                    x_at(range_start)..=x_at(range_end),
+                                    rect.y_range(),
+                                ),
+                                0.0,
+                                egui::Color32::from_rgba_unmultiplied(90, 140, 220, 40),
+                            );
+                        }
+
+                        match track {
+                            Some(track) => {
+                                for keyframe in &track.keyframes {
+                                    let x = x_at(*keyframe);
+                                    painter.line_segment(
+                                        [
+                                            egui::pos2(x, rect.top() + 3.0),
+                                            egui::pos2(x, rect.bottom() - 3.0),
+                                        ],
+                                        egui::Stroke::new(
+                                            2.0,
+                                            egui::Color32::from_rgb(230, 190, 80),
+                                        ),
+                                    );
+                                }
+                            }
+                            None => {
+                                let total_frames = (duration * frame_rate).ceil().max(1.0) as i32;
+                                let pixels_per_frame = rect.width() / (duration * frame_rate);
+                                let label_step = [1, 2, 5, 10, 20, 50, 100, 200, 500, 1000]
+                                    .into_iter()
+                                    .find(|step| *step as f32 * pixels_per_frame >= 40.0)
+                                    .unwrap_or(total_frames);
+                                let tick_step = if pixels_per_frame >= 4.0 {
+                                    1
+                                } else {
+                                    label_step
+                                };
+                                for frame in (0..=total_frames).step_by(tick_step as usize) {
+                                    let x = x_at(frame as f32 / frame_rate);
+                                    let major = frame % label_step == 0;
+                                    let top = if major { rect.top() } else { rect.center().y };
+                                    painter.line_segment(
+                                        [egui::pos2(x, top), egui::pos2(x, rect.bottom())],
+                                        egui::Stroke::new(1.0, egui::Color32::from_gray(110)),
+                                    );
+                                    if major {
+                                        painter.text(
+                                            egui::pos2(x + 2.0, rect.top()),
+                                            egui::Align2::LEFT_TOP,
+                                            frame.to_string(),
+                                            egui::FontId::monospace(10.0),
+                                            egui::Color32::from_gray(170),
+                                        );
+                                    }
+                                }
+                            }
+                        }
+
+                        if highlight_range {
+                            for marker_time in [range_start, range_end] {
+                                let x = x_at(marker_time);
+                                painter.line_segment(
+                                    [egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())],
+                                    egui::Stroke::new(1.5, egui::Color32::from_rgb(90, 160, 255)),
+                                );
+                            }
+                        }
+
+                        let x = x_at(time);
+                        painter.line_segment(
+                            [egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())],
+                            egui::Stroke::new(1.5, egui::Color32::from_rgb(240, 80, 80)),
+                        );
+
+                        if (response.clicked() || response.dragged())
+                            && let Some(pointer) = response.interact_pointer_pos()
+                        {
+                            let fraction =
+                                ((pointer.x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+                            let frame = (fraction * duration * frame_rate).round();
+                            time = (frame / frame_rate).clamp(0.0, duration);
+                            scrubbed = true;
+                        }
+                    });
+                };
+
+                track_row(ui, None);
+                egui::ScrollArea::vertical()
+                    .max_height(240.0)
+                    .auto_shrink([false, true])
+                    .show(ui, |ui| {
+                        for track in &tracks {
+                            track_row(ui, Some(track));
+                        }
+                    });
+            });
+
+        self.animation_timeline.show_window = open;
+
+        if let Some(in_point) = marker {
+            self.set_timeline_marker(time, in_point);
+        }
+        if step != 0 {
+            self.step_animation_frame(world, entity, step);
+        }
+        if let Some(player) = world.get_animation_player_mut(entity) {
+            if scrubbed {
+                player.time = time;
+                player.pause();
+            }
+            if toggle_playback {
+                if player.playing {
+                    player.pause();
+                } else {
+                    player.resume();
+                }
+            }
+        }
+    }
+
+    fn auto_rotation_system(&mut self, world: &mut World) {
+        if !self.loaded || self.rotation_speed <= 0.0 {
+            self.auto_rotation.reset();
+            return;
+        }
+
+        let delta_time = world.resources.window.timing.delta_time;
+        self.auto_rotation
+            .track_roots(root_poses(world, &self.model_entities));
+        let pivot = match self.auto_rotation.pivot {
+            RotationPivot::RootOrigins => None,
+            RotationPivot::BoundsCenter => {
+                if self.auto_rotation.pivot_point.is_none() {
+                    self.auto_rotation.pivot_point =
+                        self.scene_bounds(world).map(|(min, max)| (min + max) * 0.5);
+                }
+                self.auto_rotation.pivot_point
+            }
+        };
+        let step = self.auto_rotation.advance(self.rotation_speed * delta_time);
+
+        if self.auto_rotation.rotate_camera {
+            if let Some(camera_entity) = self.camera_entity
+                && let Some(pan_orbit) = world.get_pan_orbit_camera_mut(camera_entity)
+            {
+                if let Some(pivot) = pivot {
+                    pan_orbit.target_focus = pivot;
+                }
+                match self.auto_rotation.axis {
+                    GizmoAxis::X => pan_orbit.target_pitch += step,
+                    GizmoAxis::Y | GizmoAxis::Z => pan_orbit.target_yaw += step,
+                }
+            }
+            return;
+        }
+
+        let rotation = nalgebra_glm::quat_angle_axis(step, &self.auto_rotation.axis.direction());
+        for entity in &self.model_entities {
+            if let Some(transform) = world.get_local_transform_mut(*entity) {
+                if let Some(pivot) = pivot {
+                    transform.translation = pivot
+                        + nalgebra_glm::quat_rotate_vec3(
+                            &rotation,
+                            &(transform.translation - pivot),
+                        );
+                }
+                transform.rotation = rotation * transform.rotation;
+            }
+            world.mark_local_transform_dirty(*entity);
+        }
+        self.auto_rotation.pivot_roots = root_poses(world, &self.model_entities);
+    }
+
+    fn camera_target_pose(&self, world: &World) -> Option<CameraPose> {
+        let pan_orbit = world.get_pan_orbit_camera(self.camera_entity?)?;
+        Some(CameraPose {
+            focus: pan_orbit.target_focus,
+            radius: pan_orbit.target_radius,
+            yaw: pan_orbit.target_yaw,
+            pitch: pan_orbit.target_pitch,
+        })
+    }
+
+    fn set_camera_target_pose(&self, world: &mut World, pose: &CameraPose) {
+        if let Some(camera_entity) = self.camera_entity
+            && let Some(pan_orbit) = world.get_pan_orbit_camera_mut(camera_entity)
+        {
+            pan_orbit.target_focus = pose.focus;
+            pan_orbit.target_radius = pose.radius;
+            pan_orbit.target_yaw = pose.yaw;
+            pan_orbit.target_pitch = pose.pitch;
+        }
+    }
+
+    fn camera_bookmark_from_pose(&self, name: String, pose: &CameraPose) -> CameraBookmark {
+        let focus = pose.focus / self.scene_scale + self.scene_center;
+        CameraBookmark {
+            name,
+            focus: [focus.x, focus.y, focus.z],
+            radius: pose.radius / self.scene_scale,
+            yaw: pose.yaw,
+            pitch: pose.pitch,
+        }
+    }
+
+    fn camera_pose_from_bookmark(&self, bookmark: &CameraBookmark) -> CameraPose {
+        let focus = Vec3::new(bookmark.focus[0], bookmark.focus[1], bookmark.focus[2]);
+        CameraPose {
+            focus: (focus - self.scene_center) * self.scene_scale,
+            radius: bookmark.radius * self.scene_scale,
+            yaw: bookmark.yaw,
+            pitch: bookmark.pitch,
+        }
+    }
+
+    fn recall_camera_bookmark(&mut self, world: &mut World, index: usize) {
+        let Some(bookmark) = self.camera_bookmarks.bookmarks.get(index) else {
+            return;
+        };
+        let to = self.camera_pose_from_bookmark(bookmark);
+        self.transition_camera_to(world, to);
+    }
+
+    fn transition_camera_to(&mut self, world: &mut World, to: CameraPose) {
+        self.set_fly_camera(world, false);
+        let Some(from) = self.camera_target_pose(world) else {
+            return;
+        };
+        if self.camera_bookmarks.transition_duration <= 0.0 {
+            self.set_camera_target_pose(world, &to);
+            return;
+        }
+        self.camera_bookmarks.transition = Some(CameraTransition {
+            from,
+            to,
+            elapsed: 0.0,
+        });
+    }
+
+    fn apply_camera_pose(&self, world: &mut World, pose: &CameraPose) {
+        if let Some(camera_entity) = self.camera_entity
+            && let Some(pan_orbit) = world.get_pan_orbit_camera_mut(camera_entity)
+        {
+            pan_orbit.focus = pose.focus;
+            pan_orbit.radius = pose.radius;
+            pan_orbit.yaw = pose.yaw;
+            pan_orbit.pitch = pose.pitch;
+            pan_orbit.target_focus = pose.focus;
+            pan_orbit.target_radius = pose.radius;
+            pan_orbit.target_yaw = pose.yaw;
+            pan_orbit.target_pitch = pose.pitch;
+        }
+    }
+
+    fn set_fly_camera(&mut self, world: &mut World, enabled: bool) {
+        if enabled == self.fly_camera.pose.is_some() {
+            return;
+        }
+        let (Some(pose), Some(matrices)) = (
+            self.camera_target_pose(world),
+            query_active_camera_matrices(world),
+        ) else {
+            return;
+        };
+
+        let eye = matrices.camera_position;
+        let offset = eye - pose.focus;
+        let direction = if nalgebra_glm::length(&offset) > f32::EPSILON {
+            nalgebra_glm::normalize(&offset)
+        } else {
+            Vec3::new(0.0, 0.0, 1.0)
+        };
+
+        let radius = if enabled {
+            self.view_presets.orthographic = false;
+            self.fly_camera.orbit_radius = pose.radius;
+            FLY_PIVOT_DISTANCE * self.scene_scale.max(f32::EPSILON)
+        } else {
+            self.fly_camera.orbit_radius
+        };
+        let pose = CameraPose {
+            focus: eye - direction * radius,
+            radius,
+            ..pose
+        };
+
+        self.camera_bookmarks.transition = None;
+        self.fly_camera.pose = enabled.then_some(pose);
+        self.apply_camera_pose(world, &pose);
+    }
+
+    fn fly_camera_system(&mut self, world: &mut World) {
+        let (wants_keyboard, pointer_over_ui, (look_active, pointer_delta, scroll)) =
+            match &world.resources.user_interface.state {
+                Some(gui_state) => {
+                    let ctx = gui_state.egui_ctx();
+                    (
+                        ctx.wants_keyboard_input(),
+                        ctx.is_pointer_over_area() || ctx.is_using_pointer(),
+                        ctx.input(|input| {
+                            (
+                                input.pointer.secondary_down(),
+                                input.pointer.delta(),
+                                input.smooth_scroll_delta.y,
+                            )
+                        }),
+                    )
+                }
+                None => (false, false, (false, egui::Vec2::ZERO, 0.0)),
+            };
+
+        if self.key_bindings.pressed(KeyAction::ToggleFlyCamera) && !wants_keyboard {
+            let enabled = self.fly_camera.pose.is_none();
+            self.set_fly_camera(world, enabled);
+        }
+
+        let Some(mut pose) = self.fly_camera.pose else {
+            return;
+        };
+        let Some(matrices) = query_active_camera_matrices(world) else {
+            return;
+        };
+
+        if look_active && !pointer_over_ui {
+            let sensitivity = self.fly_camera.look_sensitivity.to_radians();
+            let vertical = if self.fly_camera.invert_y { -1.0 } else { 1.0 };
+            pose.yaw -= pointer_delta.x * sensitivity;
+            pose.pitch = (pose.pitch + pointer_delta.y * sensitivity * vertical).clamp(-1.55, 1.55);
+        }
+        if !pointer_over_ui && scroll != 0.0 {
+            self.fly_camera.speed =
+                (self.fly_camera.speed * 1.1f32.powf(scroll / 50.0)).clamp(0.05, 500.0);
+        }
+
+        if !wants_keyboard {
+            let input = FLY_MOVEMENT_ACTIONS
+                .iter()
+                .filter(|(action, _)| self.key_bindings.held(*action))
+                .fold(Vec3::zeros(), |sum, (_, axis)| {
+                    sum + Vec3::new(axis[0], axis[1], axis[2])
+                });
+            let boost = if self.key_bindings.held(KeyAction::FlyBoost) {
+                4.0
+            } else {
+                1.0
+            };
+
+            if nalgebra_glm::length(&input) > f32::EPSILON {
+                let view = matrices.view;
+                let forward = -Vec3::new(view[(2, 0)], view[(2, 1)], view[(2, 2)]);
+                let up = Vec3::new(0.0, 1.0, 0.0);
+                let right = nalgebra_glm::normalize(&nalgebra_glm::cross(&forward, &up));
+                let direction =
+                    nalgebra_glm::normalize(&(right * input.x + up * input.y + forward * input.z));
+                let delta_time = world.resources.window.timing.delta_time;
+                pose.focus +=
+                    direction * self.fly_camera.speed * self.scene_scale * boost * delta_time;
+            }
+        }
+
+        self.fly_camera.pose = Some(pose);
+        self.apply_camera_pose(world, &pose);
+    }
+
+    fn apply_view_preset(&mut self, world: &mut World, preset: ViewPreset) {
+        let Some((min, max)) = self.scene_bounds(world) else {
+            return;
+        };
+        let (yaw, pitch) = preset.yaw_pitch();
+        self.transition_camera_to(
+            world,
+            CameraPose {
+                focus: (min + max) * 0.5,
+                radius: self.framing_radius(world, min, max),
+                yaw,
+                pitch,
+            },
+        );
+    }
+
+    fn framing_radius(&self, world: &World, min: Vec3, max: Vec3) -> f32 {
+        let sphere_radius = (nalgebra_glm::distance(&min, &max) * 0.5).max(0.01);
+        let y_fov = self
+            .view_presets
+            .perspective
+            .map(|perspective| perspective.y_fov_rad)
+            .unwrap_or(std::f32::consts::FRAC_PI_4);
+        let aspect = self
+            .view_presets
+            .perspective
+            .and_then(|perspective| perspective.aspect_ratio)
+            .or_else(|| {
+                let gui_state = world.resources.user_interface.state.as_ref()?;
+                let screen = gui_state.egui_ctx().content_rect();
+                Some(screen.width() / screen.height().max(1.0))
+            })
+            .unwrap_or(1.0);
+
+        let half_y = y_fov * 0.5;
+        let half_x = (half_y.tan() * aspect).atan();
+        sphere_radius / half_x.min(half_y).sin() * 1.05
+    }
+
+    fn selection_bounds(&self, world: &World) -> Option<(Vec3, Vec3)> {
+        if let Some(entity) = self.selected_entity {
+            let parent = find_parent_matrix(world, &self.model_entities, entity)?;
+            let mut min = Vec3::new(f32::MAX, f32::MAX, f32::MAX);
+            let mut max = Vec3::new(f32::MIN, f32::MIN, f32::MIN);
+            let mut has_bounds = false;
+            calculate_bounds_recursive(world, entity, &parent, &mut min, &mut max, &mut has_bounds);
+            return has_bounds.then_some((min, max));
+        }
+        let instance = self.instances.get(self.selected_instance?)?;
+        entities_bounds(world, &instance.entities)
+    }
+
+    fn frame_bounds(&mut self, world: &mut World, bounds: Option<(Vec3, Vec3)>) {
+        let (Some((min, max)), Some(pose)) = (bounds, self.camera_target_pose(world)) else {
+            return;
+        };
+        let radius = self.framing_radius(world, min, max);
+        self.transition_camera_to(
+            world,
+            CameraPose {
+                focus: (min + max) * 0.5,
+                radius,
+                ..pose
+            },
+        );
+    }
+
+    fn frame_selection(&mut self, world: &mut World) {
+        let bounds = self.selection_bounds(world);
+        self.frame_bounds(world, bounds);
+    }
+
+    #[cfg(feature = "gamepad")]
+    fn gamepad_system(&mut self, world: &mut World) {
+        let gamepad = &world.resources.input.gamepad;
+        if !gamepad.is_connected() {
+            self.gamepad.buttons_down.clear();
+            return;
+        }
+
+        let buttons_down: Vec<GamepadButton> = GAMEPAD_BUTTONS
+            .into_iter()
+            .filter(|button| gamepad.is_button_pressed(*button))
+            .collect();
+        let pressed: Vec<GamepadButton> = buttons_down
+            .iter()
+            .copied()
+            .filter(|button| !self.gamepad.buttons_down.contains(button))
+            .collect();
+        self.gamepad.buttons_down = buttons_down;
+
+        let dead_zone = self.gamepad.dead_zone;
+        let axis = |axis: GamepadAxis| {
+            let value = gamepad.axis_value(axis);
+            if value.abs() < dead_zone { 0.0 } else { value }
+        };
+        let left = Vec2::new(axis(GamepadAxis::LeftStickX), axis(GamepadAxis::LeftStickY));
+        let right = Vec2::new(
+            axis(GamepadAxis::RightStickX),
+            axis(GamepadAxis::RightStickY),
+        );
+
+        for button in pressed {
+            match button {
+                GamepadButton::Start => {
+                    self.gamepad.navigating = !self.gamepad.navigating;
+                    self.gamepad.navigation_presses.push(button);
+                }
+                GamepadButton::RightThumb => {
+                    self.gamepad.right_stick_zoom = !self.gamepad.right_stick_zoom;
+                }
+                GamepadButton::DPadUp
+                | GamepadButton::DPadDown
+                | GamepadButton::DPadLeft
+                | GamepadButton::DPadRight
+                | GamepadButton::South
+                | GamepadButton::East
+                    if self.gamepad.navigating =>
+                {
+                    if button == GamepadButton::East {
+                        self.gamepad.navigating = false;
+                    }
+                    self.gamepad.navigation_presses.push(button);
+                }
+                _ => {
+                    if let Some((_, action)) =
+                        GAMEPAD_ACTIONS.iter().find(|(mapped, _)| *mapped == button)
+                    {
+                        self.key_bindings.pressed.push(*action);
+                    }
+                }
+            }
+        }
+
+        if left == Vec2::zeros() && right == Vec2::zeros() {
+            return;
+        }
+        self.camera_bookmarks.transition = None;
+
+        let Some(matrices) = query_active_camera_matrices(world) else {
+            return;
+        };
+        let view = matrices.view;
+        let camera_right = Vec3::new(view[(0, 0)], view[(0, 1)], view[(0, 2)]);
+        let camera_up = Vec3::new(view[(1, 0)], view[(1, 1)], view[(1, 2)]);
+        let forward = -Vec3::new(view[(2, 0)], view[(2, 1)], view[(2, 2)]);
+        let delta_time = world.resources.window.timing.delta_time;
+        let rate = self.gamepad.sensitivity * delta_time;
+        let vertical = if self.gamepad.invert_y { -1.0 } else { 1.0 };
+        let pitch_limit = std::f32::consts::FRAC_PI_2 - 0.001;
+
+        if let Some(pose) = &mut self.fly_camera.pose {
+            pose.yaw -= right.x * rate * 2.0;
+            pose.pitch = (pose.pitch + right.y * vertical * rate * 2.0).clamp(-1.55, 1.55);
+            pose.focus += (camera_right * left.x + forward * left.y)
+                * self.fly_camera.speed
+                * self.scene_scale
+                * delta_time;
+            return;
+        }
+
+        let Some(camera_entity) = self.camera_entity else {
+            return;
+        };
+        let Some(pan_orbit) = world.get_pan_orbit_camera_mut(camera_entity) else {
+            return;
+        };
+        pan_orbit.target_yaw -= left.x * rate * 2.0;
+        pan_orbit.target_pitch = (pan_orbit.target_pitch + left.y * vertical * rate * 2.0)
+            .clamp(-pitch_limit, pitch_limit);
+        if self.gamepad.right_stick_zoom {
+            pan_orbit.target_radius *= (-right.y * rate * 1.5).exp();
+        } else {
+            pan_orbit.target_focus -= (camera_right * right.x + camera_up * right.y)
+                * pan_orbit.target_radius
+                * rate
+                * 0.5;
+        }
+    }
+
+    #[cfg(feature = "gamepad")]
+    fn gamepad_navigation_ui(&mut self, ui_context: &egui::Context) {
+        // Presses are replayed as egui focus moves and key events so the d-pad drives
+        // the same widgets as the keyboard: up/down move focus, left/right step sliders
+        // and A activates the focused widget.
+        for button in std::mem::take(&mut self.gamepad.navigation_presses) {
+            let key = match button {
+                GamepadButton::DPadUp => {
+                    ui_context.memory_mut(|memory| {
+                        memory.move_focus(egui::FocusDirection::Previous);
+                    });
+                    continue;
+                }
+                GamepadButton::DPadDown => {
+                    ui_context.memory_mut(|memory| memory.move_focus(egui::FocusDirection::Next));
+                    continue;
+                }
+                GamepadButton::Start | GamepadButton::East if !self.gamepad.navigating => {
+                    ui_context.memory_mut(|memory| {
+                        if let Some(focused) = memory.focused() {
+                            memory.surrender_focus(focused);
+                        }
+                    });
+                    continue;
+                }
+                GamepadButton::Start => {
+                    ui_context.memory_mut(|memory| {
+                        if memory.focused().is_none() {
+                            memory.move_focus(egui::FocusDirection::Next);
+                        }
+                    });
+                    continue;
+                }
+                GamepadButton::DPadLeft => egui::Key::ArrowLeft,
+                GamepadButton::DPadRight => egui::Key::ArrowRight,
+                GamepadButton::South => egui::Key::Enter,
+                _ => continue,
+            };
+            ui_context.input_mut(|input| {
+                input.events.push(egui::Event::Key {
+                    key,
+                    physical_key: None,
+                    pressed: true,
+                    repeat: false,
+                    modifiers: egui::Modifiers::NONE,
+                });
+            });
+        }
+
+        if !self.gamepad.navigating {
+            return;
+        }
+        egui::Area::new(egui::Id::new("gamepad_navigation_hint"))
+            .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -16.0])
+            .show(ui_context, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(
+                        "D-pad: move focus and adjust   A: activate   B or Menu: stop navigating",
+                    );
+                });
+            });
+    }
+
+    #[cfg(feature = "gamepad")]
+    fn gamepad_settings_ui(&mut self, ui: &mut egui::Ui) {
+        ui.collapsing("Controller", |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Stick Sensitivity:");
+                ui.add(egui::Slider::new(&mut self.gamepad.sensitivity, 0.25..=4.0).step_by(0.25));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Dead Zone:");
+                ui.add(egui::Slider::new(&mut self.gamepad.dead_zone, 0.0..=0.5).step_by(0.05));
+            });
+            ui.checkbox(&mut self.gamepad.invert_y, "Invert Y");
+            ui.checkbox(&mut self.gamepad.right_stick_zoom, "Right Stick Zooms");
+        });
+    }
+
+    #[cfg(feature = "openxr")]
+    fn xr_system(&mut self, world: &mut World) {
+        let Some(input) = self.xr.source.next_frame(world.resources.xr.as_ref()) else {
+            self.xr.active = false;
+            self.xr.grab = None;
+            return;
+        };
+        self.xr.active = true;
+        self.xr.head = input
+            .head
+            .tracked
+            .then(|| (input.head.position(), input.head.orientation()));
+
+        if self.xr.tracked_roots != self.model_entities {
+            self.xr.tracked_roots = self.model_entities.clone();
+            self.xr.model_scale = 1.0;
+            self.xr.grab = None;
+        }
+
+        let buttons = input.buttons();
+        let pressed: Vec<usize> = (0..buttons.len())
+            .filter(|index| buttons[*index] && !self.xr.buttons_down[*index])
+            .collect();
+        self.xr.buttons_down = buttons;
+        for index in pressed {
+            match index {
+                0 => self.key_bindings.pressed.push(KeyAction::NextAtmosphere),
+                1 => self
+                    .key_bindings
+                    .pressed
+                    .push(KeyAction::PreviousAtmosphere),
+                2 => self.key_bindings.pressed.push(KeyAction::NextAsset),
+                3 => self.key_bindings.pressed.push(KeyAction::PreviousAsset),
+                4 => self.xr.place_requested = true,
+                _ => self.xr.panels_visible = !self.xr.panels_visible,
+            }
+        }
+
+        if std::mem::take(&mut self.xr.place_requested) {
+            self.place_model_on_floor(world);
+        }
+
+        self.xr_grab_system(world, &input);
+
+        self.xr.panel_anchor = self.xr.pinned_panel.or_else(|| {
+            input.left.tracked.then(|| {
+                let orientation = input.left.orientation();
+                let offset =
+                    nalgebra_glm::quat_rotate_vec3(&orientation, &Vec3::new(0.0, 0.12, -0.08));
+                let tilt = nalgebra_glm::quat_angle_axis(-0.6, &Vec3::new(1.0, 0.0, 0.0));
+                (input.left.position() + offset, orientation * tilt)
+            })
+        });
+        // The runtime draws the whole egui context, including the Settings window, onto
+        // this quad, so hiding the panels hides the desktop UI inside the headset too.
+        if let Some(xr) = world.resources.xr.as_mut() {
+            xr.ui_panel = self.xr.panel_anchor.filter(|_| self.xr.panels_visible).map(
+                |(position, orientation)| XrPanel {
+                    position,
+                    orientation,
+                    width: 0.4,
+                },
+            );
+        }
+    }
+
+    #[cfg(feature = "openxr")]
+    fn xr_grab_system(&mut self, world: &mut World, input: &XrFrameInput) {
+        let hands = [input.left.grabbing(), input.right.grabbing()];
+        let poses = [
+            (input.left.position(), input.left.orientation()),
+            (input.right.position(), input.right.orientation()),
+        ];
+
+        if self.xr.grab.as_ref().map(|grab| grab.hands) != Some(hands) {
+            self.xr.grab = hands.contains(&true).then(|| XrGrab {
+                hands,
+                start: poses,
+                roots: self
+                    .model_entities
+                    .iter()
+                    .filter_map(|entity| {
+                        let transform = world.get_local_transform(*entity)?;
+                        Some((
+                            *entity,
+                            transform.translation,
+                            transform.rotation,
+                            transform.scale,
+                        ))
+                    })
+                    .collect(),
+                model_scale: self.xr.model_scale,
+            });
+            return;
+        }
+        let Some(grab) = &self.xr.grab else {
+            return;
+        };
+        let Some(motion) = grab.motion(poses) else {
+            return;
+        };
+
+        for (entity, translation, rotation, scale) in &grab.roots {
+            if let Some(transform) = world.get_local_transform_mut(*entity) {
+                (transform.translation, transform.rotation, transform.scale) =
+                    motion.apply(*translation, *rotation, *scale);
+            }
+            world.mark_local_transform_dirty(*entity);
+        }
+        self.xr.model_scale = grab.model_scale * motion.scale;
+    }
+
+    #[cfg(feature = "openxr")]
+    fn place_model_on_floor(&mut self, world: &mut World) {
+        let factor = 1.0 / (self.scene_scale * self.xr.model_scale);
+        for entity in &self.model_entities {
+            if let Some(transform) = world.get_local_transform_mut(*entity) {
+                transform.translation *= factor;
+                transform.scale *= factor;
+            }
+            world.mark_local_transform_dirty(*entity);
+        }
+        self.xr.model_scale = 1.0 / self.scene_scale;
+        self.xr.grab = None;
+
+        let Some((min, max)) = self.scene_bounds(world) else {
+            return;
+        };
+        let offset = floor_placement_offset(min, max, self.xr.head);
+
+        for entity in &self.model_entities {
+            if let Some(transform) = world.get_local_transform_mut(*entity) {
+                transform.translation += offset;
+            }
+            world.mark_local_transform_dirty(*entity);
+        }
+    }
+
+    #[cfg(feature = "openxr")]
+    fn xr_settings_ui(&mut self, ui: &mut egui::Ui) {
+        if !self.xr.active {
+            return;
+        }
+
+        ui.collapsing("VR", |ui| {
+            ui.label(format!(
+                "Model Scale: {:.0}% of true size",
+                self.scene_scale * self.xr.model_scale * 100.0
+            ));
+            if ui.button("Place at True Scale on Floor").clicked() {
+                self.xr.place_requested = true;
+            }
+
+            let mut pinned = self.xr.pinned_panel.is_some();
+            if ui.checkbox(&mut pinned, "Pin Panel in Place").changed() {
+                self.xr.pinned_panel = if pinned { self.xr.panel_anchor } else { None };
+            }
+
+            ui.label(
+                egui::RichText::new(
+                    "Grip to grab, both grips to rotate and scale. \
+                     A/B: assets, X/Y: skyboxes, left stick click: place on floor, \
+                     right stick click: hide panel",
+                )
+                .small()
+                .weak(),
+            );
+        });
+    }
+
+    fn frame_all(&mut self, world: &mut World) {
+        let bounds = self.scene_bounds(world);
+        self.frame_bounds(world, bounds);
+    }
+
+    fn view_presets_system(&mut self, world: &mut World) {
+        let just_pressed: Vec<KeyAction> = self.key_bindings.pressed.clone();
+        let (wants_keyboard, aspect) = match &world.resources.user_interface.state {
+            Some(gui_state) => {
+                let ctx = gui_state.egui_ctx();
+                let screen = ctx.content_rect();
+                (
+                    ctx.wants_keyboard_input(),
+                    screen.width() / screen.height().max(1.0),
+                )
+            }
+            None => (false, 1.0),
+        };
+
+        if !wants_keyboard {
+            for action in just_pressed {
+                let preset = match action {
+                    KeyAction::ViewFront => ViewPreset::Front,
+                    KeyAction::ViewBack => ViewPreset::Back,
+                    KeyAction::ViewLeft => ViewPreset::Left,
+                    KeyAction::ViewRight => ViewPreset::Right,
+                    KeyAction::ViewTop => ViewPreset::Top,
+                    KeyAction::ViewBottom => ViewPreset::Bottom,
+                    KeyAction::ViewIsometric => ViewPreset::Isometric,
+                    KeyAction::ToggleOrthographic => {
+                        if self.fly_camera.pose.is_none() {
+                            self.view_presets.orthographic = !self.view_presets.orthographic;
+                        }
+                        continue;
+                    }
+                    _ => continue,
+                };
+                self.apply_view_preset(world, preset);
+            }
+        }
+
+        let Some(camera_entity) = self.camera_entity else {
+            return;
+        };
+        let radius = world
+            .get_pan_orbit_camera(camera_entity)
+            .map(|pan_orbit| pan_orbit.radius)
+            .unwrap_or(3.0);
+        let Some(camera) = world.get_camera_mut(camera_entity) else {
+            return;
+        };
+
+        if let Projection::Perspective(perspective) = camera.projection {
+            self.view_presets.perspective = Some(perspective);
+        }
+        let Some(perspective) = self.view_presets.perspective else {
+            return;
+        };
+        if !self.view_presets.orthographic {
+            if matches!(camera.projection, Projection::Orthographic(_)) {
+                camera.projection = Projection::Perspective(perspective);
+            }
+            return;
+        }
+
+        let y_mag = radius * (perspective.y_fov_rad * 0.5).tan();
+        camera.projection = Projection::Orthographic(OrthographicCamera {
+            x_mag: y_mag * perspective.aspect_ratio.unwrap_or(aspect),
+            y_mag,
+            z_far: perspective.z_far.unwrap_or(1000.0),
+            z_near: perspective.z_near,
+        });
+    }
+
+    fn camera_ui(&mut self, world: &mut World, ui: &mut egui::Ui) {
+        ui.collapsing("Camera", |ui| {
+            let mut preset = None;
+            ui.horizontal_wrapped(|ui| {
+                for view in ViewPreset::ALL {
+                    if ui.button(format!("{:?}", view)).clicked() {
+                        preset = Some(view);
+                    }
+                }
+            });
+            if let Some(preset) = preset {
+                self.apply_view_preset(world, preset);
+            }
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(
+                        self.selected_entity.is_some() || self.selected_instance.is_some(),
+                        egui::Button::new("Frame Selection"),
+                    )
+                    .on_hover_text("F")
+                    .clicked()
+                {
+                    self.frame_selection(world);
+                }
+                if ui.button("Frame All").on_hover_text("A").clicked() {
+                    self.frame_all(world);
+                }
+            });
+            ui.add_enabled(
+                self.fly_camera.pose.is_none(),
+                egui::Checkbox::new(&mut self.view_presets.orthographic, "Orthographic"),
+            );
+            let key_bindings = &self.key_bindings;
+            ui.label(
+                egui::RichText::new(format!(
+                    "{} front, {} right, {} top, {} isometric, {} toggles orthographic",
+                    key_bindings.label(KeyAction::ViewFront),
+                    key_bindings.label(KeyAction::ViewRight),
+                    key_bindings.label(KeyAction::ViewTop),
+                    key_bindings.label(KeyAction::ViewIsometric),
+                    key_bindings.label(KeyAction::ToggleOrthographic),
+                ))
+                .small()
+                .weak(),
+            );
+
+            ui.separator();
+
+            let mut fly = self.fly_camera.pose.is_some();
+            ui.horizontal(|ui| {
+                ui.label("Mode:");
+                ui.selectable_value(&mut fly, false, "Orbit");
+                ui.selectable_value(&mut fly, true, "Fly");
+            });
+            if fly != self.fly_camera.pose.is_some() {
+                self.set_fly_camera(world, fly);
+            }
+
+            ui.add_enabled_ui(fly, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Speed:");
+                    ui.add(
+                        egui::Slider::new(&mut self.fly_camera.speed, 0.05..=500.0)
+                            .logarithmic(true)
+                            .suffix(" m/s"),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Look Sensitivity:");
+                    ui.add(
+                        egui::Slider::new(&mut self.fly_camera.look_sensitivity, 0.02..=1.0)
+                            .fixed_decimals(2),
+                    );
+                });
+                ui.checkbox(&mut self.fly_camera.invert_y, "Invert Y");
+            });
+            let key_bindings = &self.key_bindings;
+            ui.label(
+                egui::RichText::new(format!(
+                    "{} toggles fly mode. {}/{}/{}/{} move, {}/{} up/down, {} faster, \
+                     hold right mouse to look, scroll to change speed",
+                    key_bindings.label(KeyAction::ToggleFlyCamera),
+                    key_bindings.label(KeyAction::FlyForward),
+                    key_bindings.label(KeyAction::FlyLeft),
+                    key_bindings.label(KeyAction::FlyBackward),
+                    key_bindings.label(KeyAction::FlyRight),
+                    key_bindings.label(KeyAction::FlyUp),
+                    key_bindings.label(KeyAction::FlyDown),
+                    key_bindings.label(KeyAction::FlyBoost),
+                ))
+                .small()
+                .weak(),
+            );
+        });
+    }
+
+    fn camera_bookmarks_system(&mut self, world: &mut World) {
+        let just_pressed = self
+            .key_bindings
+            .pressed
+            .iter()
+            .find_map(|action| match action {
+                KeyAction::Bookmark(index) => Some(*index),
+                _ => None,
+            });
+
+        let wants_keyboard = world
+            .resources
+            .user_interface
+            .state
+            .as_ref()
+            .is_some_and(|gui_state| gui_state.egui_ctx().wants_keyboard_input());
+        if !wants_keyboard && let Some(index) = just_pressed {
+            self.recall_camera_bookmark(world, index);
+        }
+
+        let delta_time = world.resources.window.timing.delta_time;
+        let duration = self.camera_bookmarks.transition_duration.max(f32::EPSILON);
+        let Some(transition) = &mut self.camera_bookmarks.transition else {
+            return;
+        };
+        transition.elapsed += delta_time;
+        let progress = (transition.elapsed / duration).min(1.0);
+        let eased = progress * progress * (3.0 - 2.0 * progress);
+        let pose = transition.from.interpolate(&transition.to, eased);
+        if progress >= 1.0 {
+            self.camera_bookmarks.transition = None;
+        }
+        self.set_camera_target_pose(world, &pose);
+    }
+
+    fn camera_bookmarks_ui(&mut self, world: &mut World, ui: &mut egui::Ui) {
+        if self.model_entities.is_empty() {
+            return;
+        }
+
+        let mut recall = None;
+        let mut update = None;
+        let mut remove = None;
+        let mut add = false;
+        let mut renamed = false;
+
+        ui.collapsing("Camera Bookmarks", |ui| {
+            let key_bindings = &self.key_bindings;
+            let bookmarks = &mut self.camera_bookmarks;
+            for (index, bookmark) in bookmarks.bookmarks.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    let key = if index < BOOKMARK_KEYS.len() {
+                        key_bindings.label(KeyAction::Bookmark(index))
+                    } else {
+                        " ".to_string()
+                    };
+                    ui.label(egui::RichText::new(key).monospace());
+                    renamed |= ui
+                        .add(egui::TextEdit::singleline(&mut bookmark.name).desired_width(120.0))
+                        .lost_focus();
+                    if ui.button("Go").clicked() {
+                        recall = Some(index);
+                    }
+                    if ui
+                        .button("Update")
+                        .on_hover_text("Replace with the current view")
+                        .clicked()
+                    {
+                        update = Some(index);
+                    }
+                    if ui.button("x").clicked() {
+                        remove = Some(index);
+                    }
+                });
+            }
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut bookmarks.new_name)
+                        .hint_text("Bookmark name")
+                        .desired_width(150.0),
+                );
+                add = ui.button("Add View").clicked();
+            });
+            ui.horizontal(|ui| {
+                ui.label("Transition:");
+                ui.add(
+                    egui::DragValue::new(&mut bookmarks.transition_duration)
+                        .speed(0.05)
+                        .range(0.0..=5.0)
+                        .suffix("s"),
+                );
+            });
+            ui.label(
+                egui::RichText::new("Press the bookmark's key to recall it")
+                    .small()
+                    .weak(),
+            );
+        });
+
+        let mut changed = renamed;
+        if let Some(index) = recall {
+            self.recall_camera_bookmark(world, index);
+        }
+        if let Some(index) = update
+            && let Some(pose) = self.camera_target_pose(world)
+        {
+            let name = self.camera_bookmarks.bookmarks[index].name.clone();
+            self.camera_bookmarks.bookmarks[index] = self.camera_bookmark_from_pose(name, &pose);
+            changed = true;
+        }
+        if let Some(index) = remove {
+            self.camera_bookmarks.bookmarks.remove(index);
+            changed = true;
+        }
+        if add && let Some(pose) = self.camera_target_pose(world) {
+            let name = match self.camera_bookmarks.new_name.trim() {
+                "" => format!("View {}", self.camera_bookmarks.bookmarks.len() + 1),
+                name => name.to_string(),
+            };
+            let bookmark = self.camera_bookmark_from_pose(name, &pose);
+            self.camera_bookmarks.bookmarks.push(bookmark);
+            self.camera_bookmarks.new_name.clear();
+            changed = true;
+        }
+        if changed {
+            self.save_camera_bookmarks();
+        }
+    }
+
+    fn key_bindings_system(&mut self, world: &mut World) {
+        let keyboard = &world.resources.input.keyboard;
+        self.key_bindings.update(|key| keyboard.is_key_pressed(key));
+
+        if let Some(action) = self.key_bindings.capture {
+            self.key_bindings.pressed.clear();
+            let ctrl = keyboard.is_key_pressed(KeyCode::ControlLeft)
+                || keyboard.is_key_pressed(KeyCode::ControlRight);
+            let keys_down: Vec<KeyCode> = BINDABLE_KEYS
+                .into_iter()
+                .filter(|key| keyboard.is_key_pressed(*key))
+                .collect();
+            let new_key = keys_down
+                .iter()
+                .find(|key| !self.key_bindings.capture_keys_down.contains(key))
+                .copied();
+            self.key_bindings.capture_keys_down = keys_down;
+
+            if let Some(key) = new_key {
+                let binding = KeyBinding { key, ctrl };
+                let bindings = self.key_bindings.bindings.entry(action).or_default();
+                if !bindings.contains(&binding) {
+                    bindings.push(binding);
+                }
+                self.key_bindings.capture = None;
+                self.save_key_bindings();
+            }
+            return;
+        }
+
+        let wants_keyboard = world
+            .resources
+            .user_interface
+            .state
+            .as_ref()
+            .is_some_and(|gui_state| gui_state.egui_ctx().wants_keyboard_input());
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.sequence_export.run.is_some() {
+            return;
+        }
+        if !wants_keyboard && self.key_bindings.pressed(KeyAction::Exit) {
+            world.resources.window.should_exit = true;
+        }
+    }
+
+    fn key_bindings_ui(&mut self, world: &mut World, ui_context: &egui::Context) {
+        if !self.key_bindings.show_window {
+            return;
+        }
+
+        let mut open = true;
+        let mut changed = false;
+        let mut capture = None;
+        let key_bindings = &mut self.key_bindings;
+
+        egui::Window::new("Key Bindings")
+            .open(&mut open)
+            .default_width(420.0)
+            .show(ui_context, |ui| {
+                if let Some(action) = key_bindings.capture {
+                    ui.horizontal(|ui| {
+                        ui.label(format!(
+                            "Press a key for {} (hold Ctrl for a chord)",
+                            action.label()
+                        ));
+                        if ui.button("Cancel").clicked() {
+                            key_bindings.capture = None;
+                        }
+                    });
+                    ui.separator();
+                }
+
+                let mut conflicting_actions = 0;
+                egui::ScrollArea::vertical()
+                    .max_height(420.0)
+                    .show(ui, |ui| {
+                        let mut category = "";
+                        for action in KeyAction::ALL {
+                            if action.category() != category {
+                                category = action.category();
+                                ui.add_space(4.0);
+                                ui.label(egui::RichText::new(category).strong());
+                            }
+
+                            ui.horizontal(|ui| {
+                                ui.add_sized([170.0, 18.0], egui::Label::new(action.label()));
+                                let mut remove = None;
+                                let mut has_conflict = false;
+                                for (index, binding) in
+                                    key_bindings.bindings(action).iter().enumerate()
+                                {
+                                    let conflicts = key_bindings.conflicts(action, binding);
+                                    let mut text = egui::RichText::new(binding.label()).monospace();
+                                    let mut hover = "Click to remove".to_string();
+                                    if !conflicts.is_empty() {
+                                        has_conflict = true;
+                                        text = text.color(egui::Color32::from_rgb(230, 90, 80));
+                                        let names: Vec<String> =
+                                            conflicts.iter().map(|other| other.label()).collect();
+                                        hover = format!("Conflicts with {}", names.join(", "));
+                                    }
+                                    if ui.button(text).on_hover_text(hover).clicked() {
+                                        remove = Some(index);
+                                    }
+                                }
+                                if has_conflict {
+                                    conflicting_actions += 1;
+                                }
+                                if let Some(index) = remove
+                                    && let Some(bindings) = key_bindings.bindings.get_mut(&action)
+                                {
+                                    bindings.remove(index);
+                                    changed = true;
+                                }
+                                if ui.small_button("+").on_hover_text("Add a key").clicked() {
+                                    capture = Some(action);
+                                }
+                            });
+                        }
+                    });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Reset to Defaults").clicked() {
+                        for action in KeyAction::ALL {
+                            key_bindings
+                                .bindings
+                                .insert(action, action.default_bindings());
+                        }
+                        changed = true;
+                    }
+                    if conflicting_actions > 0 {
+                        ui.colored_label(
+                            egui::Color32::from_rgb(230, 90, 80),
+                            format!("{} actions share keys", conflicting_actions),
+                        );
+                    }
+                });
+            });
+
+        self.key_bindings.show_window = open;
+        if let Some(action) = capture {
+            let keyboard = &world.resources.input.keyboard;
+            self.key_bindings.capture = Some(action);
+            self.key_bindings.capture_keys_down = BINDABLE_KEYS
+                .into_iter()
+                .filter(|key| keyboard.is_key_pressed(*key))
+                .collect();
+        }
+        if changed {
+            self.save_key_bindings();
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn settings_file() -> Option<PathBuf> {
+        Some(config_directory()?.join("settings.json"))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn read_settings() -> Settings {
+        let Some(path) = Self::settings_file() else {
+            return Settings::default();
+        };
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            return Settings::default();
+        };
+        serde_json::from_str(&content).unwrap_or_else(|error| {
+            tracing::warn!("Failed to parse {}: {}", path.display(), error);
+            Settings::default()
+        })
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_key_bindings(&mut self) {
+        let settings = Self::read_settings();
+        self.key_bindings.apply_settings(&settings.key_bindings);
+    }
+
+    fn save_key_bindings(&self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let Some(file) = Self::settings_file() else {
+                return;
+            };
+
+            let mut settings = Self::read_settings();
+            settings.key_bindings = self.key_bindings.to_settings();
+
+            let result = file
+                .parent()
+                .map(std::fs::create_dir_all)
+                .transpose()
+                .map_err(|error| error.to_string())
+                .and_then(|_| {
+                    serde_json::to_string_pretty(&settings).map_err(|error| error.to_string())
+                })
+                .and_then(|content| {
+                    std::fs::write(&file, content).map_err(|error| error.to_string())
+                });
+            if let Err(error) = result {
+                tracing::error!("Failed to save key bindings: {}", error);
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn camera_bookmarks_file() -> Option<PathBuf> {
+        Some(config_directory()?.join("camera_bookmarks.json"))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn read_camera_bookmarks_file() -> std::collections::BTreeMap<String, Vec<CameraBookmark>> {
+        Self::camera_bookmarks_file()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn load_camera_bookmarks(&mut self) {
+        let model_path = self
+            .instances
+            .first()
+            .and_then(|instance| instance.path.clone());
+        if model_path == self.camera_bookmarks.model_path {
+            return;
+        }
+        self.camera_bookmarks.model_path = model_path;
+        self.camera_bookmarks.bookmarks.clear();
+        self.camera_bookmarks.transition = None;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = &self.camera_bookmarks.model_path {
+            self.camera_bookmarks.bookmarks = Self::read_camera_bookmarks_file()
+                .remove(&bookmark_key(path))
+                .unwrap_or_default();
+        }
+    }
+
+    fn save_camera_bookmarks(&self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let (Some(model_path), Some(file)) = (
+                &self.camera_bookmarks.model_path,
+                Self::camera_bookmarks_file(),
+            ) else {
+                return;
+            };
+
+            let mut stored = Self::read_camera_bookmarks_file();
+            let key = bookmark_key(model_path);
+            if self.camera_bookmarks.bookmarks.is_empty() {
+                stored.remove(&key);
+            } else {
+                stored.insert(key, self.camera_bookmarks.bookmarks.clone());
+            }
+
+            let result = file
+                .parent()
+                .map(std::fs::create_dir_all)
+                .transpose()
+                .map_err(|error| error.to_string())
+                .and_then(|_| {
+                    serde_json::to_string_pretty(&stored).map_err(|error| error.to_string())
+                })
+                .and_then(|content| {
+                    std::fs::write(&file, content).map_err(|error| error.to_string())
+                });
+            if let Err(error) = result {
+                tracing::error!("Failed to save camera bookmarks: {}", error);
+            }
+        }
+    }
+
+    fn atmosphere_switch_system(&mut self, world: &mut World) {
+        if self.key_bindings.pressed(KeyAction::NextAtmosphere) {
+            world.resources.graphics.atmosphere = world.resources.graphics.atmosphere.next();
+            self.selected_custom_skybox = None;
+        }
+        if self.key_bindings.pressed(KeyAction::PreviousAtmosphere) {
+            world.resources.graphics.atmosphere = world.resources.graphics.atmosphere.previous();
+            self.selected_custom_skybox = None;
+        }
+
+        let current_atmosphere = world.resources.graphics.atmosphere;
+        if current_atmosphere != self.previous_atmosphere {
+            if current_atmosphere.is_procedural() {
+                capture_procedural_atmosphere_ibl(world, current_atmosphere, 0.0);
+            }
+            self.previous_atmosphere = current_atmosphere;
+        }
+    }
+
+    fn hint_hide_system(&mut self, world: &mut World) {
+        let uptime = world.resources.window.timing.uptime_milliseconds;
+
+        if let Some(hint_entity) = self.hint_text_entity
+            && uptime > self.hint_hide_time
+        {
+            world.despawn_entities(&[hint_entity]);
+            self.hint_text_entity = None;
+        }
+    }
+
+    fn keyboard_shortcuts_system(&mut self, world: &mut World) {
+        if let Some(gui_state) = &world.resources.user_interface.state
+            && gui_state.egui_ctx().wants_keyboard_input()
+        {
+            return;
+        }
+
+        if self.key_bindings.pressed(KeyAction::ResetCamera) {
+            self.reset_camera(world);
+            world.resources.user_interface.enabled = true;
+        }
+
+        if self.key_bindings.pressed(KeyAction::DeleteInstance)
+            && let Some(index) = self.selected_instance
+        {
+            self.remove_instance(world, index);
+        }
+
+        let flying = self.fly_camera.pose.is_some();
+        if !flying && self.key_bindings.pressed(KeyAction::FrameSelection) {
+            self.frame_selection(world);
+        }
+
+        if !flying && self.key_bindings.pressed(KeyAction::FrameAll) {
+            self.frame_all(world);
+        }
+    }
+
+    fn gizmo_camera_lock_system(&mut self, world: &mut World) {
+        let Some(targets) = self
+            .transform_gizmo
+            .drag
+            .as_ref()
+            .and_then(|drag| drag.camera_targets.as_ref())
+        else {
+            return;
+        };
+
+        if let Some(camera_entity) = self.camera_entity
+            && let Some(pan_orbit) = world.get_pan_orbit_camera_mut(camera_entity)
+        {
+            pan_orbit.target_focus = targets.focus;
+            pan_orbit.target_radius = targets.radius;
+            pan_orbit.target_yaw = targets.yaw;
+            pan_orbit.target_pitch = targets.pitch;
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn asset_cycle_system(&mut self, world: &mut World) {
+        if self.asset_library.entries.is_empty() {
+            return;
+        }
+
+        let entry_count = self.asset_library.entries.len();
+        let mut new_index = None;
+
+        if self.key_bindings.pressed(KeyAction::NextAsset) {
+            new_index = Some(match self.asset_library.selected_index {
+                Some(index) => (index + 1) % entry_count,
+                None => 0,
+            });
+        }
+
+        if self.key_bindings.pressed(KeyAction::PreviousAsset) {
+            new_index = Some(match self.asset_library.selected_index {
+                Some(index) => {
+                    if index == 0 {
+                        entry_count - 1
+                    } else {
+                        index - 1
+                    }
+                }
+                None => entry_count - 1,
+            });
+        }
+
+        if let Some(index) = new_index {
+            self.asset_library.selected_index = Some(index);
+            let entry = &self.asset_library.entries[index];
+            let path = entry.path.clone();
+            let asset_type = entry.asset_type;
+
+            match asset_type {
+                AssetType::Model => self.load_gltf_from_path(world, &path),
+                AssetType::Animation => self.load_fbx(world, &path),
+                AssetType::Skybox => self.load_hdr_skybox(world, &path),
+            }
+        }
+    }
+
+    fn load_hdr_skybox(&mut self, world: &mut World, path: &std::path::Path) {
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("Custom HDR")
+            .to_string();
+
+        let already_loaded = self.custom_skyboxes.iter().position(|s| s.path == path);
+
+        if let Some(index) = already_loaded {
+            load_hdr_skybox_from_path(world, path.to_path_buf());
+            world.resources.graphics.atmosphere = Atmosphere::Hdr;
+            self.selected_custom_skybox = Some(index);
+        } else {
+            load_hdr_skybox_from_path(world, path.to_path_buf());
+            world.resources.graphics.atmosphere = Atmosphere::Hdr;
+
+            self.custom_skyboxes.push(CustomSkybox {
+                name,
+                path: path.to_path_buf(),
+            });
+            self.selected_custom_skybox = Some(self.custom_skyboxes.len() - 1);
+        }
+    }
+
+    fn load_hdr_skybox_from_bytes(&mut self, world: &mut World, name: &str, data: &[u8]) {
+        let file_name = std::path::Path::new(name)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("Custom HDR")
+            .to_string();
+
+        let already_exists = self
+            .custom_skyboxes
+            .iter()
+            .position(|s| s.name == file_name);
+
+        load_hdr_skybox(world, data.to_vec());
+        world.resources.graphics.atmosphere = Atmosphere::Hdr;
+
+        if let Some(index) = already_exists {
+            self.selected_custom_skybox = Some(index);
+        } else {
+            self.custom_skyboxes.push(CustomSkybox {
+                name: file_name,
+                path: PathBuf::new(),
+            });
+            self.selected_custom_skybox = Some(self.custom_skyboxes.len() - 1);
+        }
+    }
+
+    fn load_gltf_from_path(&mut self, world: &mut World, path: &std::path::Path) {
+        match nightshade::ecs::prefab::import_gltf_from_path(path) {
+            Ok(result) => {
+                self.clear_scene(world);
+                self.process_gltf_result(world, result, instance_name(path), Some(path));
+            }
+            Err(error) => {
+                tracing::error!("Failed to load glTF file: {}", error);
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn add_gltf_instance_from_path(
+        &mut self,
+        world: &mut World,
+        path: &std::path::Path,
+        placement: Option<Vec3>,
+    ) {
+        if !self.loaded {
+            self.load_gltf_from_path(world, path);
+            return;
+        }
+
+        let result = match nightshade::ecs::prefab::import_gltf_from_path(path) {
+            Ok(result) => result,
+            Err(error) => {
+                tracing::error!("Failed to load glTF file: {}", error);
+                return;
+            }
+        };
+
+        let (scene_min, scene_max) = self
+            .scene_bounds(world)
+            .unwrap_or((Vec3::zeros(), Vec3::zeros()));
+        let (entities, resources) = self.spawn_gltf_result(world, result);
+        self.index_mesh_primitives(world, &resources, path);
+
+        let (instance_min, instance_max) =
+            entities_bounds(world, &entities).unwrap_or((Vec3::zeros(), Vec3::zeros()));
+        let instance_center = (instance_min + instance_max) * 0.5;
+        let anchor = Vec3::new(instance_center.x, instance_min.y, instance_center.z);
+        let scale = self.scene_scale;
+        let half_width = (instance_max.x - instance_min.x) * 0.5 * scale;
+
+        let target = placement.unwrap_or_else(|| {
+            Vec3::new(
+                scene_max.x + half_width + 0.25,
+                scene_min.y,
+                (scene_min.z + scene_max.z) * 0.5,
+            )
+        });
+
+        for entity in &entities {
+            if let Some(transform) = world.get_local_transform_mut(*entity) {
+                transform.translation = (transform.translation - anchor) * scale + target;
+                transform.scale *= scale;
+            }
+            world.mark_local_transform_dirty(*entity);
+        }
+
+        self.model_entities.extend(entities.iter().copied());
+        self.instances.push(ModelInstance {
+            name: instance_name(path),
+            path: Some(path.to_path_buf()),
+            entities,
+            resources,
+        });
+        self.selected_instance = Some(self.instances.len() - 1);
+        self.refresh_animation_state(world);
+    }
+
+    fn remove_instance(&mut self, world: &mut World, index: usize) {
+        if index >= self.instances.len() {
+            return;
+        }
+
+        self.restore_surfaces(world);
+        let instance = self.instances.remove(index);
+        self.model_entities
+            .retain(|entity| !instance.entities.contains(entity));
+        for (entity, _, _) in collect_global_matrices(world, &instance.entities) {
+            self.morph_targets.overrides.remove(&entity);
+        }
+        for entity in instance.entities {
+            self.authored_root_poses.remove(&entity);
+            despawn_recursive_immediate(world, entity);
+        }
+        self.release_resources(world, instance.resources);
+
+        self.transform_gizmo.drag = None;
+        self.selected_entity = None;
+        self.hovered_hit = None;
+        self.selected_instance = match self.selected_instance {
+            Some(selected) if selected == index => None,
+            Some(selected) if selected > index => Some(selected - 1),
+            selected => selected,
+        };
+        self.loaded = !self.model_entities.is_empty();
+        self.refresh_animation_state(world);
+        self.load_camera_bookmarks();
+    }
+
+    fn scene_bounds(&self, world: &World) -> Option<(Vec3, Vec3)> {
+        entities_bounds(world, &self.model_entities)
+    }
+
+    fn load_gltf_from_bytes(&mut self, world: &mut World, name: &str, data: &[u8]) {
+        match nightshade::ecs::prefab::import_gltf_from_bytes(data) {
+            Ok(result) => {
+                self.process_gltf_result(
+                    world,
+                    result,
+                    instance_name(std::path::Path::new(name)),
+                    None,
+                );
+            }
+            Err(error) => {
+                tracing::error!("Failed to load glTF from bytes: {}", error);
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_fbx(&mut self, world: &mut World, path: &std::path::Path) {
+        match nightshade::ecs::prefab::import_fbx_from_path(path) {
+            Ok(result) => {
+                let has_meshes = !result.prefabs.is_empty() || !result.meshes.is_empty();
+
+                if has_meshes {
+                    self.clear_scene(world);
+
+                    let namespace = self.next_instance_namespace();
+                    let mut resources = self.register_model_assets(
+                        world,
+                        &namespace,
+                        result.textures,
+                        result.meshes,
+                    );
+                    let mut entities = Vec::new();
+                    for prefab in &result.prefabs {
+                        let root = nightshade::ecs::prefab::spawn_prefab_with_skins(
+                            world,
+                            prefab,
+                            &result.animations,
+                            &result.skins,
+                            nalgebra_glm::vec3(0.0, 0.0, 0.0),
+                        );
+                        self.scope_spawned_model(world, root, &namespace, &mut resources);
+                        self.record_authored_pose(world, root);
+                        entities.push(root);
+                    }
+                    self.release_unscoped_materials(world, &resources);
+
+                    self.model_entities.extend(entities.iter().copied());
+                    self.instances.push(ModelInstance {
+                        name: instance_name(path),
+                        path: Some(path.to_path_buf()),
+                        entities,
+                        resources,
+                    });
+
+                    self.loaded = true;
+                    self.center_and_fit_model(world);
+                    self.refresh_animation_state(world);
+                    self.load_camera_bookmarks();
+                } else if !result.animations.is_empty() {
+                    if self.model_entities.is_empty() {
+                        tracing::warn!(
+                            "No model loaded - load a model first before adding FBX animations"
+                        );
+                        return;
+                    }
+
+                    let targets = self.animation_import_targets(world, &result.animations);
+                    if targets.is_empty() {
+                        tracing::info!(
+                            "FBX bone names do not match the model, opening retargeting"
+                        );
+                        self.open_retarget(world, result.animations, Default::default());
+                        return;
+                    }
+
+                    let count = result.animations.len();
+                    for entity in &targets {
+                        if let Some(player) = world.get_animation_player_mut(*entity) {
+                            player.add_clips(result.animations.clone());
+
+                            if player.current_clip.is_none() && !player.clips.is_empty() {
+                                player.play(0);
+                            }
+                        }
+                    }
+                    tracing::info!(
+                        "Added {} animation(s) from FBX to {} player(s)",
+                        count,
+                        targets.len()
+                    );
+                } else {
+                    tracing::warn!("FBX file contains no meshes or animations");
+                }
+            }
+            Err(error) => {
+                tracing::error!("Failed to load FBX file: {}", error);
+            }
+        }
+    }
+
+    fn process_gltf_result(
+        &mut self,
+        world: &mut World,
+        result: nightshade::ecs::prefab::GltfLoadResult,
+        name: String,
+        path: Option<&std::path::Path>,
+    ) {
+        let (entities, resources) = self.spawn_gltf_result(world, result);
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = path {
+            self.index_mesh_primitives(world, &resources, path);
+        }
+
+        self.model_entities.extend(entities.iter().copied());
+        self.instances.push(ModelInstance {
+            name,
+            path: path.map(|path| path.to_path_buf()),
+            entities,
+            resources,
+        });
+
+        self.loaded = true;
+        self.center_and_fit_model(world);
+        self.refresh_animation_state(world);
+        self.load_camera_bookmarks();
+    }
+
+    fn insert_mesh(&mut self, world: &mut World, name: String, mesh: Mesh) {
+        if let Some(morph_targets) = &mesh.morph_targets
+            && !morph_targets.names.is_empty()
+        {
+            self.morph_targets
+                .target_names
+                .insert(name.clone(), morph_targets.names.clone());
+        }
+        mesh_cache_insert(&mut world.resources.mesh_cache, name, mesh);
+    }
+
+    fn spawn_gltf_result(
+        &mut self,
+        world: &mut World,
+        result: nightshade::ecs::prefab::GltfLoadResult,
+    ) -> (Vec<Entity>, SceneResources) {
+        let namespace = self.next_instance_namespace();
+        let mut resources =
+            self.register_model_assets(world, &namespace, result.textures, result.meshes);
+        let mut entities = Vec::new();
+        for prefab in &result.prefabs {
+            let root = nightshade::ecs::prefab::spawn_prefab_with_skins(
+                world,
+                prefab,
+                &result.animations,
+                &result.skins,
+                nalgebra_glm::vec3(0.0, 0.0, 0.0),
+            );
+            self.scope_spawned_model(world, root, &namespace, &mut resources);
+            self.record_authored_pose(world, root);
+            entities.push(root);
+        }
+        self.release_unscoped_materials(world, &resources);
+        (entities, resources)
+    }
+
+    /// Records which glTF primitive each triangle of a load's meshes came from, so the
+    /// hover tooltip can name it.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn index_mesh_primitives(
+        &mut self,
+        world: &World,
+        resources: &SceneResources,
+        path: &std::path::Path,
+    ) {
+        let Some((document, _)) = GltfDocument::read_json(path) else {
+            return;
+        };
+        let gltf_meshes = document.mesh_primitive_triangles();
+        for name in &resources.meshes {
+            if let Some(mesh) = mesh_cache_get(&world.resources.mesh_cache, name)
+                && let Some(starts) = primitive_starts(
+                    &gltf_meshes,
+                    instance_resource_name(name),
+                    mesh_triangle_count(mesh),
+                )
+            {
+                self.mesh_primitives.insert(name.clone(), starts);
+            }
+        }
+    }
+
+    /// Keeps a root's transform as loaded, before any placement or fitting, since
+    /// animation channels on the root are expressed in that space.
+    fn record_authored_pose(&mut self, world: &World, root: Entity) {
+        if let Some(transform) = world.get_local_transform(root) {
+            self.authored_root_poses
+                .insert(root, NodePose::of(transform));
+        }
+    }
+
+    fn next_instance_namespace(&mut self) -> String {
+        self.spawned_instance_count += 1;
+        instance_namespace(self.spawned_instance_count)
+    }
+
+    /// Registers a load's textures and meshes under the instance namespace, so an
+    /// additive instance never overwrites another one's assets.
+    fn register_model_assets(
+        &mut self,
+        world: &mut World,
+        namespace: &str,
+        textures: impl IntoIterator<Item = (String, (Vec<u8>, u32, u32))>,
+        meshes: impl IntoIterator<Item = (String, Mesh)>,
+    ) -> SceneResources {
+        let mut resources = SceneResources::default();
+        for (name, (rgba_data, width, height)) in textures {
+            let name = format!("{namespace}{name}");
+            self.surface_debug
+                .texture_sizes
+                .insert(name.clone(), (width, height));
+            #[cfg(not(target_arch = "wasm32"))]
+            self.texture_sources
+                .insert(name.clone(), (rgba_data.clone(), width, height));
+            resources.textures.push(name.clone());
+            world.queue_command(WorldCommand::LoadTexture {
+                name,
+                rgba_data,
+                width,
+                height,
+            });
+        }
+        for (name, mesh) in meshes {
+            let name = format!("{namespace}{name}");
+            resources.meshes.push(name.clone());
+            self.insert_mesh(world, name, mesh);
+        }
+        resources
+    }
+
+    /// Prefabs reference meshes and materials by their names in the file, so every
+    /// spawned entity is pointed at this instance's copies before the next load can
+    /// overwrite the shared names.
+    fn scope_spawned_model(
+        &mut self,
+        world: &mut World,
+        root: Entity,
+        namespace: &str,
+        resources: &mut SceneResources,
+    ) {
+        let scoped = |name: &str| format!("{namespace}{name}");
+        for (entity, _, _) in collect_global_matrices(world, &[root]) {
+            if let Some(render_mesh) = world.get_render_mesh_mut(entity) {
+                render_mesh.name = scoped(&render_mesh.name);
+            }
+            let Some(material_name) = world
+                .get_material_ref(entity)
+                .map(|material_ref| material_ref.name.clone())
+            else {
+                continue;
+            };
+            let scoped_material = scoped(&material_name);
+            let registry = &mut world.resources.material_registry;
+            if nightshade::ecs::material::resources::material_registry_get(
+                registry,
+                &scoped_material,
+            )
+            .is_none()
+                && let Some(material) = nightshade::ecs::material::resources::material_registry_get(
+                    registry,
+                    &material_name,
+                )
+            {
+                let mut material = material.clone();
+                for name in [
+                    &mut material.base_texture,
+                    &mut material.normal_texture,
+                    &mut material.metallic_roughness_texture,
+                    &mut material.occlusion_texture,
+                    &mut material.emissive_texture,
+                ]
+                .into_iter()
+                .flatten()
+                {
+                    *name = scoped(name);
+                }
+                nightshade::ecs::material::resources::material_registry_insert(
+                    registry,
+                    scoped_material.clone(),
+                    material,
+                );
+                resources.materials.push(scoped_material.clone());
+            }
+            if let Some(material_ref) = world.get_material_ref_mut(entity) {
+                material_ref.name = scoped_material;
+            }
+        }
+    }
+
+    /// Spawning registers materials under their file names; once every entity points at
+    /// its scoped copy those entries are unused.
+    fn release_unscoped_materials(&self, world: &mut World, resources: &SceneResources) {
+        for name in &resources.materials {
+            nightshade::ecs::material::resources::material_registry_remove(
+                &mut world.resources.material_registry,
+                instance_resource_name(name),
+            );
+        }
+    }
+
+    fn release_resources(&mut self, world: &mut World, resources: SceneResources) {
+        for name in &resources.meshes {
+            self.morph_targets.target_names.remove(name);
+            self.mesh_bvhs.remove(name);
+            self.mesh_primitives.remove(name);
+        }
+        for name in &resources.textures {
+            self.surface_debug.texture_sizes.remove(name);
+            #[cfg(not(target_arch = "wasm32"))]
+            self.texture_sources.remove(name);
+        }
+        resources.release(world);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn screenshot_ui(&mut self, world: &mut World, ui_context: &egui::Context) {
+        if !self.screenshot.show_window || self.screenshot.pending.is_some() {
+            return;
+        }
+
+        let model_name = self
+            .instances
+            .first()
+            .map(|instance| instance.name.clone())
+            .unwrap_or_else(|| "scene".to_string());
+        let options = &mut self.screenshot;
+        let mut open = true;
+        let mut capture = false;
+
+        egui::Window::new("Screenshot")
+            .open(&mut open)
+            .default_width(340.0)
+            .show(ui_context, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Resolution:");
+                    ui.add(egui::DragValue::new(&mut options.width).range(16..=8192));
+                    ui.label("×");
+                    ui.add(egui::DragValue::new(&mut options.height).range(16..=8192));
+                    egui::ComboBox::from_id_salt("screenshot_resolution")
+                        .selected_text("Presets")
+                        .show_ui(ui, |ui| {
+                            for (label, width, height) in [
+                                ("1080p", 1920, 1080),
+                                ("4K", 3840, 2160),
+                                ("Square 2048", 2048, 2048),
+                                ("Square 4096", 4096, 4096),
+                            ] {
+                                if ui.selectable_label(false, label).clicked() {
+                                    options.width = width;
+                                    options.height = height;
+                                }
+                            }
+                        });
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Supersampling:");
+                    for factor in [1, 2, 4] {
+                        ui.selectable_value(
+                            &mut options.supersampling,
+                            factor,
+                            format!("{}×", factor),
+                        );
+                    }
+                });
+                let render_size = options.width.max(options.height) * options.supersampling;
+                if render_size > 8192 {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(230, 150, 80),
+                        format!("Renders at {} px, which may exceed GPU limits", render_size),
+                    );
+                }
+
+                ui.separator();
+                ui.checkbox(
+                    &mut options.transparent_background,
+                    "Transparent Background",
+                )
+                .on_hover_text("Hide the skybox and keep the background alpha at zero");
+                ui.checkbox(&mut options.hide_ui, "Hide UI and Overlays");
+                ui.checkbox(&mut options.hide_grid, "Hide Grid");
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Folder:");
+                    ui.label(options.directory.display().to_string());
+                    if ui.button("Browse...").clicked()
+                        && let Some(path) = rfd::FileDialog::new().pick_folder()
+                    {
+                        options.directory = path;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("File Name:");
+                    ui.text_edit_singleline(&mut options.filename_template);
+                });
+                ui.label(
+                    egui::RichText::new("Tokens: {model} {width} {height} {index} {timestamp}")
+                        .small()
+                        .weak(),
+                );
+                ui.label(format!("→ {}", options.file_name(&model_name)));
+
+                ui.separator();
+                capture = ui.button("Capture").clicked();
+            });
+
+        self.screenshot.show_window = open;
+        if capture {
+            self.start_screenshot(world, &model_name);
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn start_screenshot(&mut self, world: &mut World, model_name: &str) {
+        let options = &mut self.screenshot;
+        if let Err(error) = std::fs::create_dir_all(&options.directory) {
+            tracing::error!(
+                "Failed to create screenshot folder {}: {}",
+                options.directory.display(),
+                error
+            );
+            return;
+        }
+
+        let path = options.directory.join(options.file_name(model_name));
+        options.counter += 1;
+
+        let graphics = &mut world.resources.graphics;
+        let restore = ScreenshotRestore {
+            atmosphere: graphics.atmosphere,
+            clear_color: graphics.clear_color,
+            show_grid: graphics.show_grid,
+            ui_enabled: world.resources.user_interface.enabled,
+        };
+
+        if options.transparent_background {
+            graphics.atmosphere = Atmosphere::None;
+            graphics.clear_color = [0.0, 0.0, 0.0, 0.0];
+        }
+        if options.hide_grid {
+            graphics.show_grid = false;
+        }
+        graphics.render_resolution = Some((
+            options.width * options.supersampling,
+            options.height * options.supersampling,
+        ));
+        if options.hide_ui {
+            world.resources.user_interface.enabled = false;
+        }
+
+        options.pending = Some(PendingScreenshot {
+            path,
+            supersampling: options.supersampling,
+            requested: false,
+            restore,
+        });
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn screenshot_system(&mut self, world: &mut World) {
+        let Some(pending) = &mut self.screenshot.pending else {
+            return;
+        };
+
+        if !pending.requested {
+            nightshade::ecs::world::request_frame_capture(world);
+            pending.requested = true;
+            return;
+        }
+
+        let Some(frame) = nightshade::ecs::world::take_captured_frame(world) else {
+            return;
+        };
+        let Some(pending) = self.screenshot.pending.take() else {
+            return;
+        };
+
+        let graphics = &mut world.resources.graphics;
+        graphics.atmosphere = pending.restore.atmosphere;
+        graphics.clear_color = pending.restore.clear_color;
+        graphics.show_grid = pending.restore.show_grid;
+        graphics.render_resolution = None;
+        world.resources.user_interface.enabled = pending.restore.ui_enabled;
+        self.previous_atmosphere = pending.restore.atmosphere;
+
+        let path = pending.path;
+        let factor = pending.supersampling;
+        std::thread::spawn(move || {
+            let (width, height, rgba) =
+                downsample_rgba(frame.width, frame.height, &frame.rgba, factor);
+            let result = encode_png(width, height, &rgba)
+                .map_err(|error| error.to_string())
+                .and_then(|png| std::fs::write(&path, png).map_err(|error| error.to_string()));
+            match result {
+                Ok(()) => tracing::info!("Saved screenshot to {}", path.display()),
+                Err(error) => tracing::error!("Failed to write {}: {}", path.display(), error),
+            }
+        });
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn sequence_export_ui(&mut self, world: &mut World, ui_context: &egui::Context) {
+        if !self.sequence_export.show_window || self.sequence_export.run.is_some() {
+            return;
+        }
+
+        let animation = self.animation_entity(world).and_then(|entity| {
+            let player = world.get_animation_player(entity)?;
+            let duration = current_clip_duration(player)?;
+            Some((entity, duration))
+        });
+        let timeline_range =
+            animation.map(|(_, duration)| self.animation_timeline.playback_range(duration));
+        let exit_label = self.key_bindings.label(KeyAction::Exit);
+        let export = &mut self.sequence_export;
+        let mut open = true;
+        let mut start = false;
+
+        egui::Window::new("Render Sequence")
+            .open(&mut open)
+            .default_width(340.0)
+            .show(ui_context, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Source:");
+                    ui.selectable_value(&mut export.source, SequenceSource::Turntable, "Turntable");
+                    ui.add_enabled_ui(animation.is_some(), |ui| {
+                        ui.selectable_value(
+                            &mut export.source,
+                            SequenceSource::AnimationRange,
+                            "Animation Range",
+                        );
+                    });
+                });
+
+                match export.source {
+                    SequenceSource::Turntable => {
+                        ui.horizontal(|ui| {
+                            ui.label("Duration:");
+                            ui.add(
+                                egui::DragValue::new(&mut export.turntable_seconds)
+                                    .speed(0.1)
+                                    .range(0.1..=120.0)
+                                    .suffix("s"),
+                            );
+                        });
+                    }
+                    SequenceSource::AnimationRange => {
+                        let duration = animation.map(|(_, duration)| duration).unwrap_or(0.0);
+                        ui.horizontal(|ui| {
+                            ui.label("Range:");
+                            ui.add(
+                                egui::DragValue::new(&mut export.range_start)
+                                    .speed(0.01)
+                                    .range(0.0..=duration)
+                                    .suffix("s"),
+                            );
+                            ui.label("to");
+                            ui.add(
+                                egui::DragValue::new(&mut export.range_end)
+                                    .speed(0.01)
+                                    .range(0.0..=duration)
+                                    .suffix("s"),
+                            );
+                            if let Some((range_start, range_end)) = timeline_range
+                                && ui.button("Use Timeline").clicked()
+                            {
+                                export.range_start = range_start;
+                                export.range_end = range_end;
+                            }
+                        });
+                    }
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Resolution:");
+                    ui.add(egui::DragValue::new(&mut export.width).range(16..=8192));
+                    ui.label("×");
+                    ui.add(egui::DragValue::new(&mut export.height).range(16..=8192));
+                    egui::ComboBox::from_id_salt("sequence_resolution")
+                        .selected_text("Presets")
+                        .show_ui(ui, |ui| {
+                            for (label, width, height) in [
+                                ("720p", 1280, 720),
+                                ("1080p", 1920, 1080),
+                                ("4K", 3840, 2160),
+                                ("Square 1080", 1080, 1080),
+                            ] {
+                                if ui.selectable_label(false, label).clicked() {
+                                    export.width = width;
+                                    export.height = height;
+                                }
+                            }
+                        });
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Frame Rate:");
+                    ui.add(
+                        egui::DragValue::new(&mut export.frame_rate)
+                            .range(1..=120)
+                            .suffix(" fps"),
+                    );
+                    ui.label(format!("{} frames", export.frame_count()));
+                });
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("Output:");
+                    ui.selectable_value(
+                        &mut export.output,
+                        SequenceOutput::ImageSequence,
+                        "PNG Sequence",
+                    );
+                    ui.selectable_value(&mut export.output, SequenceOutput::Video, "Video (MP4)");
+                });
+                if export.output == SequenceOutput::Video {
+                    ui.checkbox(&mut export.keep_frames, "Keep PNG Frames")
+                        .on_hover_text("Video encoding requires ffmpeg on the PATH");
+                }
+                ui.horizontal(|ui| {
+                    ui.label("Folder:");
+                    ui.label(export.directory.display().to_string());
+                    if ui.button("Browse...").clicked()
+                        && let Some(path) = rfd::FileDialog::new().pick_folder()
+                    {
+                        export.directory = path;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("File Prefix:");
+                    ui.text_edit_singleline(&mut export.file_prefix);
+                });
+
+                ui.separator();
+                let busy = export.job.as_ref().is_some_and(|job| job.result.is_none());
+                ui.horizontal(|ui| {
+                    start = ui.add_enabled(!busy, egui::Button::new("Render")).clicked();
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "The interface is hidden while frames are captured, {} cancels",
+                            exit_label
+                        ))
+                        .small()
+                        .weak(),
+                    );
+                });
+            });
+
+        self.sequence_export.show_window = open;
+        if start {
+            self.start_sequence_export(world, animation.map(|(entity, _)| entity));
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn start_sequence_export(&mut self, world: &mut World, animation_entity: Option<Entity>) {
+        let export = &mut self.sequence_export;
+        if export.file_prefix.trim().is_empty() {
+            export.file_prefix = "frame".to_string();
+        }
+        if let Err(error) = std::fs::create_dir_all(&export.directory) {
+            tracing::error!(
+                "Failed to create output folder {}: {}",
+                export.directory.display(),
+                error
+            );
+            return;
+        }
+
+        let animation = match export.source {
+            SequenceSource::Turntable => None,
+            SequenceSource::AnimationRange => {
+                let Some(entity) = animation_entity else {
+                    tracing::warn!("No animation clip selected to render");
+                    return;
+                };
+                let was_playing = world
+                    .get_animation_player(entity)
+                    .is_some_and(|player| player.playing);
+                Some((entity, was_playing))
+            }
+        };
+
+        let initial_rotations = self
+            .model_entities
+            .iter()
+            .filter_map(|entity| {
+                world
+                    .get_local_transform(*entity)
+                    .map(|transform| (*entity, transform.rotation))
+            })
+            .collect();
+
+        let video = (export.output == SequenceOutput::Video).then(|| SequenceVideo {
+            input: export.directory.join(format!(
+                "{}_%05d.png",
+                export.file_prefix.replace('%', "%%")
+            )),
+            output: export.directory.join(format!("{}.mp4", export.file_prefix)),
+            frame_rate: export.frame_rate,
+            keep_frames: export.keep_frames,
+        });
+        let (frames, frame_receiver) = std::sync::mpsc::sync_channel(SEQUENCE_WRITE_QUEUE);
+        let (events, event_receiver) = std::sync::mpsc::channel();
+        let cancel = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let directory = export.directory.clone();
+        let writer_cancel = cancel.clone();
+        std::thread::spawn(move || {
+            write_sequence(frame_receiver, events, &writer_cancel, directory, video)
+        });
+
+        let frame_count = export.frame_count();
+        world.resources.graphics.render_resolution = Some((export.width, export.height));
+        world.resources.user_interface.enabled = false;
+        export.run = Some(SequenceRun {
+            frame: 0,
+            frame_count,
+            awaiting_capture: false,
+            initial_rotations,
+            animation,
+            frames,
+            pending: None,
+        });
+        export.job = Some(SequenceJob {
+            events: event_receiver,
+            cancel,
+            frame_count,
+            frames_written: 0,
+            encoding: false,
+            errors: Vec::new(),
+            result: None,
+        });
+        tracing::info!(
+            "Rendering {} frames to {} (press {} to cancel)",
+            frame_count,
+            export.directory.display(),
+            self.key_bindings.label(KeyAction::Exit)
+        );
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn sequence_export_system(&mut self, world: &mut World) -> bool {
+        if self.sequence_export.run.is_none() {
+            return false;
+        }
+        if self.key_bindings.pressed(KeyAction::Exit) {
+            self.cancel_sequence_export(world);
+            return false;
+        }
+
+        let export = &mut self.sequence_export;
+        let Some(run) = &mut export.run else {
+            return false;
+        };
+
+        if run.awaiting_capture {
+            let Some(frame) = nightshade::ecs::world::take_captured_frame(world) else {
+                return true;
+            };
+            let path = sequence_frame_path(&export.directory, &export.file_prefix, run.frame);
+            run.pending = Some((path, frame));
+            run.awaiting_capture = false;
+            run.frame += 1;
+        }
+
+        if let Some(pending) = run.pending.take() {
+            match run.frames.try_send(pending) {
+                Ok(()) => {}
+                Err(std::sync::mpsc::TrySendError::Full(pending)) => {
+                    run.pending = Some(pending);
+                    return true;
+                }
+                Err(std::sync::mpsc::TrySendError::Disconnected(_)) => {
+                    self.finish_sequence_export(world);
+                    return false;
+                }
+            }
+        }
+
+        if run.frame >= run.frame_count {
+            self.finish_sequence_export(world);
+            return false;
+        }
+
+        let frame = run.frame;
+        match run.animation {
+            Some((entity, _)) => {
+                if let Some(player) = world.get_animation_player_mut(entity) {
+                    player.pause();
+                    player.time = export.range_start + frame as f32 / export.frame_rate as f32;
+                }
+            }
+            None => {
+                let angle = std::f32::consts::TAU * frame as f32 / run.frame_count as f32;
+                let rotation =
+                    nalgebra_glm::quat_angle_axis(angle, &nalgebra_glm::vec3(0.0, 1.0, 0.0));
+                for (entity, initial) in &run.initial_rotations {
+                    if let Some(transform) = world.get_local_transform_mut(*entity) {
+                        transform.rotation = rotation * initial;
+                    }
+                    world.mark_local_transform_dirty(*entity);
+                }
+            }
+        }
+
+        nightshade::ecs::world::request_frame_capture(world);
+        run.awaiting_capture = true;
+        true
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn cancel_sequence_export(&mut self, world: &mut World) {
+        if let Some(job) = &self.sequence_export.job {
+            job.cancel.store(true, std::sync::atomic::Ordering::Relaxed);
+        }
+        self.finish_sequence_export(world);
+    }
+
+    /// Restores the scene once every frame has been captured. Dropping the run closes
+    /// the frame channel, which lets the writer thread move on to encoding.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn finish_sequence_export(&mut self, world: &mut World) {
+        let Some(run) = self.sequence_export.run.take() else {
+            return;
+        };
+
+        world.resources.graphics.render_resolution = None;
+        world.resources.user_interface.enabled = true;
+        for (entity, rotation) in &run.initial_rotations {
+            if let Some(transform) = world.get_local_transform_mut(*entity) {
+                transform.rotation = *rotation;
+            }
+            world.mark_local_transform_dirty(*entity);
+        }
+        if let Some((entity, was_playing)) = run.animation
+            && was_playing
+            && let Some(player) = world.get_animation_player_mut(entity)
+        {
+            player.resume();
+        }
+        tracing::info!("Captured {} of {} frames", run.frame, run.frame_count);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn sequence_progress_ui(&mut self, ui_context: &egui::Context) {
+        if self.sequence_export.run.is_some() {
+            return;
+        }
+        let Some(job) = &mut self.sequence_export.job else {
+            return;
+        };
+
+        while let Ok(event) = job.events.try_recv() {
+            match event {
+                SequenceEvent::FrameWritten => job.frames_written += 1,
+                SequenceEvent::FrameFailed(error) => {
+                    tracing::error!("Failed to write frame {}", error);
+                    job.errors.push(error);
+                }
+                SequenceEvent::Encoding => job.encoding = true,
+                SequenceEvent::Finished(result) => {
+                    match &result {
+                        Ok(path) => tracing::info!("Rendered sequence to {}", path.display()),
+                        Err(error) => tracing::error!("Sequence render failed: {}", error),
+                    }
+                    job.result = Some(result);
+                }
+            }
+        }
+
+        let mut close = false;
+        egui::Window::new("Render Progress")
+            .collapsible(false)
+            .resizable(false)
+            .show(ui_context, |ui| {
+                match &job.result {
+                    None if job.encoding => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label("Encoding video with ffmpeg...");
+                        });
+                    }
+                    None => {
+                        ui.add(
+                            egui::ProgressBar::new(
+                                job.frames_written as f32 / job.frame_count.max(1) as f32,
+                            )
+                            .text(format!(
+                                "Writing frame {} of {}",
+                                job.frames_written, job.frame_count
+                            )),
+                        );
+                    }
+                    Some(Ok(path)) => {
+                        ui.label(format!("Saved to {}", path.display()));
+                    }
+                    Some(Err(error)) => {
+                        ui.colored_label(ui.visuals().error_fg_color, error);
+                    }
+                }
+
+                if !job.errors.is_empty() {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        format!("{} frame(s) failed to write", job.errors.len()),
+                    )
+                    .on_hover_text(job.errors.join("\n"));
+                }
+
+                ui.separator();
+                if job.result.is_some() {
+                    close = ui.button("Close").clicked();
+                } else if ui.button("Cancel").clicked() {
+                    job.cancel.store(true, std::sync::atomic::Ordering::Relaxed);
+                }
+            });
+
+        if close {
+            self.sequence_export.job = None;
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export_glb(&self, world: &World, path: &std::path::Path) -> Result<(), String> {
+        use serde_json::json;
+
+        if self.model_entities.is_empty() {
+            return Err("No model loaded".to_string());
+        }
+
+        let mut writer = GlbWriter::default();
+        let scene_nodes = collect_global_matrices(world, &self.model_entities);
+        let node_indices: std::collections::HashMap<Entity, usize> = scene_nodes
+            .iter()
+            .enumerate()
+            .map(|(index, (entity, _, _))| (*entity, index))
+            .collect();
+
+        let mut nodes = Vec::new();
+        let mut meshes = Vec::new();
+        let mut mesh_indices = std::collections::HashMap::new();
+        let mut materials = Vec::new();
+        let mut material_indices = std::collections::HashMap::new();
+        let mut textures = Vec::new();
+        let mut images = Vec::new();
+        let mut texture_indices = std::collections::HashMap::new();
+        let mut skins = Vec::new();
+        let mut skin_indices = std::collections::HashMap::new();
+
+        for (entity, parent, _) in &scene_nodes {
+            let mut node = json!({});
+            if let Some(name) = world.get_name(*entity).filter(|name| !name.0.is_empty()) {
+                node["name"] = json!(name.0);
+            }
+
+            if let Some(transform) = world.get_local_transform(*entity) {
+                let (translation, scale) = if parent.is_none() {
+                    (
+                        transform.translation / self.scene_scale + self.scene_center,
+                        transform.scale / self.scene_scale,
+                    )
+                } else {
+                    (transform.translation, transform.scale)
+                };
+                node["translation"] = json!([translation.x, translation.y, translation.z]);
+                node["rotation"] = json!(transform.rotation.coords.as_slice());
+                node["scale"] = json!([scale.x, scale.y, scale.z]);
+            }
+
+            let (mesh_name, material_name) = self.surface_debug.original_names(world, *entity);
+            if let Some(mesh_name) = mesh_name
+                && let Some(mesh) = mesh_cache_get(&world.resources.mesh_cache, &mesh_name)
+            {
+                let material = material_name.as_ref().and_then(|material_name| {
+                    if let Some(index) = material_indices.get(material_name) {
+                        return Some(*index);
+                    }
+                    let material = nightshade::ecs::material::resources::material_registry_get(
+                        &world.resources.material_registry,
+                        material_name,
+                    )?;
+
+                    let mut texture = |name: &Option<String>| -> Option<serde_json::Value> {
+                        let name = name.as_ref()?;
+                        if let Some(index) = texture_indices.get(name) {
+                            return Some(json!({ "index": index }));
+                        }
+                        let Some((rgba_data, width, height)) = self.texture_sources.get(name)
+                        else {
+                            tracing::warn!("Texture {} is not available for export", name);
+                            return None;
+                        };
+                        let png = match encode_png(*width, *height, rgba_data) {
+                            Ok(png) => png,
+                            Err(error) => {
+                                tracing::warn!("Failed to encode texture {}: {}", name, error);
+                                return None;
+                            }
+                        };
+                        let view = writer.buffer_view(&png, None);
+                        images.push(
+                            json!({ "name": instance_resource_name(name), "bufferView": view, "mimeType": "image/png" }),
+                        );
+                        textures.push(json!({ "source": images.len() - 1 }));
+                        texture_indices.insert(name.clone(), textures.len() - 1);
+                        Some(json!({ "index": textures.len() - 1 }))
+                    };
+
+                    let mut pbr = json!({
+                        "baseColorFactor": material.base_color,
+                        "metallicFactor": material.metallic,
+                        "roughnessFactor": material.roughness,
+                    });
+                    if let Some(info) = texture(&material.base_texture) {
+                        pbr["baseColorTexture"] = info;
+                    }
+                    if let Some(info) = texture(&material.metallic_roughness_texture) {
+                        pbr["metallicRoughnessTexture"] = info;
+                    }
+
+                    let mut exported = json!({
+                        "name": instance_resource_name(material_name),
+                        "pbrMetallicRoughness": pbr,
+                        "emissiveFactor": material.emissive_factor,
+                    });
+                    if let Some(info) = texture(&material.normal_texture) {
+                        exported["normalTexture"] = info;
+                    }
+                    if let Some(info) = texture(&material.occlusion_texture) {
+                        exported["occlusionTexture"] = info;
+                    }
+                    if let Some(info) = texture(&material.emissive_texture) {
+                        exported["emissiveTexture"] = info;
+                    }
+                    match material.alpha_mode {
+                        AlphaMode::Opaque => {}
+                        AlphaMode::Mask => {
+                            exported["alphaMode"] = json!("MASK");
+                            exported["alphaCutoff"] = json!(material.alpha_cutoff);
+                        }
+                        AlphaMode::Blend => exported["alphaMode"] = json!("BLEND"),
+                    }
+
+                    materials.push(exported);
+                    material_indices.insert(material_name.clone(), materials.len() - 1);
+                    Some(materials.len() - 1)
+                });
+
+                let key = (mesh_name.clone(), material);
+                let mesh_index = *mesh_indices.entry(key).or_insert_with(|| {
+                    let mut primitive = writer.mesh_primitive(mesh);
+                    if let Some(material) = material {
+                        primitive["material"] = json!(material);
+                    }
+                    meshes.push(json!({
+                        "name": instance_resource_name(&mesh_name),
+                        "primitives": [primitive],
+                    }));
+                    meshes.len() - 1
+                });
+                node["mesh"] = json!(mesh_index);
+            }
+
+            if let Some(skin) = world.get_skin(*entity) {
+                let joints: Vec<usize> = skin
+                    .joints
+                    .iter()
+                    .filter_map(|joint| node_indices.get(joint).copied())
+                    .collect();
+                if joints.len() == skin.joints.len() && !joints.is_empty() {
+                    let skin_index = *skin_indices.entry(joints.clone()).or_insert_with(|| {
+                        let mut exported = json!({ "joints": joints });
+                        if skin.inverse_bind_matrices.len() == joints.len() {
+                            let values: Vec<f32> = skin
+                                .inverse_bind_matrices
+                                .iter()
+                                .flat_map(|matrix| matrix.as_slice().to_vec())
+                                .collect();
+                            exported["inverseBindMatrices"] =
+                                json!(writer.float_accessor(&values, "MAT4", 16, None, false));
+                        }
+                        skins.push(exported);
+                        skins.len() - 1
+                    });
+                    node["skin"] = json!(skin_index);
+                }
+            }
+
+            nodes.push(node);
+        }
+
+        let mut roots = Vec::new();
+        for (index, (_, parent, _)) in scene_nodes.iter().enumerate() {
+            match parent.and_then(|parent| node_indices.get(&parent)) {
+                Some(parent_index) => {
+                    let children = &mut nodes[*parent_index]["children"];
+                    if children.is_null() {
+                        *children = json!([]);
+                    }
+                    if let Some(children) = children.as_array_mut() {
+                        children.push(json!(index));
+                    }
+                }
+                None => roots.push(index),
+            }
+        }
+
+        let mut animations = Vec::new();
+        for player_entity in &self.animation_players {
+            let Some(player) = world.get_animation_player(*player_entity) else {
+                continue;
+            };
+            let mut targets = std::collections::HashMap::new();
+            for (entity, _, _) in collect_global_matrices(world, &[*player_entity]) {
+                if let (Some(name), Some(index)) =
+                    (world.get_name(entity), node_indices.get(&entity))
+                {
+                    targets.entry(name.0.clone()).or_insert(*index);
+                }
+            }
+
+            for clip in &player.clips {
+                let mut samplers = Vec::new();
+                let mut channels = Vec::new();
+                for channel in &clip.channels {
+                    let Some(node) = targets.get(&channel.target_node) else {
+                        continue;
+                    };
+                    let input = &channel.sampler.input;
+                    let output = &channel.sampler.output;
+                    if input.is_empty() || output.is_empty() {
+                        continue;
+                    }
+
+                    // Morph targets are not written to the exported meshes, so weight
+                    // channels would point at meshes without targets.
+                    let (target_path, accessor_type, components) = match channel.target_property {
+                        AnimationProperty::Translation => ("translation", "VEC3", 3),
+                        AnimationProperty::Rotation => ("rotation", "VEC4", 4),
+                        AnimationProperty::Scale => ("scale", "VEC3", 3),
+                        AnimationProperty::MorphWeights => continue,
+                    };
+                    let interpolation = match channel.sampler.interpolation {
+                        AnimationInterpolation::Linear => "LINEAR",
+                        AnimationInterpolation::Step => "STEP",
+                        AnimationInterpolation::CubicSpline => "CUBICSPLINE",
+                    };
+
+                    let input_accessor = writer.float_accessor(input, "SCALAR", 1, None, true);
+                    let output_accessor =
+                        writer.float_accessor(output, accessor_type, components, None, false);
+                    samplers.push(json!({
+                        "input": input_accessor,
+                        "output": output_accessor,
+                        "interpolation": interpolation,
+                    }));
+                    channels.push(json!({
+                        "sampler": samplers.len() - 1,
+                        "target": { "node": node, "path": target_path },
+                    }));
+                }
+
+                if !channels.is_empty() {
+                    animations.push(json!({
+                        "name": clip.name,
+                        "samplers": samplers,
+                        "channels": channels,
+                    }));
+                }
+            }
+        }
+
+        let mut document = json!({
+            "asset": { "version": "2.0", "generator": "gltf-viewer" },
+            "scene": 0,
+            "scenes": [{ "nodes": roots }],
+            "nodes": nodes,
+        });
+        for (key, values) in [
+            ("meshes", meshes),
+            ("materials", materials),
+            ("textures", textures),
+            ("images", images),
+            ("skins", skins),
+            ("animations", animations),
+        ] {
+            if !values.is_empty() {
+                document[key] = json!(values);
+            }
+        }
+
+        std::fs::write(path, writer.finish(document)).map_err(|error| error.to_string())
+    }
+
+    fn clear_scene(&mut self, world: &mut World) {
+        self.restore_surfaces(world);
+        let entities: Vec<Entity> = self.model_entities.drain(..).collect();
+        for entity in entities {
+            despawn_recursive_immediate(world, entity);
+        }
+        for instance in std::mem::take(&mut self.instances) {
+            self.release_resources(world, instance.resources);
+        }
+        self.selected_instance = None;
+        self.selected_entity = None;
+        self.hovered_hit = None;
+        self.measurement.points.clear();
+        self.transform_gizmo.drag = None;
+        self.animation_blend.reset();
+        self.animation_players.clear();
+        self.selected_animation_player = None;
+        self.morph_targets.overrides.clear();
+        self.morph_targets.target_names.clear();
+        self.mesh_bvhs.clear();
+        self.mesh_primitives.clear();
+        self.authored_root_poses.clear();
+        self.surface_debug.texture_sizes.clear();
+        self.auto_rotation.reset();
         #[cfg(not(target_arch = "wasm32"))]
-        self.asset_library_ui(world, ui_context);
+        self.texture_sources.clear();
+        self.loaded = false;
+        self.load_camera_bookmarks();
+    }
+
+    fn center_and_fit_model(&mut self, world: &mut World) {
+        if self.scale_reference.true_scale {
+            self.scene_scale = 1.0;
+            self.scene_center = Vec3::zeros();
+            self.reset_camera(world);
+            return;
+        }
+
+        let Some((min, max)) = self.scene_bounds(world) else {
+            return;
+        };
+
+        let center = (min + max) * 0.5;
+        let size = max - min;
+        let max_dimension = size.x.max(size.y).max(size.z);
+
+        if max_dimension <= 0.0 {
+            return;
+        }
+
+        let scale = 2.0 / max_dimension;
+        self.scene_scale = scale;
+        self.scene_center = center;
+
+        for entity in &self.model_entities {
+            if let Some(transform) = world.get_local_transform_mut(*entity) {
+                transform.translation = (transform.translation - center) * scale;
+                transform.scale *= scale;
+            }
+            world.mark_local_transform_dirty(*entity);
+        }
+
+        self.reset_camera(world);
+    }
+
+    fn set_true_scale(&mut self, world: &mut World, enabled: bool) {
+        if self.scale_reference.true_scale == enabled {
+            return;
+        }
+        self.scale_reference.true_scale = enabled;
+
+        if !enabled {
+            self.center_and_fit_model(world);
+            return;
+        }
+
+        let scale = self.scene_scale;
+        let center = self.scene_center;
+        for entity in &self.model_entities {
+            if let Some(transform) = world.get_local_transform_mut(*entity) {
+                transform.translation = transform.translation / scale + center;
+                transform.scale /= scale;
+            }
+            world.mark_local_transform_dirty(*entity);
+        }
+
+        self.scene_scale = 1.0;
+        self.scene_center = Vec3::zeros();
+        self.reset_camera(world);
+    }
+
+    fn reset_camera(&mut self, world: &mut World) {
+        self.set_fly_camera(world, false);
+        let (focus, radius) = match self.scene_bounds(world) {
+            Some((min, max)) => ((min + max) * 0.5, self.framing_radius(world, min, max)),
+            None => (Vec3::new(0.0, 0.0, 0.0), 3.0),
+        };
+
+        if let Some(camera_entity) = self.camera_entity
+            && let Some(pan_orbit) = world.get_pan_orbit_camera_mut(camera_entity)
+        {
+            pan_orbit.target_focus = focus;
+            pan_orbit.target_radius = radius;
+            pan_orbit.target_yaw = 0.0;
+            pan_orbit.target_pitch = 0.3;
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn scan_directory(&mut self, path: &std::path::Path) {
+        self.asset_library.clear_entries();
+        self.asset_library.view = AssetLibraryView::Entries;
+
+        for entry in walkdir::WalkDir::new(path)
+            .follow_links(true)
+            .into_iter()
+            .filter_map(|e| e.ok())
+        {
+            let entry_path = entry.path();
+            if !entry_path.is_file() {
+                continue;
+            }
+
+            let extension = match entry_path.extension().and_then(|e| e.to_str()) {
+                Some(ext) => ext.to_lowercase(),
+                None => continue,
+            };
+
+            let asset_type = match AssetType::from_extension(&extension) {
+                Some(t) => t,
+                None => continue,
+            };
+
+            let file_stem = entry_path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("Unknown")
+                .to_string();
+
+            let (base_name, tags, categories) = self
+                .parse_polyhaven_info(entry_path)
+                .unwrap_or_else(|| (file_stem, Vec::new(), Vec::new()));
+
+            let display_name = format!("{}.{}", base_name, extension);
+
+            let directory = entry_path
+                .parent()
+                .map(|parent| {
+                    let relative = parent.strip_prefix(path).unwrap_or(parent);
+                    if relative.as_os_str().is_empty() {
+                        ".".to_string()
+                    } else {
+                        relative.to_string_lossy().to_string()
+                    }
+                })
+                .unwrap_or_else(|| ".".to_string());
+
+            let metadata = entry.metadata().ok();
+            let file_size = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
+            let modified = metadata.and_then(|m| m.modified().ok());
+
+            self.asset_library.entries.push(AssetEntry {
+                path: entry_path.to_path_buf(),
+                name: display_name,
+                extension: extension.clone(),
+                asset_type,
+                tags,
+                categories,
+                directory,
+                file_size,
+                modified,
+                statistics: AssetStatistics::default(),
+            });
+        }
+
+        self.asset_library.sort_entries();
+
+        let jobs: Vec<(PathBuf, AssetType)> = self
+            .asset_library
+            .entries
+            .iter()
+            .map(|entry| (entry.path.clone(), entry.asset_type))
+            .collect();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for (path, asset_type) in jobs {
+                let statistics = read_asset_statistics(&path, asset_type);
+                if sender.send((path, statistics)).is_err() {
+                    return;
+                }
+            }
+        });
+        self.asset_library.pending_statistics = Some(receiver);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn parse_polyhaven_info(
+        &self,
+        asset_path: &std::path::Path,
+    ) -> Option<(String, Vec<String>, Vec<String>)> {
+        let parent = asset_path.parent()?;
+        let info_path = parent.join("info.json");
+
+        if !info_path.exists() {
+            return None;
+        }
+
+        let content = std::fs::read_to_string(&info_path).ok()?;
+        let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+
+        let name = json
+            .get("name")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())?;
+
+        let tags = json
+            .get("tags")
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let categories = json
+            .get("categories")
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some((name, tags, categories))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn asset_library_ui(&mut self, world: &mut World, ui_context: &egui::Context) {
+        if !self.asset_library.show_window {
+            return;
+        }
+
+        self.asset_library.receive_statistics();
+        self.asset_library.receive_duplicates();
+
+        let mut asset_to_load: Option<(PathBuf, AssetType)> = None;
+        let mut directory_to_scan: Option<PathBuf> = None;
+        let mut should_close = false;
+
+        egui::Window::new("Asset Library")
+            .default_width(400.0)
+            .default_height(500.0)
+            .show(ui_context, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("Scan Directory").clicked()
+                        && let Some(path) = rfd::FileDialog::new().pick_folder()
+                    {
+                        directory_to_scan = Some(path);
+                    }
+
+                    if ui.button("Clear").clicked() {
+                        self.asset_library.clear_entries();
+                    }
+
+                    if ui
+                        .add_enabled(
+                            !self.asset_library.entries.is_empty()
+                                && self.asset_library.pending_duplicates.is_none(),
+                            egui::Button::new("Find Duplicates"),
+                        )
+                        .on_hover_text("Compare scanned files and group identical content")
+                        .clicked()
+                    {
+                        self.asset_library.find_duplicates();
+                    }
+
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("X").clicked() {
+                            should_close = true;
+                        }
+                    });
+                });
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.selectable_value(
+                        &mut self.asset_library.view,
+                        AssetLibraryView::Entries,
+                        format!("Library ({})", self.asset_library.entries.len()),
+                    );
+                    ui.selectable_value(
+                        &mut self.asset_library.view,
+                        AssetLibraryView::Duplicates,
+                        format!("Duplicates ({})", self.asset_library.duplicate_groups.len()),
+                    );
+                    if self.asset_library.pending_duplicates.is_some() {
+                        ui.spinner();
+                        ui.weak("Comparing files...");
+                    } else if self.asset_library.pending_statistics.is_some() {
+                        ui.spinner();
+                        ui.weak("Reading statistics...");
+                    }
+                });
+
+                ui.separator();
+
+                if self.asset_library.view == AssetLibraryView::Duplicates {
+                    if let Some((path, asset_type)) =
+                        duplicate_groups_ui(ui, &self.asset_library.duplicate_groups)
+                    {
+                        asset_to_load = Some((path, asset_type));
+                    }
+                    return;
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Search:");
+                    ui.text_edit_singleline(&mut self.asset_library.search_query);
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Filter:");
+                    egui::ComboBox::from_id_salt("asset_type_filter")
+                        .selected_text(
+                            self.asset_library
+                                .filter_type
+                                .map(|t| t.name())
+                                .unwrap_or("All"),
+                        )
+                        .show_ui(ui, |ui| {
+                            if ui
+                                .selectable_label(self.asset_library.filter_type.is_none(), "All")
+                                .clicked()
+                            {
+                                self.asset_library.filter_type = None;
+                            }
+                            for asset_type in
+                                [AssetType::Model, AssetType::Animation, AssetType::Skybox]
+                            {
+                                if ui
+                                    .selectable_label(
+                                        self.asset_library.filter_type == Some(asset_type),
+                                        asset_type.name(),
+                                    )
+                                    .clicked()
+                                {
+                                    self.asset_library.filter_type = Some(asset_type);
+                                }
+                            }
+                        });
+                });
+
+                ui.horizontal(|ui| {
+                    let mut resort = false;
+
+                    ui.label("Sort:");
+                    egui::ComboBox::from_id_salt("asset_sort_key")
+                        .selected_text(self.asset_library.sort_key.name())
+                        .show_ui(ui, |ui| {
+                            for sort_key in AssetSortKey::ALL {
+                                if ui
+                                    .selectable_value(
+                                        &mut self.asset_library.sort_key,
+                                        *sort_key,
+                                        sort_key.name(),
+                                    )
+                                    .changed()
+                                {
+                                    resort = true;
+                                }
+                            }
+                        });
+
+                    if ui
+                        .button(
+                            self.asset_library
+                                .sort_key
+                                .direction_label(self.asset_library.sort_ascending),
+                        )
+                        .clicked()
+                    {
+                        self.asset_library.sort_ascending = !self.asset_library.sort_ascending;
+                        resort = true;
+                    }
+
+                    if resort {
+                        self.asset_library.sort_entries();
+                    }
+
+                    ui.label("Group:");
+                    egui::ComboBox::from_id_salt("asset_grouping")
+                        .selected_text(self.asset_library.grouping.name())
+                        .show_ui(ui, |ui| {
+                            for grouping in AssetGrouping::ALL {
+                                ui.selectable_value(
+                                    &mut self.asset_library.grouping,
+                                    *grouping,
+                                    grouping.name(),
+                                );
+                            }
+                        });
+                });
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("Show:");
+                    ui.checkbox(&mut self.asset_library.show_glb, "glb");
+                    ui.checkbox(&mut self.asset_library.show_gltf, "gltf");
+                    ui.checkbox(&mut self.asset_library.show_fbx, "fbx");
+                    ui.checkbox(&mut self.asset_library.show_hdr, "hdr");
+                });
+
+                ui.checkbox(&mut self.asset_library.add_to_scene, "Add models to scene")
+                    .on_hover_text(
+                        "Place models next to the loaded scene instead of replacing it. \
+                         Models can also be dragged into the viewport.",
+                    );
+
+                ui.separator();
+
+                let search_query_lower = self.asset_library.search_query.to_lowercase();
+
+                let groups = self.asset_library.grouped_entries(&search_query_lower);
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (group_name, indices) in &groups {
+                        if self.asset_library.grouping == AssetGrouping::None {
+                            for index in indices {
+                                let entry = &self.asset_library.entries[*index];
+                                if asset_entry_ui(ui, entry) {
+                                    asset_to_load = Some((entry.path.clone(), entry.asset_type));
+                                }
+                            }
+                            continue;
+                        }
+
+                        egui::CollapsingHeader::new(format!("{} ({})", group_name, indices.len()))
+                            .id_salt(("asset_group", group_name))
+                            .default_open(true)
+                            .show(ui, |ui| {
+                                for index in indices {
+                                    let entry = &self.asset_library.entries[*index];
+                                    if asset_entry_ui(ui, entry) {
+                                        asset_to_load =
+                                            Some((entry.path.clone(), entry.asset_type));
+                                    }
+                                }
+                            });
+                    }
+                });
+            });
+
+        if should_close {
+            self.asset_library.show_window = false;
+        }
+
+        if let Some(path) = directory_to_scan {
+            self.scan_directory(&path);
+        }
+
+        if let Some((path, asset_type)) = asset_to_load {
+            match asset_type {
+                AssetType::Model if self.asset_library.add_to_scene => {
+                    self.add_gltf_instance_from_path(world, &path, None)
+                }
+                AssetType::Model => self.load_gltf_from_path(world, &path),
+                AssetType::Animation => self.load_fbx(world, &path),
+                AssetType::Skybox => self.load_hdr_skybox(world, &path),
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn scene_drop_ui(&mut self, world: &mut World, ui_context: &egui::Context) {
+        let Some(path) = egui::DragAndDrop::payload::<PathBuf>(ui_context) else {
+            return;
+        };
+
+        let Some(pointer) = ui_context.input(|i| i.pointer.hover_pos()) else {
+            return;
+        };
+
+        if ui_context.is_pointer_over_area() {
+            return;
+        }
+
+        let painter = ui_context.layer_painter(egui::LayerId::new(
+            egui::Order::Tooltip,
+            egui::Id::new("scene_drop_hint"),
+        ));
+        painter.text(
+            pointer + egui::vec2(16.0, 16.0),
+            egui::Align2::LEFT_TOP,
+            format!("Add {} to scene", instance_name(&path)),
+            egui::FontId::proportional(14.0),
+            egui::Color32::WHITE,
+        );
+
+        if !ui_context.input(|i| i.pointer.any_released()) {
+            return;
+        }
+
+        egui::DragAndDrop::clear_payload(ui_context);
+
+        let placement = self.scene_bounds(world).and_then(|(min, _)| {
+            let projection = ViewportProjection::new(world, ui_context.content_rect())?;
+            let (origin, direction) = projection.screen_ray(pointer);
+            if direction.y.abs() <= f32::EPSILON {
+                return None;
+            }
+            let distance = (min.y - origin.y) / direction.y;
+            (distance > 0.0).then(|| origin + direction * distance)
+        });
+
+        self.add_gltf_instance_from_path(world, &path, placement);
+    }
+
+    fn morph_targets_ui(&mut self, world: &mut World, ui: &mut egui::Ui) {
+        let driven = self.animated_entities(world);
+        let mut morph_meshes = Vec::new();
+        for (entity, _, _) in collect_global_matrices(world, &self.model_entities) {
+            let Some(morph_weights) = world.get_morph_weights(entity) else {
+                continue;
+            };
+            if morph_weights.weights.is_empty() {
+                continue;
+            }
+            let render_mesh = self.surface_debug.original_names(world, entity).0;
+            let mesh_name = render_mesh
+                .as_deref()
+                .map(|name| instance_resource_name(name).to_string())
+                .unwrap_or_else(|| entity_display_name(world, entity));
+            let target_names = render_mesh
+                .and_then(|name| self.morph_targets.target_names.get(&name))
+                .cloned()
+                .unwrap_or_default();
+            morph_meshes.push((entity, mesh_name, target_names, driven.contains(&entity)));
+        }
+
+        if morph_meshes.is_empty() {
+            return;
+        }
+
+        let morph_targets = &mut self.morph_targets;
+
+        ui.collapsing("Morph Targets", |ui| {
+            ui.checkbox(
+                &mut morph_targets.override_while_paused,
+                "Override Animated Weights While Paused",
+            );
+
+            ui.horizontal(|ui| {
+                if ui.button("Zero All").clicked() {
+                    for (entity, _, _, _) in &morph_meshes {
+                        if let Some(morph_weights) = world.get_morph_weights_mut(*entity) {
+                            morph_weights
+                                .weights
+                                .iter_mut()
+                                .for_each(|weight| *weight = 0.0);
+                            morph_targets
+                                .overrides
+                                .insert(*entity, morph_weights.weights.clone());
+                        }
+                    }
+                }
+                if ui.button("Clear Overrides").clicked() {
+                    morph_targets.overrides.clear();
+                }
+            });
+
+            egui::ScrollArea::vertical()
+                .id_salt("morph_targets")
+                .max_height(320.0)
+                .show(ui, |ui| {
+                    for (entity, mesh_name, target_names, animated) in &morph_meshes {
+                        let Some(morph_weights) = world.get_morph_weights_mut(*entity) else {
+                            continue;
+                        };
+                        egui::CollapsingHeader::new(format!(
+                            "{} ({})",
+                            mesh_name,
+                            morph_weights.weights.len()
+                        ))
+                        .id_salt(("morph_mesh", *entity))
+                        .show(ui, |ui| {
+                            if *animated && morph_targets.overrides.contains_key(entity) {
+                                ui.label(
+                                    "Playback is driving weights; overrides apply when paused",
+                                );
+                            }
+                            let mut changed = false;
+                            for (index, weight) in morph_weights.weights.iter_mut().enumerate() {
+                                let label = target_names
+                                    .get(index)
+                                    .cloned()
+                                    .unwrap_or_else(|| format!("Target {}", index));
+                                changed |= ui
+                                    .add(egui::Slider::new(weight, 0.0..=1.0).text(label))
+                                    .changed();
+                            }
+                            if changed {
+                                morph_targets
+                                    .overrides
+                                    .insert(*entity, morph_weights.weights.clone());
+                            }
+                        });
+                    }
+                });
+        });
+    }
+
+    fn morph_override_system(&mut self, world: &mut World) {
+        if !self.morph_targets.override_while_paused || self.morph_targets.overrides.is_empty() {
+            return;
+        }
+        let driven = self.animated_entities(world);
+        for (entity, weights) in &self.morph_targets.overrides {
+            if driven.contains(entity) {
+                continue;
+            }
+            if let Some(morph_weights) = world.get_morph_weights_mut(*entity) {
+                for (weight, value) in morph_weights.weights.iter_mut().zip(weights) {
+                    *weight = *value;
+                }
+            }
+        }
+    }
+
+    fn animated_entities(&self, world: &World) -> std::collections::HashSet<Entity> {
+        let playing: Vec<Entity> = self
+            .animation_players
+            .iter()
+            .copied()
+            .filter(|entity| {
+                world
+                    .get_animation_player(*entity)
+                    .is_some_and(|player| player.playing)
+            })
+            .collect();
+        collect_global_matrices(world, &playing)
+            .into_iter()
+            .map(|(entity, _, _)| entity)
+            .collect()
+    }
+
+    fn instances_ui(&mut self, world: &mut World, ui: &mut egui::Ui) {
+        if self.instances.is_empty() {
+            return;
+        }
+
+        let mut instance_to_remove = None;
+
+        ui.collapsing("Scene", |ui| {
+            ui.checkbox(&mut self.show_hierarchy, "Show Hierarchy");
+
+            for (index, instance) in self.instances.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    let is_selected = self.selected_instance == Some(index);
+                    let response = ui.selectable_label(is_selected, &instance.name);
+                    let response = match &instance.path {
+                        Some(path) => response.on_hover_text(path.to_string_lossy()),
+                        None => response,
+                    };
+                    if response.clicked() {
+                        self.selected_instance = if is_selected { None } else { Some(index) };
+                        self.selected_entity = None;
+                    }
+
+                    if ui.small_button("Remove").clicked() {
+                        instance_to_remove = Some(index);
+                    }
+                });
+            }
+        });
+
+        if let Some(index) = instance_to_remove {
+            self.remove_instance(world, index);
+        }
+    }
+
+    fn gizmo_targets(&self) -> Vec<Entity> {
+        if let Some(entity) = self.selected_entity {
+            return vec![entity];
+        }
+        self.selected_instance
+            .and_then(|index| self.instances.get(index))
+            .map(|instance| instance.entities.clone())
+            .unwrap_or_default()
+    }
+
+    fn transform_ui(&mut self, world: &mut World, ui: &mut egui::Ui) {
+        let targets = self.gizmo_targets();
+        let Some(entity) = targets.first().copied() else {
+            return;
+        };
+
+        ui.collapsing("Transform", |ui| {
+            let gizmo = &mut self.transform_gizmo;
+
+            ui.checkbox(&mut gizmo.enabled, "Show Gizmo");
+
+            ui.horizontal(|ui| {
+                for mode in GizmoMode::ALL {
+                    ui.selectable_value(&mut gizmo.mode, mode, mode.name());
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Space:");
+                for space in [GizmoSpace::World, GizmoSpace::Local] {
+                    ui.selectable_value(&mut gizmo.space, space, space.name());
+                }
+            });
+
+            ui.checkbox(&mut gizmo.snapping, "Snapping")
+                .on_hover_text("Hold Ctrl while dragging to toggle snapping");
+
+            if gizmo.snapping {
+                ui.horizontal(|ui| {
+                    ui.label("Move:");
+                    ui.add(
+                        egui::DragValue::new(&mut gizmo.translate_snap)
+                            .speed(0.01)
+                            .range(0.001..=10.0),
+                    );
+                    ui.label("Rotate:");
+                    ui.add(
+                        egui::DragValue::new(&mut gizmo.rotate_snap_degrees)
+                            .speed(1.0)
+                            .range(1.0..=90.0)
+                            .suffix("°"),
+                    );
+                    ui.label("Scale:");
+                    ui.add(
+                        egui::DragValue::new(&mut gizmo.scale_snap)
+                            .speed(0.01)
+                            .range(0.01..=1.0),
+                    );
+                });
+            }
+
+            ui.separator();
+
+            let label = if targets.len() > 1 {
+                format!("Inspector ({} roots, moved with the first)", targets.len())
+            } else {
+                "Inspector".to_string()
+            };
+            ui.label(label);
+
+            let Some(transform) = world.get_local_transform(entity) else {
+                return;
+            };
+            let before = (transform.translation, transform.rotation, transform.scale);
+            let (mut translation, mut rotation, mut scale) = before;
+
+            let mut changed = false;
+
+            ui.horizontal(|ui| {
+                ui.label("Translation:");
+                for value in translation.iter_mut() {
+                    changed |= ui
+                        .add(egui::DragValue::new(value).speed(0.01).fixed_decimals(3))
+                        .changed();
+                }
+            });
+
+            let euler = nalgebra_glm::quat_euler_angles(&rotation);
+            let mut degrees = [
+                euler.z.to_degrees(),
+                euler.y.to_degrees(),
+                euler.x.to_degrees(),
+            ];
+            let mut rotation_changed = false;
+            ui.horizontal(|ui| {
+                ui.label("Rotation:");
+                for value in degrees.iter_mut() {
+                    rotation_changed |= ui
+                        .add(
+                            egui::DragValue::new(value)
+                                .speed(0.5)
+                                .fixed_decimals(1)
+                                .suffix("°"),
+                        )
+                        .changed();
+                }
+            });
+            if rotation_changed {
+                rotation = euler_degrees_to_quat(degrees);
+                changed = true;
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("Scale:");
+                for value in scale.iter_mut() {
+                    changed |= ui
+                        .add(
+                            egui::DragValue::new(value)
+                                .speed(0.01)
+                                .range(0.001..=f32::MAX)
+                                .fixed_decimals(3),
+                        )
+                        .changed();
+                }
+            });
+
+            if !changed {
+                return;
+            }
+            let after = (translation, rotation, scale);
+            for target in &targets {
+                if let Some(transform) = world.get_local_transform_mut(*target) {
+                    let edited = if *target == entity {
+                        after
+                    } else {
+                        follow_root_edit(
+                            before,
+                            after,
+                            (transform.translation, transform.rotation, transform.scale),
+                        )
+                    };
+                    (transform.translation, transform.rotation, transform.scale) = edited;
+                }
+                world.mark_local_transform_dirty(*target);
+            }
+        });
+    }
+
+    fn selection_ui(&mut self, world: &mut World, ui_context: &egui::Context) {
+        self.hovered_hit = None;
+        self.measurement.hover_point = None;
+
+        if self.transform_gizmo.hovered
+            || self.transform_gizmo.drag.is_some()
+            || ui_context.is_pointer_over_area()
+        {
+            return;
+        }
+
+        let Some(pointer) = ui_context.input(|i| i.pointer.hover_pos()) else {
+            return;
+        };
+
+        let Some(projection) = ViewportProjection::new(world, ui_context.content_rect()) else {
+            return;
+        };
+
+        let (origin, direction) = projection.screen_ray(pointer);
+        let hit = raycast_scene(
+            world,
+            &self.model_entities,
+            &mut self.mesh_bvhs,
+            &self.mesh_primitives,
+            &origin,
+            &direction,
+        );
+
+        if self.measurement.enabled {
+            self.measurement.hover_point = hit.as_ref().map(|hit| {
+                snap_measurement_point(
+                    hit,
+                    &projection,
+                    pointer,
+                    self.measurement.snap_to_vertices,
+                    self.measurement.snap_to_edges,
+                )
+            });
+
+            if ui_context.input(|i| i.pointer.primary_clicked())
+                && let Some((point, _)) = self.measurement.hover_point
+            {
+                self.measurement.add_point(point);
+            }
+            return;
+        }
+
+        if ui_context.input(|i| i.pointer.primary_clicked()) {
+            match &hit {
+                Some(hit) => {
+                    self.selected_instance = self
+                        .instances
+                        .iter()
+                        .position(|instance| instance.entities.contains(&hit.root));
+                    self.selected_entity = Some(hit.entity);
+                    self.reveal_in_hierarchy = true;
+                }
+                None => {
+                    self.selected_instance = None;
+                    self.selected_entity = None;
+                }
+            }
+        }
+
+        let Some(hit) = hit else {
+            return;
+        };
+
+        let painter = ui_context.layer_painter(egui::LayerId::new(
+            egui::Order::Background,
+            egui::Id::new("hover_highlight"),
+        ));
+        let highlight = egui::Color32::from_rgb(255, 200, 60);
+
+        if let Some(bounding_volume) = world.get_bounding_volume(hit.entity)
+            && let Some(parent_matrix) = find_parent_matrix(world, &self.model_entities, hit.entity)
+        {
+            let global_matrix = parent_matrix * local_transform_matrix(world, hit.entity);
+            let (min, max) = local_bounds(bounding_volume);
+            let corners =
+                box_corners(&min, &max).map(|corner| transform_point(&global_matrix, &corner));
+            draw_box_edges(
+                &painter,
+                &projection,
+                &corners,
+                egui::Stroke::new(1.5, highlight),
+            );
+        }
+
+        let points: Vec<egui::Pos2> = hit
+            .triangle
+            .iter()
+            .filter_map(|vertex| projection.world_to_screen(vertex))
+            .collect();
+        if points.len() == 3 {
+            painter.add(egui::Shape::convex_polygon(
+                points,
+                highlight.gamma_multiply(0.35),
+                egui::Stroke::new(1.0, highlight),
+            ));
+        }
+
+        let (mesh_name, material_name) = self.surface_debug.original_names(world, hit.entity);
+        let [mesh_name, material_name] = [mesh_name, material_name].map(|name| {
+            name.as_deref()
+                .map_or("None", instance_resource_name)
+                .to_string()
+        });
+
+        egui::Area::new(egui::Id::new("hover_tooltip"))
+            .fixed_pos(pointer + egui::vec2(16.0, 16.0))
+            .order(egui::Order::Tooltip)
+            .interactable(false)
+            .show(ui_context, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(format!("Node: {}", entity_display_name(world, hit.entity)));
+                    ui.label(format!("Mesh: {}", mesh_name));
+                    ui.label(format!("Material: {}", material_name));
+                    ui.label(format!(
+                        "Primitive: {}",
+                        hit.primitive.map_or_else(
+                            || "Unknown".to_string(),
+                            |primitive| primitive.to_string()
+                        )
+                    ));
+                    ui.label(format!("Triangle: {}", hit.triangle_index));
+                    ui.label(format!("Distance: {:.3}", hit.distance));
+                });
+            });
+
+        self.hovered_hit = Some(hit);
+    }
+
+    fn measurement_ui(&mut self, world: &mut World, ui: &mut egui::Ui) {
+        ui.collapsing("Measure", |ui| {
+            let measurement = &mut self.measurement;
+
+            ui.checkbox(&mut measurement.enabled, "Measurement Mode")
+                .on_hover_text("Click on the model to place measurement points");
+
+            ui.horizontal(|ui| {
+                ui.label("Tool:");
+                for tool in [MeasurementTool::Distance, MeasurementTool::Angle] {
+                    if ui
+                        .selectable_value(&mut measurement.tool, tool, tool.name())
+                        .changed()
+                    {
+                        measurement.points.clear();
+                    }
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Snap:");
+                ui.checkbox(&mut measurement.snap_to_vertices, "Vertices");
+                ui.checkbox(&mut measurement.snap_to_edges, "Edges");
+            });
+
+            ui.checkbox(&mut measurement.show_scene_bounds, "Show Scene Bounds");
+
+            if ui.button("Clear Points").clicked() {
+                measurement.points.clear();
+            }
+
+            ui.separator();
+
+            let units_per_scene_unit = 1.0 / self.scene_scale;
+
+            match measurement.tool {
+                MeasurementTool::Distance => {
+                    let mut total = 0.0;
+                    for (index, segment) in measurement.points.windows(2).enumerate() {
+                        let length =
+                            nalgebra_glm::distance(&segment[0], &segment[1]) * units_per_scene_unit;
+                        total += length;
+                        ui.label(format!("Segment {}: {}", index + 1, format_length(length)));
+                    }
+                    if measurement.points.len() > 2 {
+                        ui.label(format!("Total: {}", format_length(total)));
+                    }
+                }
+                MeasurementTool::Angle => {
+                    if let [a, b, c] = measurement.points.as_slice() {
+                        ui.label(format!(
+                            "Angle: {:.2}°",
+                            angle_between(a, b, c).to_degrees()
+                        ));
+                    } else {
+                        ui.label("Place three points (vertex in the middle)");
+                    }
+                }
+            }
+
+            ui.separator();
+
+            if let Some((min, max)) = self.scene_bounds(world) {
+                let size = (max - min) * units_per_scene_unit;
+                ui.label(format!(
+                    "Scene: {} x {} x {}",
+                    format_length(size.x),
+                    format_length(size.y),
+                    format_length(size.z)
+                ));
+            }
+
+            let targets = self.gizmo_targets();
+            if !targets.is_empty()
+                && let Some((min, max)) =
+                    entities_world_bounds(world, &self.model_entities, &targets)
+            {
+                let size = (max - min) * units_per_scene_unit;
+                ui.label(format!(
+                    "Selection: {} x {} x {}",
+                    format_length(size.x),
+                    format_length(size.y),
+                    format_length(size.z)
+                ));
+            }
+
+            ui.weak("Reported in the model's original units, before auto-fit scaling");
+        });
+    }
+
+    fn measurement_overlay_ui(&mut self, world: &mut World, ui_context: &egui::Context) {
+        let measurement = &self.measurement;
+        if !measurement.enabled && measurement.points.is_empty() && !measurement.show_scene_bounds {
+            return;
+        }
+
+        let Some(projection) = ViewportProjection::new(world, ui_context.content_rect()) else {
+            return;
+        };
+
+        let painter = ui_context.layer_painter(egui::LayerId::new(
+            egui::Order::Background,
+            egui::Id::new("measurement_overlay"),
+        ));
+        let units_per_scene_unit = 1.0 / self.scene_scale;
+        let line_color = egui::Color32::from_rgb(255, 230, 90);
+        let font = egui::FontId::proportional(14.0);
+
+        if measurement.show_scene_bounds
+            && let Some((min, max)) = self.scene_bounds(world)
+        {
+            let corners = box_corners(&min, &max);
+            draw_box_edges(
+                &painter,
+                &projection,
+                &corners,
+                egui::Stroke::new(1.0, egui::Color32::from_rgb(120, 200, 255)),
+            );
+
+            let size = (max - min) * units_per_scene_unit;
+            for (start, end, length) in [
+                (corners[0], corners[1], size.x),
+                (corners[0], corners[2], size.y),
+                (corners[0], corners[4], size.z),
+            ] {
+                if let Some(position) = projection.world_to_screen(&((start + end) * 0.5)) {
+                    draw_label(&painter, position, format_length(length), font.clone());
+                }
+            }
+        }
+
+        let screen_points: Vec<Option<egui::Pos2>> = measurement
+            .points
+            .iter()
+            .map(|point| projection.world_to_screen(point))
+            .collect();
+
+        for (segment, screen_segment) in measurement.points.windows(2).zip(screen_points.windows(2))
+        {
+            let (Some(start), Some(end)) = (screen_segment[0], screen_segment[1]) else {
+                continue;
+            };
+            painter.line_segment([start, end], egui::Stroke::new(2.0, line_color));
+
+            if measurement.tool == MeasurementTool::Distance {
+                let length =
+                    nalgebra_glm::distance(&segment[0], &segment[1]) * units_per_scene_unit;
+                draw_label(
+                    &painter,
+                    start + (end - start) * 0.5,
+                    format_length(length),
+                    font.clone(),
+                );
+            }
+        }
+
+        if measurement.tool == MeasurementTool::Angle
+            && let [a, b, c] = measurement.points.as_slice()
+            && let Some(position) = screen_points[1]
+        {
+            draw_label(
+                &painter,
+                position + egui::vec2(0.0, -18.0),
+                format!("{:.2}°", angle_between(a, b, c).to_degrees()),
+                font.clone(),
+            );
+        }
+
+        for position in screen_points.iter().flatten() {
+            painter.circle_filled(*position, 4.0, line_color);
+        }
+
+        if let Some((point, snap_kind)) = measurement.hover_point
+            && let Some(position) = projection.world_to_screen(&point)
+        {
+            painter.circle_stroke(position, 7.0, egui::Stroke::new(2.0, snap_kind.color()));
+
+            if let Some(last) = measurement.points.last()
+                && let Some(last_position) = projection.world_to_screen(last)
+                && !(measurement.tool == MeasurementTool::Angle && measurement.points.len() >= 3)
+            {
+                painter.line_segment(
+                    [last_position, position],
+                    egui::Stroke::new(1.0, line_color.gamma_multiply(0.6)),
+                );
+                if measurement.tool == MeasurementTool::Distance {
+                    let length = nalgebra_glm::distance(last, &point) * units_per_scene_unit;
+                    draw_label(
+                        &painter,
+                        last_position + (position - last_position) * 0.5,
+                        format_length(length),
+                        font,
+                    );
+                }
+            }
+        }
+    }
+
+    fn scale_reference_layout(&self, world: &World) -> Option<ScaleReferenceLayout> {
+        let reference = &self.scale_reference;
+        if !reference.true_scale || !self.loaded {
+            return None;
+        }
+        let (min, max) = self.scene_bounds(world)?;
+
+        let grid = reference.show_grid.then(|| {
+            let extent = [min.x, min.z, max.x, max.z]
+                .iter()
+                .fold(1.0_f32, |extent, value| extent.max(value.abs()))
+                * 1.25;
+            let spacing = 10.0_f32.powf((extent / 2.0).log10().floor());
+            (spacing, (extent / spacing).ceil() as i32)
+        });
+        let human = reference
+            .show_human
+            .then(|| Vec3::new(max.x + 0.5, 0.0, (min.z + max.z) * 0.5));
+
+        Some(ScaleReferenceLayout {
+            grid,
+            human,
+            origin: reference.show_origin,
+        })
+    }
+
+    fn scale_reference_system(&mut self, world: &mut World) {
+        let layout = self.scale_reference_layout(world);
+        if layout == self.scale_reference.layout {
+            return;
+        }
+        let rebuild = layout.map(|layout| layout.geometry())
+            != self.scale_reference.layout.map(|layout| layout.geometry());
+        self.scale_reference.layout = layout;
+
+        if !rebuild {
+            if let Some(base) = layout.and_then(|layout| layout.human)
+                && let Some(entity) = self.scale_reference.human_entity
+            {
+                if let Some(transform) = world.get_local_transform_mut(entity) {
+                    transform.translation = base;
+                }
+                world.mark_local_transform_dirty(entity);
+            }
+            return;
+        }
+
+        if let Some(entity) = self.scale_reference.entity.take() {
+            despawn_recursive_immediate(world, entity);
+        }
+        self.scale_reference.human_entity = None;
+        std::mem::take(&mut self.scale_reference.resources).release(world);
+        let Some(layout) = layout else {
+            return;
+        };
+
+        // Mesh names carry a generation so a rebuilt grid never reuses a cached name.
+        self.scale_reference.generation += 1;
+        let generation = self.scale_reference.generation;
+        let human_name = format!("scale_reference/{generation}/human");
+        let mut nodes = Vec::new();
+        let mut resources = SceneResources::default();
+        let mut add_lines = |world: &mut World,
+                             name: &str,
+                             translation: Vec3,
+                             segments: &[(Vec3, Vec3)],
+                             width: f32,
+                             color: egui::Color32| {
+            if segments.is_empty() {
+                return;
+            }
+            let name = format!("scale_reference/{generation}/{name}");
+            resources.meshes.push(name.clone());
+            resources.materials.push(name.clone());
+            let color = egui::Rgba::from(color);
+            mesh_cache_insert(
+                &mut world.resources.mesh_cache,
+                name.clone(),
+                line_segments_mesh(segments, width),
+            );
+            nightshade::ecs::material::resources::material_registry_insert(
+                &mut world.resources.material_registry,
+                name.clone(),
+                Material {
+                    base_color: color.to_array(),
+                    emissive_factor: [color.r(), color.g(), color.b()],
+                    metallic: 0.0,
+                    roughness: 1.0,
+                    ..Default::default()
+                },
+            );
+            nodes.push(nightshade::ecs::prefab::PrefabNode {
+                local_transform: LocalTransform {
+                    translation,
+                    ..Default::default()
+                },
+                components: nightshade::ecs::prefab::PrefabComponents {
+                    name: Some(Name(name.clone())),
+                    render_mesh: Some(RenderMesh { name: name.clone() }),
+                    material_ref: Some(MaterialRef { name }),
+                    ..Default::default()
+                },
+                children: Vec::new(),
+            });
+        };
+
+        if let Some((spacing, line_count)) = layout.grid {
+            let half_length = line_count as f32 * spacing;
+            let (mut minor, mut major) = (Vec::new(), Vec::new());
+            for index in -line_count..=line_count {
+                let offset = index as f32 * spacing;
+                let lines = if index % 10 == 0 {
+                    &mut major
+                } else {
+                    &mut minor
+                };
+                lines.push((
+                    Vec3::new(offset, 0.0, -half_length),
+                    Vec3::new(offset, 0.0, half_length),
+                ));
+                lines.push((
+                    Vec3::new(-half_length, 0.0, offset),
+                    Vec3::new(half_length, 0.0, offset),
+                ));
+            }
+            add_lines(
+                world,
+                "grid_minor",
+                Vec3::zeros(),
+                &minor,
+                spacing * 0.01,
+                egui::Color32::from_gray(70),
+            );
+            add_lines(
+                world,
+                "grid_major",
+                Vec3::zeros(),
+                &major,
+                spacing * 0.02,
+                egui::Color32::from_gray(150),
+            );
+        }
+
+        if let Some(base) = layout.human {
+            let segments: Vec<(Vec3, Vec3)> = human_silhouette_segments()
+                .into_iter()
+                .map(|(start, end)| (start * HUMAN_HEIGHT, end * HUMAN_HEIGHT))
+                .collect();
+            add_lines(
+                world,
+                "human",
+                base,
+                &segments,
+                0.025,
+                egui::Color32::from_rgb(120, 220, 160),
+            );
+        }
+
+        if layout.origin {
+            for axis in GizmoAxis::ALL {
+                add_lines(
+                    world,
+                    &format!("origin_{:?}", axis),
+                    Vec3::zeros(),
+                    &[(Vec3::zeros(), axis.direction() * 0.25)],
+                    0.015,
+                    axis.color(),
+                );
+            }
+        }
+
+        let prefab = nightshade::ecs::prefab::Prefab {
+            root_nodes: vec![nightshade::ecs::prefab::PrefabNode {
+                local_transform: LocalTransform::default(),
+                components: nightshade::ecs::prefab::PrefabComponents {
+                    name: Some(Name("Scale Reference".to_string())),
+                    ..Default::default()
+                },
+                children: nodes,
+            }],
+            ..Default::default()
+        };
+        let root = nightshade::ecs::prefab::spawn_prefab_with_skins(
+            world,
+            &prefab,
+            &[],
+            &[],
+            nalgebra_glm::vec3(0.0, 0.0, 0.0),
+        );
+        self.scale_reference.human_entity = collect_global_matrices(world, &[root])
+            .into_iter()
+            .map(|(entity, _, _)| entity)
+            .find(|entity| {
+                world
+                    .get_name(*entity)
+                    .is_some_and(|name| name.0 == human_name)
+            });
+        self.scale_reference.entity = Some(root);
+        self.scale_reference.resources = resources;
+    }
+
+    fn scale_reference_overlay_ui(&mut self, world: &mut World, ui_context: &egui::Context) {
+        let Some(layout) = self.scale_reference.layout else {
+            return;
+        };
+        let Some(projection) = ViewportProjection::new(world, ui_context.content_rect()) else {
+            return;
+        };
+
+        let painter = ui_context.layer_painter(egui::LayerId::new(
+            egui::Order::Background,
+            egui::Id::new("scale_reference_overlay"),
+        ));
+        let font = egui::FontId::proportional(13.0);
+
+        if let Some((spacing, line_count)) = layout.grid {
+            let half_length = line_count as f32 * spacing;
+            if let Some(position) =
+                projection.world_to_screen(&Vec3::new(half_length, 0.0, half_length))
+            {
+                draw_label(
+                    &painter,
+                    position,
+                    format!("Grid: {}", format_length(spacing)),
+                    font.clone(),
+                );
+            }
+        }
+
+        if let Some(base) = layout.human
+            && let Some(position) =
+                projection.world_to_screen(&(base + Vec3::new(0.0, HUMAN_HEIGHT + 0.1, 0.0)))
+        {
+            draw_label(
+                &painter,
+                position,
+                format_length(HUMAN_HEIGHT),
+                font.clone(),
+            );
+        }
+
+        if layout.origin
+            && let Some(position) = projection.world_to_screen(&Vec3::zeros())
+        {
+            draw_label(
+                &painter,
+                position + egui::vec2(0.0, 18.0),
+                "Origin".to_string(),
+                font,
+            );
+        }
+    }
+
+    fn surface_debug_settings_ui(&mut self, ui: &mut egui::Ui) {
+        let surface_debug = &mut self.surface_debug;
+
+        ui.horizontal(|ui| {
+            ui.label("Surface Override:");
+            egui::ComboBox::from_id_salt("surface_override")
+                .selected_text(surface_debug.mode.name())
+                .show_ui(ui, |ui| {
+                    for mode in SurfaceOverride::ALL {
+                        ui.selectable_value(&mut surface_debug.mode, mode, mode.name());
+                    }
+                });
+        });
+
+        match surface_debug.mode {
+            SurfaceOverride::None => {}
+            SurfaceOverride::UvChecker => {
+                ui.horizontal(|ui| {
+                    ui.label("UV Set:");
+                    ui.selectable_value(&mut surface_debug.uv_set, 0, "0");
+                    ui.selectable_value(&mut surface_debug.uv_set, 1, "1");
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Checker Scale:");
+                    ui.add(
+                        egui::Slider::new(&mut surface_debug.checker_scale, 0.25..=32.0)
+                            .logarithmic(true),
+                    );
+                });
+            }
+            SurfaceOverride::TexelDensity => {
+                ui.horizontal(|ui| {
+                    ui.label("UV Set:");
+                    ui.selectable_value(&mut surface_debug.uv_set, 0, "0");
+                    ui.selectable_value(&mut surface_debug.uv_set, 1, "1");
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Target:");
+                    ui.add(
+                        egui::DragValue::new(&mut surface_debug.target_texels_per_meter)
+                            .speed(8.0)
+                            .range(1.0..=16384.0)
+                            .suffix(" texels/m"),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Fallback Resolution:");
+                    ui.add(
+                        egui::DragValue::new(&mut surface_debug.fallback_resolution)
+                            .speed(16.0)
+                            .range(1..=16384)
+                            .suffix(" px"),
+                    )
+                    .on_hover_text("Used when a material's base color texture size is unknown");
+                });
+                ui.horizontal(|ui| {
+                    for (label, ratio) in [("x0.25", 0.25), ("Target", 1.0), ("x4", 4.0)] {
+                        let (rect, _) =
+                            ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
+                        ui.painter()
+                            .rect_filled(rect, 2.0, texel_density_color(ratio));
+                        ui.label(label);
+                    }
+                });
+                ui.label(
+                    egui::RichText::new(
+                        "Density is measured with the node transforms at the moment the view is applied; \
+                         morph targets are frozen while it is shown.",
+                    )
+                    .small()
+                    .weak(),
+                );
+            }
+        }
+    }
+
+    fn surface_debug_system(&mut self, world: &mut World) {
+        let settings = &self.surface_debug;
+        let key =
+            (settings.mode != SurfaceOverride::None && self.loaded).then(|| SurfaceDebugKey {
+                mode: settings.mode,
+                uv_set: settings.uv_set,
+                checker_scale: settings.checker_scale,
+                target_texels_per_meter: settings.target_texels_per_meter,
+                fallback_resolution: settings.fallback_resolution,
+                entities: self.model_entities.clone(),
+            });
+        if key == self.surface_debug.applied {
+            self.surface_debug.pending = None;
+            return;
+        }
+
+        // Switching views or models applies at once; slider edits wait until the value
+        // has settled so a drag does not rebuild textures and meshes every frame.
+        let immediate = key.as_ref().map(|key| (key.mode, &key.entities))
+            != self
+                .surface_debug
+                .applied
+                .as_ref()
+                .map(|key| (key.mode, &key.entities));
+        if let Some(key) = key.clone()
+            && !immediate
+        {
+            let delta_time = world.resources.window.timing.delta_time;
+            let settled = match &mut self.surface_debug.pending {
+                Some((pending, elapsed)) if *pending == key => {
+                    *elapsed += delta_time;
+                    *elapsed >= SURFACE_DEBUG_SETTLE_SECONDS
+                }
+                pending => {
+                    *pending = Some((key, 0.0));
+                    false
+                }
+            };
+            if !settled {
+                return;
+            }
+        }
+
+        self.restore_surfaces(world);
+        self.surface_debug.pending = None;
+        self.surface_debug.applied = key.clone();
+        let Some(key) = key else {
+            return;
+        };
+
+        // Everything generated for one setting shares a generation prefix so a rebuild
+        // never reuses a name the renderer may already have uploaded; the previous
+        // generation was freed by `restore_surfaces`.
+        self.surface_debug.generation += 1;
+        let prefix = format!("surface_debug/{}/", self.surface_debug.generation);
+        let texture = format!("{prefix}texture");
+        let (rgba_data, width, height) = match key.mode {
+            SurfaceOverride::TexelDensity => (
+                texel_density_ramp(TEXEL_DENSITY_RAMP_WIDTH),
+                TEXEL_DENSITY_RAMP_WIDTH as u32,
+                1,
+            ),
+            _ => {
+                let cells = (8.0 * key.checker_scale).round().clamp(2.0, 256.0) as usize;
+                (checker_rgba(1024, cells), 1024, 1024)
+            }
+        };
+        world.queue_command(WorldCommand::LoadTexture {
+            name: texture.clone(),
+            rgba_data,
+            width,
+            height,
+        });
+        self.surface_debug.resources.textures.push(texture.clone());
+
+        let area_to_original_units = 1.0 / (self.scene_scale * self.scene_scale);
+        let nodes = collect_global_matrices(world, &self.model_entities);
+        for (index, (entity, _, global_matrix)) in nodes.into_iter().enumerate() {
+            let Some(mesh_name) = world
+                .get_render_mesh(entity)
+                .map(|render_mesh| render_mesh.name.clone())
+            else {
+                continue;
+            };
+            let Some(mesh) = mesh_cache_get(&world.resources.mesh_cache, &mesh_name) else {
+                continue;
+            };
+            let material_name = world
+                .get_material_ref(entity)
+                .map(|material_ref| material_ref.name.clone());
+            let material = material_name.as_ref().and_then(|name| {
+                nightshade::ecs::material::resources::material_registry_get(
+                    &world.resources.material_registry,
+                    name,
+                )
+            });
+
+            let override_mesh = match key.mode {
+                SurfaceOverride::TexelDensity => {
+                    let (texture_width, texture_height) = material
+                        .and_then(|material| material.base_texture.as_ref())
+                        .and_then(|name| self.surface_debug.texture_sizes.get(name).copied())
+                        .unwrap_or((key.fallback_resolution, key.fallback_resolution));
+                    Some(texel_density_mesh(
+                        mesh,
+                        &global_matrix,
+                        key.uv_set,
+                        texture_width as f32 * texture_height as f32,
+                        key.target_texels_per_meter,
+                        area_to_original_units,
+                    ))
+                }
+                _ if key.uv_set == 1 => Some(mesh_with_secondary_uvs(mesh)),
+                _ => None,
+            };
+
+            let override_material =
+                format!("{prefix}{}", material_name.as_deref().unwrap_or("default"));
+            if nightshade::ecs::material::resources::material_registry_get(
+                &world.resources.material_registry,
+                &override_material,
+            )
+            .is_none()
+            {
+                let mut material = material.cloned().unwrap_or_default();
+                material.base_color = [1.0; 4];
+                material.base_texture = Some(texture.clone());
+                material.metallic = 0.0;
+                material.roughness = 1.0;
+                material.metallic_roughness_texture = None;
+                material.occlusion_texture = None;
+                material.emissive_texture = None;
+                material.emissive_factor = [0.0; 3];
+                if key.mode == SurfaceOverride::TexelDensity {
+                    material.normal_texture = None;
+                }
+                nightshade::ecs::material::resources::material_registry_insert(
+                    &mut world.resources.material_registry,
+                    override_material.clone(),
+                    material,
+                );
+                self.surface_debug
+                    .resources
+                    .materials
+                    .push(override_material.clone());
+            }
+
+            if let Some(override_mesh) = override_mesh {
+                let override_mesh_name = format!("{prefix}{index}/{mesh_name}");
+                mesh_cache_insert(
+                    &mut world.resources.mesh_cache,
+                    override_mesh_name.clone(),
+                    override_mesh,
+                );
+                self.surface_debug
+                    .resources
+                    .meshes
+                    .push(override_mesh_name.clone());
+                if let Some(render_mesh) = world.get_render_mesh_mut(entity) {
+                    render_mesh.name = override_mesh_name;
+                }
+            }
+            match world.get_material_ref_mut(entity) {
+                Some(material_ref) => material_ref.name = override_material,
+                None => continue,
+            }
+            self.surface_debug
+                .swapped
+                .insert(entity, (mesh_name, material_name));
+        }
+    }
+
+    fn restore_surfaces(&mut self, world: &mut World) {
+        self.surface_debug.applied = None;
+        for (entity, (mesh_name, material_name)) in self.surface_debug.swapped.drain() {
+            if let Some(render_mesh) = world.get_render_mesh_mut(entity) {
+                render_mesh.name = mesh_name;
+            }
+            if let Some(material_name) = material_name
+                && let Some(material_ref) = world.get_material_ref_mut(entity)
+            {
+                material_ref.name = material_name;
+            }
+        }
+        let resources = std::mem::take(&mut self.surface_debug.resources);
+        self.release_resources(world, resources);
+    }
+
+    fn debug_overlay_ui(&mut self, world: &mut World, ui_context: &egui::Context) {
+        let overlays = &self.debug_overlays;
+        if !overlays.any_enabled() || !self.loaded {
+            return;
+        }
+
+        let Some(projection) = ViewportProjection::new(world, ui_context.content_rect()) else {
+            return;
+        };
+
+        let painter = ui_context.layer_painter(egui::LayerId::new(
+            egui::Order::Background,
+            egui::Id::new("debug_overlay"),
+        ));
+        let nodes = collect_global_matrices(world, &self.model_entities);
+
+        if overlays.scene_bounds
+            && let Some((min, max)) = self.scene_bounds(world)
+        {
+            draw_box_edges(
+                &painter,
+                &projection,
+                &box_corners(&min, &max),
+                egui::Stroke::new(1.5, egui::Color32::from_rgb(255, 255, 255)),
+            );
+        }
+
+        if overlays.node_bounds {
+            for (entity, _, global_matrix) in &nodes {
+                let Some(bounding_volume) = world.get_bounding_volume(*entity) else {
+                    continue;
+                };
+                let (min, max) = local_bounds(bounding_volume);
+                let corners =
+                    box_corners(&min, &max).map(|corner| transform_point(global_matrix, &corner));
+                draw_box_edges(
+                    &painter,
+                    &projection,
+                    &corners,
+                    egui::Stroke::new(1.0, egui::Color32::from_rgb(255, 170, 50)),
+                );
+            }
+        }
+
+        if overlays.skeleton {
+            let joints: std::collections::HashSet<Entity> = nodes
+                .iter()
+                .filter_map(|(entity, _, _)| world.get_skin(*entity))
+                .flat_map(|skin| skin.joints.iter().copied())
+                .collect();
+            let positions: std::collections::HashMap<Entity, Vec3> = nodes
+                .iter()
+                .map(|(entity, _, matrix)| (*entity, transform_point(matrix, &Vec3::zeros())))
+                .collect();
+            let bone_stroke = egui::Stroke::new(2.0, egui::Color32::from_rgb(90, 200, 255));
+
+            for (entity, parent, _) in &nodes {
+                if !joints.contains(entity) {
+                    continue;
+                }
+                let Some(position) = positions.get(entity) else {
+                    continue;
+                };
+                if let Some(parent) = parent
+                    && joints.contains(parent)
+                    && let Some(parent_position) = positions.get(parent)
+                {
+                    draw_world_line(
+                        &painter,
+                        &projection,
+                        parent_position,
+                        position,
+                        bone_stroke,
+                    );
+                }
+                if let Some(screen_position) = projection.world_to_screen(position) {
+                    painter.circle_filled(screen_position, 3.0, egui::Color32::WHITE);
+                }
+            }
+        }
+
+        // The caps bound the transform work as well as the drawing: vertices are skinned
+        // and transformed only when a triangle or sampled vertex still fits under them.
+        let mut limit_reached = false;
+        if overlays.normals || overlays.tangents || overlays.wireframe {
+            let camera_position = projection.camera_position;
+            let globals: std::collections::HashMap<Entity, Mat4> = nodes
+                .iter()
+                .map(|(entity, _, matrix)| (*entity, *matrix))
+                .collect();
+            let draw_vectors = overlays.normals || overlays.tangents;
+            let mut vectors_drawn = 0;
+            let mut triangles_drawn = 0;
+
+            for (entity, _, global_matrix) in &nodes {
+                let Some(mesh) = world.get_render_mesh(*entity).and_then(|render_mesh| {
+                    mesh_cache_get(&world.resources.mesh_cache, &render_mesh.name)
+                }) else {
+                    continue;
+                };
+                if (!draw_vectors || vectors_drawn >= DEBUG_MAX_VECTORS)
+                    && (!overlays.wireframe || triangles_drawn >= DEBUG_MAX_WIREFRAME_TRIANGLES)
+                {
+                    limit_reached = true;
+                    break;
+                }
+
+                let joint_matrices = skin_joint_matrices(world, *entity, mesh, &globals);
+                let vertex_matrix = |index: usize| {
+                    joint_matrices
+                        .as_deref()
+                        .zip(mesh.skin_data.as_ref())
+                        .and_then(|(joint_matrices, skin_data)| {
+                            skinned_vertex_matrix(joint_matrices, skin_data, index)
+                        })
+                        .unwrap_or(*global_matrix)
+                };
+                let mut positions: Vec<Option<Vec3>> = vec![None; mesh.vertices.len()];
+                let mut position = |index: usize| {
+                    let vertex = mesh.vertices.get(index)?;
+                    Some(*positions[index].get_or_insert_with(|| {
+                        transform_point(&vertex_matrix(index), &Vec3::from(vertex.position))
+                    }))
+                };
+
+                if overlays.wireframe {
+                    let stroke = egui::Stroke::new(1.0, egui::Color32::from_white_alpha(110));
+                    for triangle_index in 0..mesh_triangle_count(mesh) {
+                        if triangles_drawn >= DEBUG_MAX_WIREFRAME_TRIANGLES {
+                            limit_reached = true;
+                            break;
+                        }
+                        let corners: [usize; 3] = std::array::from_fn(|corner| {
+                            let index = triangle_index * 3 + corner;
+                            if mesh.indices.is_empty() {
+                                index
+                            } else {
+                                mesh.indices[index] as usize
+                            }
+                        });
+                        let (Some(a), Some(b), Some(c)) = (
+                            position(corners[0]),
+                            position(corners[1]),
+                            position(corners[2]),
+                        ) else {
+                            continue;
+                        };
+                        let facing = (b - a).cross(&(c - a)).dot(&(camera_position - a));
+                        if facing <= 0.0 {
+                            continue;
+                        }
+                        let (Some(a), Some(b), Some(c)) = (
+                            projection.world_to_screen(&a),
+                            projection.world_to_screen(&b),
+                            projection.world_to_screen(&c),
+                        ) else {
+                            continue;
+                        };
+                        painter.add(egui::Shape::closed_line(vec![a, b, c], stroke));
+                        triangles_drawn += 1;
+                    }
+                }
+
+                if !draw_vectors {
+                    continue;
+                }
+
+                let remaining = DEBUG_MAX_VECTORS.saturating_sub(vectors_drawn);
+                if mesh.vertices.len() > remaining {
+                    limit_reached = true;
+                }
+                let stride = (mesh.vertices.len() / remaining.max(1)).max(1);
+
+                for index in (0..mesh.vertices.len()).step_by(stride) {
+                    if vectors_drawn >= DEBUG_MAX_VECTORS {
+                        break;
+                    }
+                    let (Some(vertex), Some(position)) =
+                        (mesh.vertices.get(index), position(index))
+                    else {
+                        continue;
+                    };
+                    vectors_drawn += 1;
+
+                    let tangent_matrix = nalgebra_glm::mat4_to_mat3(&vertex_matrix(index));
+                    let normal_matrix = nalgebra_glm::transpose(
+                        &tangent_matrix
+                            .try_inverse()
+                            .unwrap_or_else(nalgebra_glm::Mat3::identity),
+                    );
+
+                    if overlays.normals {
+                        let normal =
+                            nalgebra_glm::normalize(&(normal_matrix * Vec3::from(vertex.normal)));
+                        draw_world_segment(
+                            &painter,
+                            &projection,
+                            &position,
+                            &(position + normal * overlays.vector_length),
+                            egui::Stroke::new(1.0, egui::Color32::from_rgb(80, 140, 255)),
+                        );
+                    }
+
+                    if overlays.tangents {
+                        let tangent =
+                            Vec3::new(vertex.tangent[0], vertex.tangent[1], vertex.tangent[2]);
+                        if tangent.norm_squared() <= f32::EPSILON {
+                            continue;
+                        }
+                        let tangent = nalgebra_glm::normalize(&(tangent_matrix * tangent));
+                        draw_world_segment(
+                            &painter,
+                            &projection,
+                            &position,
+                            &(position + tangent * overlays.vector_length),
+                            egui::Stroke::new(1.0, egui::Color32::from_rgb(255, 90, 90)),
+                        );
+                    }
+                }
+            }
+        }
+        self.debug_overlays.limit_reached = limit_reached;
+    }
+
+    fn hierarchy_ui(&mut self, world: &mut World, ui_context: &egui::Context) {
+        if !self.show_hierarchy {
+            self.reveal_in_hierarchy = false;
+            return;
+        }
+
+        let selected_path = self
+            .selected_entity
+            .and_then(|entity| find_entity_path(world, &self.model_entities, entity))
+            .unwrap_or_default();
+        let reveal = std::mem::take(&mut self.reveal_in_hierarchy);
+        let mut clicked = None;
+        let mut open = true;
+
+        egui::Window::new("Hierarchy")
+            .open(&mut open)
+            .default_width(260.0)
+            .default_height(400.0)
+            .show(ui_context, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (index, instance) in self.instances.iter().enumerate() {
+                        let mut header_state =
+                            egui::collapsing_header::CollapsingState::load_with_default_open(
+                                ui.ctx(),
+                                ui.make_persistent_id(("hierarchy_instance", index)),
+                                true,
+                            );
+                        if reveal && self.selected_instance == Some(index) {
+                            header_state.set_open(true);
+                        }
+                        header_state
+                            .show_header(ui, |ui| {
+                                ui.strong(&instance.name);
+                            })
+                            .body(|ui| {
+                                for entity in &instance.entities {
+                                    hierarchy_node_ui(
+                                        ui,
+                                        world,
+                                        *entity,
+                                        &selected_path,
+                                        reveal,
+                                        &mut clicked,
+                                    );
+                                }
+                            });
+                    }
+                });
+            });
+
+        self.show_hierarchy = open;
+
+        if let Some(entity) = clicked {
+            self.selected_entity = Some(entity);
+            self.selected_instance =
+                selected_root(world, &self.model_entities, entity).and_then(|root| {
+                    self.instances
+                        .iter()
+                        .position(|instance| instance.entities.contains(&root))
+                });
+        }
+    }
+
+    fn transform_gizmo_ui(&mut self, world: &mut World, ui_context: &egui::Context) {
+        self.transform_gizmo.hovered = false;
+
+        let targets = self.gizmo_targets();
+        if !self.transform_gizmo.enabled || targets.is_empty() {
+            self.transform_gizmo.drag = None;
+            return;
+        }
+
+        let Some(projection) = ViewportProjection::new(world, ui_context.content_rect()) else {
+            return;
+        };
+
+        let (pivot, axes) = match &self.transform_gizmo.drag {
+            Some(drag) => (drag.pivot, drag.axes),
+            None => {
+                let Some(pivot) = self.gizmo_pivot(world, &targets) else {
+                    return;
+                };
+                (pivot, self.gizmo_axes(world, targets[0]))
+            }
+        };
+
+        let gizmo_length = match &self.transform_gizmo.drag {
+            Some(drag) => drag.gizmo_length,
+            None => nalgebra_glm::distance(&projection.camera_position, &pivot) * 0.15,
+        };
+        let Some(origin) = projection.world_to_screen(&pivot) else {
+            return;
+        };
+
+        let (pointer, primary_pressed, primary_down, ctrl_down) = ui_context.input(|i| {
+            (
+                i.pointer.hover_pos(),
+                i.pointer.primary_pressed(),
+                i.pointer.primary_down(),
+                i.modifiers.ctrl,
+            )
+        });
+
+        let mode = self
+            .transform_gizmo
+            .drag
+            .as_ref()
+            .map(|drag| drag.mode)
+            .unwrap_or(self.transform_gizmo.mode);
+
+        let mut handles: Vec<(GizmoHandle, Vec<egui::Pos2>, egui::Color32)> = Vec::new();
+        for (axis, direction) in GizmoAxis::ALL.iter().zip(axes.iter()) {
+            let points = match mode {
+                GizmoMode::Translate | GizmoMode::Scale => {
+                    let Some(end) = projection.world_to_screen(&(pivot + direction * gizmo_length))
+                    else {
+                        continue;
+                    };
+                    vec![origin, end]
+                }
+                GizmoMode::Rotate => {
+                    let (tangent, bitangent) = perpendicular_basis(direction);
+                    (0..=48)
+                        .filter_map(|step| {
+                            let angle = step as f32 / 48.0 * std::f32::consts::TAU;
+                            let offset =
+                                (tangent * angle.cos() + bitangent * angle.sin()) * gizmo_length;
+                            projection.world_to_screen(&(pivot + offset))
+                        })
+                        .collect()
+                }
+            };
+            handles.push((GizmoHandle::Axis(*axis), points, axis.color()));
+        }
+        if mode == GizmoMode::Scale {
+            handles.push((GizmoHandle::Uniform, vec![origin], egui::Color32::WHITE));
+        }
+
+        let hovered_handle = pointer.and_then(|pointer| {
+            handles
+                .iter()
+                .map(|(handle, points, _)| (*handle, distance_to_polyline(pointer, points)))
+                .filter(|(_, distance)| *distance < 8.0)
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(handle, _)| handle)
+        });
+        self.transform_gizmo.hovered = hovered_handle.is_some();
+
+        let active_handle = self
+            .transform_gizmo
+            .drag
+            .as_ref()
+            .map(|drag| drag.handle)
+            .or(hovered_handle);
+        let painter = ui_context.layer_painter(egui::LayerId::new(
+            egui::Order::Background,
+            egui::Id::new("transform_gizmo"),
+        ));
+
+        for (handle, points, color) in &handles {
+            let color = if active_handle == Some(*handle) {
+                egui::Color32::YELLOW
+            } else {
+                *color
+            };
+            match (mode, handle) {
+                (_, GizmoHandle::Uniform) => {
+                    painter.rect_filled(
+                        egui::Rect::from_center_size(origin, egui::vec2(10.0, 10.0)),
+                        0.0,
+                        color,
+                    );
+                }
+                (GizmoMode::Rotate, _) => {
+                    painter.add(egui::Shape::line(
+                        points.clone(),
+                        egui::Stroke::new(2.5, color),
+                    ));
+                }
+                (GizmoMode::Translate, _) => {
+                    painter.line_segment([points[0], points[1]], egui::Stroke::new(3.0, color));
+                    painter.circle_filled(points[1], 6.0, color);
+                }
+                (GizmoMode::Scale, _) => {
+                    painter.line_segment([points[0], points[1]], egui::Stroke::new(3.0, color));
+                    painter.rect_filled(
+                        egui::Rect::from_center_size(points[1], egui::vec2(10.0, 10.0)),
+                        0.0,
+                        color,
+                    );
+                }
+            }
+        }
+        if mode != GizmoMode::Scale {
+            painter.circle_filled(origin, 4.0, egui::Color32::WHITE);
+        }
+
+        if self.transform_gizmo.drag.is_none()
+            && primary_pressed
+            && !ui_context.is_pointer_over_area()
+            && let (Some(handle), Some(pointer)) = (hovered_handle, pointer)
+        {
+            let snapshots = targets
+                .iter()
+                .filter_map(|entity| {
+                    let parent_matrix = find_parent_matrix(world, &self.model_entities, *entity)?;
+                    world
+                        .get_local_transform(*entity)
+                        .map(|transform| TransformSnapshot {
+                            entity: *entity,
+                            parent_matrix,
+                            translation: transform.translation,
+                            rotation: transform.rotation,
+                            scale: transform.scale,
+                        })
+                })
+                .collect();
+
+            let camera_targets = self
+                .camera_entity
+                .and_then(|camera_entity| world.get_pan_orbit_camera(camera_entity))
+                .map(|pan_orbit| CameraTargets {
+                    focus: pan_orbit.target_focus,
+                    radius: pan_orbit.target_radius,
+                    yaw: pan_orbit.target_yaw,
+                    pitch: pan_orbit.target_pitch,
+                });
+
+            self.transform_gizmo.drag = Some(GizmoDrag {
+                handle,
+                mode,
+                pivot,
+                axes,
+                gizmo_length,
+                start_pointer: pointer,
+                snapshots,
+                camera_targets,
+            });
+            return;
+        }
+
+        if self.transform_gizmo.drag.is_some() && !primary_down {
+            self.transform_gizmo.drag = None;
+            return;
+        }
+
+        let (Some(drag), Some(pointer)) = (&self.transform_gizmo.drag, pointer) else {
+            return;
+        };
+
+        let snapping = self.transform_gizmo.snapping != ctrl_down;
+        let snap = |value: f32, step: f32| {
+            if snapping && step > 0.0 {
+                (value / step).round() * step
+            } else {
+                value
+            }
+        };
+
+        let axis_direction = match drag.handle {
+            GizmoHandle::Axis(axis) => drag.axes[axis as usize],
+            GizmoHandle::Uniform => Vec3::zeros(),
+        };
+        let along_axis = || {
+            projection
+                .pixels_per_unit(&drag.pivot, &axis_direction)
+                .map(|(screen_direction, pixels_per_unit)| {
+                    (pointer - drag.start_pointer).dot(screen_direction) / pixels_per_unit
+                })
+                .unwrap_or(0.0)
+        };
+
+        match drag.mode {
+            GizmoMode::Translate => {
+                let distance = snap(along_axis(), self.transform_gizmo.translate_snap);
+                let offset = axis_direction * distance;
+                for snapshot in &drag.snapshots {
+                    let local_offset = inverse_transform_vector(&snapshot.parent_matrix, &offset);
+                    if let Some(transform) = world.get_local_transform_mut(snapshot.entity) {
+                        transform.translation = snapshot.translation + local_offset;
+                        transform.rotation = snapshot.rotation;
+                        transform.scale = snapshot.scale;
+                    }
+                    world.mark_local_transform_dirty(snapshot.entity);
+                }
+            }
+            GizmoMode::Rotate => {
+                let start = drag.start_pointer - origin;
+                let current = pointer - origin;
+                let screen_angle = -(start.x * current.y - start.y * current.x)
+                    .atan2(start.x * current.x + start.y * current.y);
+                let view_direction = drag.pivot - projection.camera_position;
+                let sign = if axis_direction.dot(&view_direction) < 0.0 {
+                    1.0
+                } else {
+                    -1.0
+                };
+                let angle = snap(
+                    (screen_angle * sign).to_degrees(),
+                    self.transform_gizmo.rotate_snap_degrees,
+                )
+                .to_radians();
+                let rotation = nalgebra_glm::quat_angle_axis(angle, &axis_direction);
+
+                for snapshot in &drag.snapshots {
+                    let world_position =
+                        transform_point(&snapshot.parent_matrix, &snapshot.translation);
+                    let rotated_position = drag.pivot
+                        + nalgebra_glm::quat_rotate_vec3(&rotation, &(world_position - drag.pivot));
+                    let parent_rotation = matrix_rotation(&snapshot.parent_matrix);
+                    if let Some(transform) = world.get_local_transform_mut(snapshot.entity) {
+                        transform.translation =
+                            inverse_transform_point(&snapshot.parent_matrix, &rotated_position);
+                        transform.rotation = nalgebra_glm::quat_normalize(
+                            &(nalgebra_glm::quat_inverse(&parent_rotation)
+                                * rotation
+                                * parent_rotation
+                                * snapshot.rotation),
+                        );
+                        transform.scale = snapshot.scale;
+                    }
+                    world.mark_local_transform_dirty(snapshot.entity);
+                }
+            }
+            GizmoMode::Scale => {
+                let raw_factor = match drag.handle {
+                    GizmoHandle::Axis(_) => {
+                        1.0 + along_axis() / drag.gizmo_length.max(f32::EPSILON)
+                    }
+                    GizmoHandle::Uniform => 1.0 + (pointer.x - drag.start_pointer.x) / 100.0,
+                };
+                let factor =
+                    (1.0 + snap(raw_factor - 1.0, self.transform_gizmo.scale_snap)).max(0.01);
+
+                for snapshot in &drag.snapshots {
+                    let world_position =
+                        transform_point(&snapshot.parent_matrix, &snapshot.translation);
+                    let offset = world_position - drag.pivot;
+                    let (scaled_offset, scale) = match drag.handle {
+                        GizmoHandle::Axis(_) => {
+                            // A local transform cannot shear, so a handle that is not aligned
+                            // with the entity's own axes spreads the factor over them by how
+                            // far each one points along the handle.
+                            let world_rotation =
+                                matrix_rotation(&snapshot.parent_matrix) * snapshot.rotation;
+                            let local_axis = nalgebra_glm::quat_rotate_vec3(
+                                &nalgebra_glm::quat_inverse(&world_rotation),
+                                &axis_direction,
+                            );
+                            let scale = snapshot.scale.component_mul(
+                                &local_axis
+                                    .map(|component| 1.0 + (factor - 1.0) * component * component),
+                            );
+                            (
+                                offset
+                                    + axis_direction * offset.dot(&axis_direction) * (factor - 1.0),
+                                scale,
+                            )
+                        }
+                        GizmoHandle::Uniform => (offset * factor, snapshot.scale * factor),
+                    };
+                    if let Some(transform) = world.get_local_transform_mut(snapshot.entity) {
+                        transform.translation = inverse_transform_point(
+                            &snapshot.parent_matrix,
+                            &(drag.pivot + scaled_offset),
+                        );
+                        transform.rotation = snapshot.rotation;
+                        transform.scale = scale;
+                    }
+                    world.mark_local_transform_dirty(snapshot.entity);
+                }
+            }
+        }
+    }
+
+    fn gizmo_pivot(&self, world: &World, targets: &[Entity]) -> Option<Vec3> {
+        if let [entity] = targets {
+            let parent_matrix = find_parent_matrix(world, &self.model_entities, *entity)?;
+            let transform = world.get_local_transform(*entity)?;
+            return Some(transform_point(&parent_matrix, &transform.translation));
+        }
+        entities_bounds(world, targets).map(|(min, max)| (min + max) * 0.5)
+    }
+
+    fn gizmo_axes(&self, world: &World, entity: Entity) -> [Vec3; 3] {
+        let identity = [
+            GizmoAxis::X.direction(),
+            GizmoAxis::Y.direction(),
+            GizmoAxis::Z.direction(),
+        ];
+
+        if self.transform_gizmo.space == GizmoSpace::World {
+            return identity;
+        }
+
+        let Some(parent_matrix) = find_parent_matrix(world, &self.model_entities, entity) else {
+            return identity;
+        };
+        let Some(transform) = world.get_local_transform(entity) else {
+            return identity;
+        };
+
+        let rotation = matrix_rotation(&parent_matrix) * transform.rotation;
+        identity.map(|axis| nalgebra_glm::quat_rotate_vec3(&rotation, &axis))
+    }
+}
+
+fn current_clip_duration(player: &AnimationPlayer) -> Option<f32> {
+    player
+        .current_clip
+        .and_then(|index| player.clips.get(index))
+        .map(|clip| clip.duration)
+}
+
+fn wrap_clip_time(time: f32, duration: f32) -> f32 {
+    if duration <= f32::EPSILON {
+        0.0
+    } else {
+        time.rem_euclid(duration)
+    }
+}
+
+fn sample_channel(channel: &AnimationChannel, time: f32) -> Option<Vec<f32>> {
+    let input = &channel.sampler.input;
+    let output = &channel.sampler.output;
+    let components = match channel.target_property {
+        AnimationProperty::Translation | AnimationProperty::Scale => 3,
+        AnimationProperty::Rotation => 4,
+        _ => return None,
+    };
+    let interpolation = channel.sampler.interpolation;
+    let stride = match interpolation {
+        AnimationInterpolation::CubicSpline => components * 3,
+        _ => components,
+    };
+    if input.is_empty() || output.len() < input.len() * stride {
+        return None;
+    }
+
+    // Cubic spline keyframes store an in-tangent, the value and an out-tangent.
+    let element = |key: usize, element: usize| {
+        let start = key * stride + element * components;
+        &output[start..start + components]
+    };
+    let value_element = usize::from(interpolation == AnimationInterpolation::CubicSpline);
+    let value = |key: usize| element(key, value_element);
+
+    let next = input.partition_point(|key_time| *key_time <= time);
+    if next == 0 {
+        return Some(value(0).to_vec());
+    }
+    if next >= input.len() {
+        return Some(value(input.len() - 1).to_vec());
+    }
+
+    let previous = next - 1;
+    let span = input[next] - input[previous];
+    let factor = if span > f32::EPSILON {
+        (time - input[previous]) / span
+    } else {
+        0.0
+    };
+    let (start, end) = (value(previous), value(next));
+
+    match interpolation {
+        AnimationInterpolation::Step => Some(start.to_vec()),
+        AnimationInterpolation::CubicSpline => {
+            let (t, t2, t3) = (factor, factor * factor, factor * factor * factor);
+            let (out_tangent, in_tangent) = (element(previous, 2), element(next, 0));
+            let mut values: Vec<f32> = (0..components)
+                .map(|index| {
+                    (2.0 * t3 - 3.0 * t2 + 1.0) * start[index]
+                        + (t3 - 2.0 * t2 + t) * span * out_tangent[index]
+                        + (-2.0 * t3 + 3.0 * t2) * end[index]
+                        + (t3 - t2) * span * in_tangent[index]
+                })
+                .collect();
+            if components == 4 {
+                let length = values.iter().map(|value| value * value).sum::<f32>().sqrt();
+                if length > f32::EPSILON {
+                    values.iter_mut().for_each(|value| *value /= length);
+                }
+            }
+            Some(values)
+        }
+        AnimationInterpolation::Linear if components == 4 => {
+            let rotation = slerp_shortest(
+                &nalgebra_glm::quat(start[0], start[1], start[2], start[3]),
+                &nalgebra_glm::quat(end[0], end[1], end[2], end[3]),
+                factor,
+            );
+            Some(rotation.coords.iter().copied().collect())
+        }
+        AnimationInterpolation::Linear => Some(
+            start
+                .iter()
+                .zip(end)
+                .map(|(a, b)| a + (b - a) * factor)
+                .collect(),
+        ),
+    }
+}
+
+fn sample_clip_pose(
+    clip: &AnimationClip,
+    time: f32,
+    nodes: &std::collections::HashMap<String, Entity>,
+    rest_pose: &std::collections::HashMap<Entity, NodePose>,
+) -> std::collections::HashMap<Entity, NodePose> {
+    let mut pose = std::collections::HashMap::new();
+    for channel in &clip.channels {
+        let (Some(entity), Some(values)) = (
+            nodes.get(&channel.target_node),
+            sample_channel(channel, time),
+        ) else {
+            continue;
+        };
+        let Some(rest) = rest_pose.get(entity) else {
+            continue;
+        };
+        let node = pose.entry(*entity).or_insert(*rest);
+        match channel.target_property {
+            AnimationProperty::Translation => {
+                node.translation = nalgebra_glm::vec3(values[0], values[1], values[2]);
+            }
+            AnimationProperty::Rotation => {
+                node.rotation = nalgebra_glm::quat_normalize(&nalgebra_glm::quat(
+                    values[0], values[1], values[2], values[3],
+                ));
+            }
+            AnimationProperty::Scale => {
+                node.scale = nalgebra_glm::vec3(values[0], values[1], values[2]);
+            }
+            _ => {}
+        }
+    }
+    pose
+}
+
+fn root_poses(world: &World, roots: &[Entity]) -> Vec<(Entity, NodePose)> {
+    roots
+        .iter()
+        .filter_map(|entity| Some((*entity, NodePose::of(world.get_local_transform(*entity)?))))
+        .collect()
+}
+
+/// Which of translation, rotation and scale the clips animate on each node.
+fn animated_properties<'a>(
+    clips: impl IntoIterator<Item = &'a AnimationClip>,
+    nodes: &std::collections::HashMap<String, Entity>,
+) -> std::collections::HashMap<Entity, [bool; 3]> {
+    let mut animated = std::collections::HashMap::<Entity, [bool; 3]>::new();
+    for channel in clips.into_iter().flat_map(|clip| &clip.channels) {
+        let Some(entity) = nodes.get(&channel.target_node) else {
+            continue;
+        };
+        let index = match channel.target_property {
+            AnimationProperty::Translation => 0,
+            AnimationProperty::Rotation => 1,
+            AnimationProperty::Scale => 2,
+            _ => continue,
+        };
+        animated.entry(*entity).or_default()[index] = true;
+    }
+    animated
+}
+
+fn blend_poses(
+    from: &std::collections::HashMap<Entity, NodePose>,
+    to: &std::collections::HashMap<Entity, NodePose>,
+    rest_pose: &std::collections::HashMap<Entity, NodePose>,
+    factor: f32,
+) -> std::collections::HashMap<Entity, NodePose> {
+    from.keys()
+        .chain(to.keys())
+        .filter_map(|entity| {
+            let rest = rest_pose.get(entity)?;
+            let a = from.get(entity).unwrap_or(rest);
+            let b = to.get(entity).unwrap_or(rest);
+            Some((
+                *entity,
+                NodePose {
+                    translation: nalgebra_glm::lerp(&a.translation, &b.translation, factor),
+                    rotation: slerp_shortest(&a.rotation, &b.rotation, factor),
+                    scale: nalgebra_glm::lerp(&a.scale, &b.scale, factor),
+                },
+            ))
+        })
+        .collect()
+}
+
+fn apply_additive_pose(
+    pose: &mut std::collections::HashMap<Entity, NodePose>,
+    reference: &std::collections::HashMap<Entity, NodePose>,
+    additive: &std::collections::HashMap<Entity, NodePose>,
+    rest_pose: &std::collections::HashMap<Entity, NodePose>,
+    weight: f32,
+) {
+    for (entity, additive) in additive {
+        let (Some(reference), Some(rest)) = (reference.get(entity), rest_pose.get(entity)) else {
+            continue;
+        };
+        let node = pose.entry(*entity).or_insert(*rest);
+        let rotation_delta = additive.rotation * nalgebra_glm::quat_inverse(&reference.rotation);
+        let scale_delta = additive.scale.component_div(
+            &reference
+                .scale
+                .map(|v| if v.abs() > f32::EPSILON { v } else { 1.0 }),
+        );
+
+        node.translation += (additive.translation - reference.translation) * weight;
+        node.rotation = nalgebra_glm::quat_normalize(
+            &(slerp_shortest(&Quat::identity(), &rotation_delta, weight) * node.rotation),
+        );
+        node.scale = node.scale.component_mul(&nalgebra_glm::lerp(
+            &Vec3::new(1.0, 1.0, 1.0),
+            &scale_delta,
+            weight,
+        ));
+    }
+}
+
+fn slerp_shortest(from: &Quat, to: &Quat, factor: f32) -> Quat {
+    let to = if from.coords.dot(&to.coords) < 0.0 {
+        -*to
+    } else {
+        *to
+    };
+    nalgebra_glm::quat_normalize(&nalgebra_glm::quat_slerp(from, &to, factor))
+}
+
+fn instance_name(path: &std::path::Path) -> String {
+    path.file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("Model")
+        .to_string()
+}
+
+fn entities_world_bounds(
+    world: &World,
+    roots: &[Entity],
+    entities: &[Entity],
+) -> Option<(Vec3, Vec3)> {
+    let mut min = Vec3::new(f32::MAX, f32::MAX, f32::MAX);
+    let mut max = Vec3::new(f32::MIN, f32::MIN, f32::MIN);
+    let mut has_bounds = false;
+
+    for entity in entities {
+        let Some(parent_matrix) = find_parent_matrix(world, roots, *entity) else {
+            continue;
+        };
+        calculate_bounds_recursive(
+            world,
+            *entity,
+            &parent_matrix,
+            &mut min,
+            &mut max,
+            &mut has_bounds,
+        );
+    }
 
-        egui::Window::new("Settings")
-            .default_pos(egui::pos2(10.0, 10.0))
-            .default_width(300.0)
-            .show(ui_context, |ui| {
-                #[cfg(not(target_arch = "wasm32"))]
-                {
-                    ui.horizontal(|ui| {
-                        if ui.button("Asset Library").clicked() {
-                            self.asset_library.show_window = true;
-                        }
+    has_bounds.then_some((min, max))
+}
 
-                        if ui
-                            .button("Take Screenshot")
-                            .on_hover_text("Save screenshot to screenshots/ folder")
-                            .clicked()
-                        {
-                            nightshade::ecs::world::capture_screenshot(world);
-                        }
+#[cfg(feature = "openxr")]
+fn floor_placement_offset(min: Vec3, max: Vec3, head: Option<(Vec3, Quat)>) -> Vec3 {
+    let (head_position, head_orientation) =
+        head.unwrap_or((Vec3::new(0.0, 1.6, 0.0), Quat::identity()));
+    let look = nalgebra_glm::quat_rotate_vec3(&head_orientation, &Vec3::new(0.0, 0.0, -1.0));
+    let forward = Vec3::new(look.x, 0.0, look.z);
+    let forward = if nalgebra_glm::length(&forward) > 0.01 {
+        nalgebra_glm::normalize(&forward)
+    } else {
+        Vec3::new(0.0, 0.0, -1.0)
+    };
+    let center = (min + max) * 0.5;
+    let footprint = (max.x - min.x).max(max.z - min.z) * 0.5;
+    let target = head_position + forward * (1.0 + footprint);
+    Vec3::new(target.x - center.x, -min.y, target.z - center.z)
+}
+
+fn entities_bounds(world: &World, entities: &[Entity]) -> Option<(Vec3, Vec3)> {
+    let mut min = Vec3::new(f32::MAX, f32::MAX, f32::MAX);
+    let mut max = Vec3::new(f32::MIN, f32::MIN, f32::MIN);
+    let mut has_bounds = false;
+
+    for entity in entities {
+        calculate_bounds_recursive(
+            world,
+            *entity,
+            &Mat4::identity(),
+            &mut min,
+            &mut max,
+            &mut has_bounds,
+        );
+    }
+
+    has_bounds.then_some((min, max))
+}
+
+fn snap_measurement_point(
+    hit: &RaycastHit,
+    projection: &ViewportProjection,
+    pointer: egui::Pos2,
+    snap_to_vertices: bool,
+    snap_to_edges: bool,
+) -> (Vec3, SnapKind) {
+    const SNAP_DISTANCE: f32 = 12.0;
+
+    let triangle = &hit.triangle;
+
+    if snap_to_vertices {
+        let closest_vertex = triangle
+            .iter()
+            .filter_map(|vertex| {
+                projection
+                    .world_to_screen(vertex)
+                    .map(|position| (*vertex, position.distance(pointer)))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((vertex, distance)) = closest_vertex
+            && distance < SNAP_DISTANCE
+        {
+            return (vertex, SnapKind::Vertex);
+        }
+    }
+
+    if snap_to_edges {
+        let closest_edge = [(0, 1), (1, 2), (2, 0)]
+            .iter()
+            .filter_map(|(start, end)| {
+                let start_position = projection.world_to_screen(&triangle[*start])?;
+                let end_position = projection.world_to_screen(&triangle[*end])?;
+                let edge = end_position - start_position;
+                let length_squared = edge.length_sq();
+                if length_squared <= f32::EPSILON {
+                    return None;
+                }
+                let t = ((pointer - start_position).dot(edge) / length_squared).clamp(0.0, 1.0);
+                let distance = pointer.distance(start_position + edge * t);
+                let point = triangle[*start] + (triangle[*end] - triangle[*start]) * t;
+                Some((point, distance))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((point, distance)) = closest_edge
+            && distance < SNAP_DISTANCE
+        {
+            return (point, SnapKind::Edge);
+        }
+    }
+
+    (hit.point, SnapKind::Surface)
+}
+
+fn angle_between(a: &Vec3, vertex: &Vec3, c: &Vec3) -> f32 {
+    let first = a - vertex;
+    let second = c - vertex;
+    let lengths = first.norm() * second.norm();
+    if lengths <= f32::EPSILON {
+        return 0.0;
+    }
+    (first.dot(&second) / lengths).clamp(-1.0, 1.0).acos()
+}
+
+const HUMAN_HEIGHT: f32 = 1.8;
+
+fn line_segments_mesh(segments: &[(Vec3, Vec3)], width: f32) -> Mesh {
+    let mut vertices = Vec::with_capacity(segments.len() * 24);
+    let mut indices = Vec::with_capacity(segments.len() * 36);
+    let half_width = width * 0.5;
+
+    for (start, end) in segments {
+        let along = end - start;
+        if along.norm_squared() <= f32::EPSILON {
+            continue;
+        }
+        let direction = nalgebra_glm::normalize(&along);
+        let reference = if direction.y.abs() < 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let side = nalgebra_glm::normalize(&direction.cross(&reference));
+        let up = side.cross(&direction);
+
+        let center = (start + end) * 0.5;
+        let half_extents = [along * 0.5, side * half_width, up * half_width];
+        for axis in 0..3 {
+            let first = half_extents[(axis + 1) % 3];
+            let second = half_extents[(axis + 2) % 3];
+            for sign in [1.0, -1.0] {
+                let offset = half_extents[axis] * sign;
+                let normal = nalgebra_glm::normalize(&offset);
+                let face_center = center + offset;
+                let base_index = vertices.len() as u32;
+                for corner in [
+                    face_center - first - second,
+                    face_center + first - second,
+                    face_center + first + second,
+                    face_center - first + second,
+                ] {
+                    vertices.push(Vertex {
+                        position: corner.into(),
+                        normal: normal.into(),
+                        tex_coords: [0.0; 2],
+                        tex_coords_1: [0.0; 2],
+                        tangent: [0.0; 4],
                     });
-                    ui.separator();
                 }
+                let winding = if first.cross(&second).dot(&normal) > 0.0 {
+                    [0, 1, 2, 0, 2, 3]
+                } else {
+                    [0, 2, 1, 0, 3, 2]
+                };
+                indices.extend(winding.map(|offset| base_index + offset));
+            }
+        }
+    }
 
-                ui.collapsing("Skybox", |ui| {
-                    let current_is_default = self.selected_custom_skybox.is_none()
-                        && world.resources.graphics.atmosphere == Atmosphere::Hdr;
+    Mesh {
+        vertices,
+        indices,
+        skin_data: None,
+        morph_targets: None,
+    }
+}
 
-                    if ui
-                        .selectable_label(current_is_default, "Default (Moonrise)")
-                        .clicked()
-                    {
-                        load_hdr_skybox(world, DEFAULT_HDR_BYTES.to_vec());
-                        world.resources.graphics.atmosphere = Atmosphere::Hdr;
-                        self.selected_custom_skybox = None;
-                    }
+fn human_silhouette_segments() -> Vec<(Vec3, Vec3)> {
+    let point = |x: f32, y: f32| Vec3::new(x, y, 0.0);
+
+    let mut segments = vec![
+        (point(0.0, 0.86), point(0.0, 0.53)),
+        (point(-0.12, 0.80), point(0.12, 0.80)),
+        (point(-0.12, 0.80), point(-0.17, 0.48)),
+        (point(0.12, 0.80), point(0.17, 0.48)),
+        (point(-0.07, 0.53), point(0.07, 0.53)),
+        (point(-0.07, 0.53), point(-0.08, 0.0)),
+        (point(0.07, 0.53), point(0.08, 0.0)),
+    ];
+
+    let head_center = point(0.0, 0.93);
+    let head_radius = 0.07;
+    let steps = 16;
+    for step in 0..steps {
+        let angle = |step: i32| step as f32 / steps as f32 * std::f32::consts::TAU;
+        let (start, end) = (angle(step), angle(step + 1));
+        segments.push((
+            head_center + point(start.cos(), start.sin()) * head_radius,
+            head_center + point(end.cos(), end.sin()) * head_radius,
+        ));
+    }
 
-                    for (index, skybox) in self.custom_skyboxes.iter().enumerate() {
-                        if skybox.path.as_os_str().is_empty() {
-                            continue;
-                        }
-                        let is_selected = self.selected_custom_skybox == Some(index);
-                        if ui.selectable_label(is_selected, &skybox.name).clicked() {
-                            load_hdr_skybox_from_path(world, skybox.path.clone());
-                            world.resources.graphics.atmosphere = Atmosphere::Hdr;
-                            self.selected_custom_skybox = Some(index);
-                        }
-                    }
+    segments
+}
+
+const TEXEL_DENSITY_RAMP_WIDTH: usize = 256;
+const SURFACE_DEBUG_SETTLE_SECONDS: f32 = 0.25;
+
+fn checker_rgba(size: usize, cells: usize) -> Vec<u8> {
+    let cell_size = (size / cells).max(1);
+    (0..size * size)
+        .flat_map(|index| {
+            let (x, y) = (index % size, index / size);
+            let (cell_x, cell_y) = (x / cell_size, y / cell_size);
+            let u = cell_x as f32 / (cells - 1).max(1) as f32;
+            let v = cell_y as f32 / (cells - 1).max(1) as f32;
+            if (cell_x + cell_y) % 2 == 0 {
+                [
+                    (150.0 + 100.0 * u) as u8,
+                    (150.0 + 100.0 * v) as u8,
+                    200,
+                    255,
+                ]
+            } else {
+                [(40.0 + 80.0 * u) as u8, (40.0 + 80.0 * v) as u8, 60, 255]
+            }
+        })
+        .collect()
+}
+
+/// One row of `texel_density_color` from a quarter of the target density to four times
+/// it; density meshes point their UVs into this row.
+fn texel_density_ramp(width: usize) -> Vec<u8> {
+    (0..width)
+        .flat_map(|x| {
+            let t = x as f32 / (width - 1) as f32 * 2.0 - 1.0;
+            texel_density_color(4.0_f32.powf(t)).to_array()
+        })
+        .collect()
+}
+
+fn mesh_with_secondary_uvs(mesh: &Mesh) -> Mesh {
+    Mesh {
+        vertices: mesh
+            .vertices
+            .iter()
+            .map(|vertex| Vertex {
+                tex_coords: vertex.tex_coords_1,
+                ..*vertex
+            })
+            .collect(),
+        indices: mesh.indices.clone(),
+        skin_data: mesh.skin_data.clone(),
+        morph_targets: mesh.morph_targets.clone(),
+    }
+}
+
+/// Unwelds `mesh` so every triangle can carry its own density, with both UV sets pointing
+/// at the matching texel of the density ramp. Morph targets are dropped.
+fn texel_density_mesh(
+    mesh: &Mesh,
+    global_matrix: &Mat4,
+    uv_set: usize,
+    texel_count: f32,
+    target: f32,
+    area_to_original_units: f32,
+) -> Mesh {
+    let triangle_count = if mesh.indices.is_empty() {
+        mesh.vertices.len() / 3
+    } else {
+        mesh.indices.len() / 3
+    };
+    let mut vertices = Vec::with_capacity(triangle_count * 3);
+    let mut joint_indices = Vec::new();
+    let mut joint_weights = Vec::new();
+
+    for triangle_index in 0..triangle_count {
+        let indices: [usize; 3] = std::array::from_fn(|corner| {
+            let index = triangle_index * 3 + corner;
+            if mesh.indices.is_empty() {
+                index
+            } else {
+                mesh.indices[index] as usize
+            }
+        });
+        let Some(corners) = indices
+            .iter()
+            .map(|index| mesh.vertices.get(*index))
+            .collect::<Option<Vec<_>>>()
+        else {
+            continue;
+        };
+
+        let positions: [Vec3; 3] = std::array::from_fn(|corner| {
+            transform_point(global_matrix, &Vec3::from(corners[corner].position))
+        });
+        let uvs: [Vec2; 3] = std::array::from_fn(|corner| {
+            Vec2::from(if uv_set == 0 {
+                corners[corner].tex_coords
+            } else {
+                corners[corner].tex_coords_1
+            })
+        });
+        let uv_area = (uvs[1] - uvs[0]).perp(&(uvs[2] - uvs[0])).abs() * 0.5;
+        let world_area = (positions[1] - positions[0])
+            .cross(&(positions[2] - positions[0]))
+            .norm()
+            * 0.5
+            * area_to_original_units;
+        let density = if world_area > f32::EPSILON {
+            (uv_area * texel_count / world_area).sqrt()
+        } else {
+            0.0
+        };
+        let t = ((density / target).max(f32::EPSILON).log2() / 2.0).clamp(-1.0, 1.0);
+        let ramp = TEXEL_DENSITY_RAMP_WIDTH as f32;
+        let u = (0.5 + (t + 1.0) * 0.5 * (ramp - 1.0)) / ramp;
+
+        for (corner, index) in corners.iter().zip(indices) {
+            vertices.push(Vertex {
+                tex_coords: [u, 0.5],
+                tex_coords_1: [u, 0.5],
+                ..**corner
+            });
+            if let Some(skin_data) = &mesh.skin_data {
+                joint_indices.push(
+                    skin_data
+                        .joint_indices
+                        .get(index)
+                        .copied()
+                        .unwrap_or_default(),
+                );
+                joint_weights.push(
+                    skin_data
+                        .joint_weights
+                        .get(index)
+                        .copied()
+                        .unwrap_or_default(),
+                );
+            }
+        }
+    }
+
+    Mesh {
+        indices: (0..vertices.len() as u32).collect(),
+        vertices,
+        skin_data: mesh.skin_data.as_ref().map(|_| SkinData {
+            joint_indices,
+            joint_weights,
+        }),
+        morph_targets: None,
+    }
+}
+
+fn texel_density_color(ratio: f32) -> egui::Color32 {
+    let t = (ratio.max(f32::EPSILON).log2() / 2.0).clamp(-1.0, 1.0);
+    let (cold, target, hot) = (
+        egui::Rgba::from_rgb(0.1, 0.3, 1.0),
+        egui::Rgba::from_rgb(0.1, 0.9, 0.2),
+        egui::Rgba::from_rgb(1.0, 0.15, 0.1),
+    );
+    let color = if t < 0.0 {
+        egui::lerp(target..=cold, -t)
+    } else {
+        egui::lerp(target..=hot, t)
+    };
+    egui::Color32::from(color)
+}
+
+fn draw_world_segment(
+    painter: &egui::Painter,
+    projection: &ViewportProjection,
+    start: &Vec3,
+    end: &Vec3,
+    stroke: egui::Stroke,
+) {
+    if let (Some(start), Some(end)) = (
+        projection.world_to_screen(start),
+        projection.world_to_screen(end),
+    ) {
+        painter.line_segment([start, end], stroke);
+    }
+}
+
+fn draw_world_line(
+    painter: &egui::Painter,
+    projection: &ViewportProjection,
+    start: &Vec3,
+    end: &Vec3,
+    stroke: egui::Stroke,
+) {
+    const SEGMENTS: usize = 16;
+    let points: Vec<Option<egui::Pos2>> = (0..=SEGMENTS)
+        .map(|index| {
+            let t = index as f32 / SEGMENTS as f32;
+            projection.world_to_screen(&(start + (end - start) * t))
+        })
+        .collect();
+
+    for pair in points.windows(2) {
+        if let (Some(first), Some(second)) = (pair[0], pair[1]) {
+            painter.line_segment([first, second], stroke);
+        }
+    }
+}
+
+fn format_length(length: f32) -> String {
+    if length.abs() < 0.01 {
+        format!("{:.2} mm", length * 1000.0)
+    } else if length.abs() < 1.0 {
+        format!("{:.1} cm", length * 100.0)
+    } else {
+        format!("{:.3} m", length)
+    }
+}
+
+fn draw_label(painter: &egui::Painter, position: egui::Pos2, text: String, font: egui::FontId) {
+    let galley = painter.layout_no_wrap(text, font, egui::Color32::WHITE);
+    let rect = egui::Rect::from_center_size(position, galley.size()).expand(3.0);
+    painter.rect_filled(rect, 3.0, egui::Color32::from_black_alpha(180));
+    painter.galley(
+        rect.min + egui::vec2(3.0, 3.0),
+        galley,
+        egui::Color32::WHITE,
+    );
+}
+
+fn hierarchy_node_ui(
+    ui: &mut egui::Ui,
+    world: &World,
+    entity: Entity,
+    selected_path: &[Entity],
+    reveal: bool,
+    clicked: &mut Option<Entity>,
+) {
+    let name = entity_display_name(world, entity);
+    let is_selected = selected_path.last() == Some(&entity);
+    let children: Vec<Entity> = world
+        .resources
+        .children_cache
+        .get(&entity)
+        .cloned()
+        .unwrap_or_default();
+
+    let label = |ui: &mut egui::Ui, clicked: &mut Option<Entity>| {
+        let response = ui.selectable_label(is_selected, &name);
+        if response.clicked() {
+            *clicked = Some(entity);
+        }
+        if is_selected && reveal {
+            response.scroll_to_me(Some(egui::Align::Center));
+        }
+    };
+
+    if children.is_empty() {
+        ui.horizontal(|ui| {
+            ui.add_space(ui.spacing().indent);
+            label(ui, clicked);
+        });
+        return;
+    }
+
+    let mut state = egui::collapsing_header::CollapsingState::load_with_default_open(
+        ui.ctx(),
+        ui.make_persistent_id(("hierarchy_node", entity)),
+        false,
+    );
+    if reveal && selected_path.contains(&entity) && !is_selected {
+        state.set_open(true);
+    }
+    state.show_header(ui, |ui| label(ui, clicked)).body(|ui| {
+        for child in children {
+            hierarchy_node_ui(ui, world, child, selected_path, reveal, clicked);
+        }
+    });
+}
+
+fn entity_display_name(world: &World, entity: Entity) -> String {
+    world
+        .get_name(entity)
+        .map(|name| name.0.clone())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| format!("{:?}", entity))
+}
+
+fn instance_namespace(instance: usize) -> String {
+    format!("instance-{instance}/")
+}
+
+fn instance_resource_name(name: &str) -> &str {
+    name.strip_prefix("instance-")
+        .and_then(|rest| rest.split_once('/'))
+        .filter(|(instance, _)| instance.parse::<usize>().is_ok())
+        .map_or(name, |(_, name)| name)
+}
+
+fn find_entity_path(world: &World, roots: &[Entity], target: Entity) -> Option<Vec<Entity>> {
+    fn search(world: &World, entity: Entity, target: Entity, path: &mut Vec<Entity>) -> bool {
+        path.push(entity);
+        if entity == target {
+            return true;
+        }
+        if let Some(children) = world.resources.children_cache.get(&entity) {
+            for child in children {
+                if search(world, *child, target, path) {
+                    return true;
+                }
+            }
+        }
+        path.pop();
+        false
+    }
+
+    let mut path = Vec::new();
+    roots
+        .iter()
+        .any(|root| search(world, *root, target, &mut path))
+        .then_some(path)
+}
+
+fn selected_root(world: &World, roots: &[Entity], entity: Entity) -> Option<Entity> {
+    find_entity_path(world, roots, entity).and_then(|path| path.first().copied())
+}
+
+fn raycast_scene(
+    world: &World,
+    roots: &[Entity],
+    mesh_bvhs: &mut std::collections::HashMap<String, MeshBvh>,
+    mesh_primitives: &std::collections::HashMap<String, Vec<(usize, usize)>>,
+    origin: &Vec3,
+    direction: &Vec3,
+) -> Option<RaycastHit> {
+    let mut candidates = Vec::new();
+    for root in roots {
+        collect_raycast_candidates(
+            world,
+            *root,
+            *root,
+            &Mat4::identity(),
+            origin,
+            direction,
+            &mut candidates,
+        );
+    }
+    candidates.sort_by(|a, b| a.3.total_cmp(&b.3));
+
+    let mut closest: Option<RaycastHit> = None;
+    for (entity, root, global_matrix, box_distance) in candidates {
+        if closest
+            .as_ref()
+            .is_some_and(|closest| box_distance > closest.distance)
+        {
+            break;
+        }
+
+        // Nodes without a cached mesh have nothing to hit, only a bounding box.
+        let Some((distance, triangle_index, primitive, triangle)) = raycast_mesh(
+            world,
+            mesh_bvhs,
+            mesh_primitives,
+            entity,
+            &global_matrix,
+            origin,
+            direction,
+        ) else {
+            continue;
+        };
+        let hit = RaycastHit {
+            entity,
+            root,
+            distance,
+            point: origin + direction * distance,
+            triangle_index,
+            primitive,
+            triangle,
+        };
+
+        if closest
+            .as_ref()
+            .is_none_or(|closest| hit.distance < closest.distance)
+        {
+            closest = Some(hit);
+        }
+    }
+
+    closest
+}
+
+fn collect_raycast_candidates(
+    world: &World,
+    entity: Entity,
+    root: Entity,
+    parent_transform: &Mat4,
+    origin: &Vec3,
+    direction: &Vec3,
+    candidates: &mut Vec<(Entity, Entity, Mat4, f32)>,
+) {
+    let global_matrix = parent_transform * local_transform_matrix(world, entity);
+
+    if let Some(bounding_volume) = world.get_bounding_volume(entity)
+        && let Some(inverse_matrix) = global_matrix.try_inverse()
+    {
+        let (min, max) = local_bounds(bounding_volume);
+        let local_origin = transform_point(&inverse_matrix, origin);
+        let local_direction = transform_vector(&inverse_matrix, direction);
+
+        if let Some(distance) = ray_aabb_intersection(&local_origin, &local_direction, &min, &max) {
+            candidates.push((entity, root, global_matrix, distance));
+        }
+    }
+
+    let children: Vec<Entity> = world
+        .resources
+        .children_cache
+        .get(&entity)
+        .cloned()
+        .unwrap_or_default();
+
+    for child in children {
+        collect_raycast_candidates(
+            world,
+            child,
+            root,
+            &global_matrix,
+            origin,
+            direction,
+            candidates,
+        );
+    }
+}
+
+fn raycast_mesh(
+    world: &World,
+    mesh_bvhs: &mut std::collections::HashMap<String, MeshBvh>,
+    mesh_primitives: &std::collections::HashMap<String, Vec<(usize, usize)>>,
+    entity: Entity,
+    global_matrix: &Mat4,
+    origin: &Vec3,
+    direction: &Vec3,
+) -> Option<(f32, usize, Option<usize>, [Vec3; 3])> {
+    let render_mesh = world.get_render_mesh(entity)?;
+    let bvh = match mesh_bvhs.entry(render_mesh.name.clone()) {
+        std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+        std::collections::hash_map::Entry::Vacant(entry) => {
+            let mesh = mesh_cache_get(&world.resources.mesh_cache, &render_mesh.name)?;
+            let primitives = mesh_primitives
+                .get(&render_mesh.name)
+                .cloned()
+                .unwrap_or_default();
+            entry.insert(MeshBvh::new(mesh, primitives))
+        }
+    };
+    let inverse_matrix = global_matrix.try_inverse()?;
+
+    let local_origin = transform_point(&inverse_matrix, origin);
+    let local_direction = transform_vector(&inverse_matrix, direction);
+
+    let (distance, triangle_index, triangle) = bvh.raycast(&local_origin, &local_direction)?;
+    Some((
+        distance,
+        triangle_index,
+        bvh.primitive(triangle_index),
+        triangle.map(|vertex| transform_point(global_matrix, &vertex)),
+    ))
+}
+
+/// Carries another root of an instance along with an edit to its first root, which acts
+/// as the instance pivot. Transforms are `(translation, rotation, scale)`.
+fn follow_root_edit(
+    before: (Vec3, Quat, Vec3),
+    after: (Vec3, Quat, Vec3),
+    root: (Vec3, Quat, Vec3),
+) -> (Vec3, Quat, Vec3) {
+    let (before_translation, before_rotation, before_scale) = before;
+    let (after_translation, after_rotation, after_scale) = after;
+    let (root_translation, root_rotation, root_scale) = root;
+
+    let ratio = after_scale.zip_map(&before_scale, |after, before| {
+        if before.abs() > f32::EPSILON {
+            after / before
+        } else {
+            1.0
+        }
+    });
+    let offset = nalgebra_glm::quat_rotate_vec3(
+        &nalgebra_glm::quat_inverse(&before_rotation),
+        &(root_translation - before_translation),
+    )
+    .component_mul(&ratio);
+
+    (
+        after_translation + nalgebra_glm::quat_rotate_vec3(&after_rotation, &offset),
+        nalgebra_glm::quat_normalize(
+            &(after_rotation * nalgebra_glm::quat_inverse(&before_rotation) * root_rotation),
+        ),
+        root_scale.component_mul(&ratio),
+    )
+}
+
+fn mesh_triangle_count(mesh: &Mesh) -> usize {
+    if mesh.indices.is_empty() {
+        mesh.vertices.len() / 3
+    } else {
+        mesh.indices.len() / 3
+    }
+}
+
+/// Maps a cached mesh to the glTF primitives it was built from, given the per-primitive
+/// triangle counts of every named glTF mesh. A mesh holding all of a glTF mesh's
+/// primitives keeps its name; one holding a single primitive is suffixed with its index.
+/// Either way the triangle counts must agree, otherwise the mapping is unknown.
+#[cfg(not(target_arch = "wasm32"))]
+fn primitive_starts(
+    gltf_meshes: &std::collections::HashMap<String, Vec<u64>>,
+    mesh_name: &str,
+    triangle_count: usize,
+) -> Option<Vec<(usize, usize)>> {
+    if let Some(counts) = gltf_meshes.get(mesh_name)
+        && counts.iter().sum::<u64>() == triangle_count as u64
+    {
+        let mut start = 0;
+        return Some(
+            counts
+                .iter()
+                .enumerate()
+                .map(|(primitive, count)| {
+                    let entry = (start, primitive);
+                    start += *count as usize;
+                    entry
+                })
+                .collect(),
+        );
+    }
+    let (base, suffix) = mesh_name.rsplit_once('_')?;
+    let primitive: usize = suffix.parse().ok()?;
+    (gltf_meshes.get(base)?.get(primitive) == Some(&(triangle_count as u64)))
+        .then(|| vec![(0, primitive)])
+}
+
+fn ray_triangle_intersection(
+    origin: &Vec3,
+    direction: &Vec3,
+    a: &Vec3,
+    b: &Vec3,
+    c: &Vec3,
+) -> Option<f32> {
+    let edge1 = b - a;
+    let edge2 = c - a;
+    let p = direction.cross(&edge2);
+    let determinant = edge1.dot(&p);
+    if determinant.abs() <= f32::EPSILON {
+        return None;
+    }
+
+    let inverse_determinant = 1.0 / determinant;
+    let t_vector = origin - a;
+    let u = t_vector.dot(&p) * inverse_determinant;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = t_vector.cross(&edge1);
+    let v = direction.dot(&q) * inverse_determinant;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let distance = edge2.dot(&q) * inverse_determinant;
+    (distance > 0.0).then_some(distance)
+}
+
+fn local_bounds(bounding_volume: &BoundingVolume) -> (Vec3, Vec3) {
+    let corners = bounding_volume.obb.get_corners();
+    let mut min = corners[0];
+    let mut max = corners[0];
+    for corner in &corners {
+        min = nalgebra_glm::min2(&min, corner);
+        max = nalgebra_glm::max2(&max, corner);
+    }
+    (min, max)
+}
+
+fn box_corners(min: &Vec3, max: &Vec3) -> [Vec3; 8] {
+    std::array::from_fn(|index| {
+        Vec3::new(
+            if index & 1 == 0 { min.x } else { max.x },
+            if index & 2 == 0 { min.y } else { max.y },
+            if index & 4 == 0 { min.z } else { max.z },
+        )
+    })
+}
+
+fn draw_box_edges(
+    painter: &egui::Painter,
+    projection: &ViewportProjection,
+    corners: &[Vec3; 8],
+    stroke: egui::Stroke,
+) {
+    let screen_corners: Vec<Option<egui::Pos2>> = corners
+        .iter()
+        .map(|corner| projection.world_to_screen(corner))
+        .collect();
+
+    for (start, end) in BOX_EDGES {
+        if let (Some(start), Some(end)) = (screen_corners[start], screen_corners[end]) {
+            painter.line_segment([start, end], stroke);
+        }
+    }
+}
+
+fn ray_aabb_intersection(origin: &Vec3, direction: &Vec3, min: &Vec3, max: &Vec3) -> Option<f32> {
+    let mut t_min = f32::MIN;
+    let mut t_max = f32::MAX;
+
+    for axis in 0..3 {
+        if direction[axis].abs() <= f32::EPSILON {
+            if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                return None;
+            }
+            continue;
+        }
+        let inverse = 1.0 / direction[axis];
+        let t0 = (min[axis] - origin[axis]) * inverse;
+        let t1 = (max[axis] - origin[axis]) * inverse;
+        t_min = t_min.max(t0.min(t1));
+        t_max = t_max.min(t0.max(t1));
+    }
+
+    (t_max >= t_min.max(0.0)).then_some(t_min.max(0.0))
+}
 
-                    ui.separator();
+fn collect_global_matrices(world: &World, roots: &[Entity]) -> Vec<(Entity, Option<Entity>, Mat4)> {
+    fn collect(
+        world: &World,
+        entity: Entity,
+        parent: Option<Entity>,
+        parent_transform: &Mat4,
+        nodes: &mut Vec<(Entity, Option<Entity>, Mat4)>,
+    ) {
+        let global_matrix = parent_transform * local_transform_matrix(world, entity);
+        nodes.push((entity, parent, global_matrix));
 
-                    ui.label("Procedural Atmospheres:");
-                    for atmosphere in Atmosphere::ALL {
-                        if atmosphere.is_procedural() {
-                            let is_selected = world.resources.graphics.atmosphere == *atmosphere
-                                && self.selected_custom_skybox.is_none();
-                            if ui
-                                .selectable_label(is_selected, format!("{:?}", atmosphere))
-                                .clicked()
-                            {
-                                world.resources.graphics.atmosphere = *atmosphere;
-                                self.selected_custom_skybox = None;
-                                capture_procedural_atmosphere_ibl(world, *atmosphere, 0.0);
-                            }
-                        }
-                    }
-                });
+        if let Some(children) = world.resources.children_cache.get(&entity) {
+            for child in children {
+                collect(world, *child, Some(entity), &global_matrix, nodes);
+            }
+        }
+    }
 
-                ui.collapsing("Color Grading", |ui| {
-                    let color_grading = &mut world.resources.graphics.color_grading;
+    let mut nodes = Vec::new();
+    for root in roots {
+        collect(world, *root, None, &Mat4::identity(), &mut nodes);
+    }
+    nodes
+}
 
-                    ui.horizontal(|ui| {
-                        ui.label("Preset:");
-                        ui.label(color_grading.preset.name());
-                    });
+fn find_parent_matrix(world: &World, roots: &[Entity], target: Entity) -> Option<Mat4> {
+    roots
+        .iter()
+        .find_map(|root| find_parent_matrix_recursive(world, *root, &Mat4::identity(), target))
+}
 
-                    ui.horizontal_wrapped(|ui| {
-                        for preset in ColorGradingPreset::ALL {
-                            if *preset == ColorGradingPreset::Custom {
-                                continue;
-                            }
-                            let is_selected = color_grading.preset == *preset;
-                            if ui.selectable_label(is_selected, preset.name()).clicked() {
-                                *color_grading = preset.to_color_grading();
-                            }
-                        }
-                    });
+fn find_parent_matrix_recursive(
+    world: &World,
+    entity: Entity,
+    parent_transform: &Mat4,
+    target: Entity,
+) -> Option<Mat4> {
+    if entity == target {
+        return Some(*parent_transform);
+    }
 
-                    ui.add_space(4.0);
+    let global_matrix = parent_transform * local_transform_matrix(world, entity);
+    world
+        .resources
+        .children_cache
+        .get(&entity)?
+        .iter()
+        .find_map(|child| find_parent_matrix_recursive(world, *child, &global_matrix, target))
+}
 
-                    ui.horizontal(|ui| {
-                        ui.label("Tonemap:");
-                        egui::ComboBox::from_id_salt("tonemap_algorithm")
-                            .selected_text(color_grading.tonemap_algorithm.name())
-                            .show_ui(ui, |ui| {
-                                for algorithm in TonemapAlgorithm::ALL {
-                                    if ui
-                                        .selectable_value(
-                                            &mut color_grading.tonemap_algorithm,
-                                            *algorithm,
-                                            algorithm.name(),
-                                        )
-                                        .changed()
-                                    {
-                                        color_grading.preset = ColorGradingPreset::Custom;
-                                    }
-                                }
-                            });
-                    });
+fn local_transform_matrix(world: &World, entity: Entity) -> Mat4 {
+    world
+        .get_local_transform(entity)
+        .map(|t| {
+            nalgebra_glm::translation(&t.translation)
+                * nalgebra_glm::quat_to_mat4(&t.rotation)
+                * nalgebra_glm::scaling(&t.scale)
+        })
+        .unwrap_or_else(Mat4::identity)
+}
 
-                    ui.horizontal(|ui| {
-                        ui.label("Gamma:");
-                        if ui
-                            .add(
-                                egui::Slider::new(&mut color_grading.gamma, 1.0..=3.0)
-                                    .fixed_decimals(2),
-                            )
-                            .changed()
-                        {
-                            color_grading.preset = ColorGradingPreset::Custom;
-                        }
-                    });
+/// Joint matrices of an entity's skin, or `None` when its mesh is not skinned.
+fn skin_joint_matrices(
+    world: &World,
+    entity: Entity,
+    mesh: &Mesh,
+    globals: &std::collections::HashMap<Entity, Mat4>,
+) -> Option<Vec<Mat4>> {
+    mesh.skin_data.as_ref()?;
+    let skin = world.get_skin(entity)?;
+    Some(
+        skin.joints
+            .iter()
+            .zip(&skin.inverse_bind_matrices)
+            .map(|(joint, inverse_bind)| {
+                globals.get(joint).copied().unwrap_or_else(Mat4::identity) * inverse_bind
+            })
+            .collect(),
+    )
+}
 
-                    ui.horizontal(|ui| {
-                        ui.label("Saturation:");
-                        if ui
-                            .add(
-                                egui::Slider::new(&mut color_grading.saturation, 0.0..=2.0)
-                                    .fixed_decimals(2),
-                            )
-                            .changed()
-                        {
-                            color_grading.preset = ColorGradingPreset::Custom;
-                        }
-                    });
+fn skinned_vertex_matrix(
+    joint_matrices: &[Mat4],
+    skin_data: &SkinData,
+    index: usize,
+) -> Option<Mat4> {
+    let joints = skin_data.joint_indices.get(index)?;
+    let weights = skin_data.joint_weights.get(index)?;
+    Some(
+        joints
+            .iter()
+            .zip(weights)
+            .filter_map(|(joint, weight)| Some(joint_matrices.get(*joint as usize)? * *weight))
+            .fold(Mat4::zeros(), |sum, matrix| sum + matrix),
+    )
+}
 
-                    ui.horizontal(|ui| {
-                        ui.label("Brightness:");
-                        if ui
-                            .add(
-                                egui::Slider::new(&mut color_grading.brightness, -0.5..=0.5)
-                                    .fixed_decimals(2),
-                            )
-                            .changed()
-                        {
-                            color_grading.preset = ColorGradingPreset::Custom;
-                        }
-                    });
+fn transform_point(matrix: &Mat4, point: &Vec3) -> Vec3 {
+    let transformed = matrix * Vec4::new(point.x, point.y, point.z, 1.0);
+    transformed.xyz() / transformed.w
+}
 
-                    ui.horizontal(|ui| {
-                        ui.label("Contrast:");
-                        if ui
-                            .add(
-                                egui::Slider::new(&mut color_grading.contrast, 0.5..=2.0)
-                                    .fixed_decimals(2),
-                            )
-                            .changed()
-                        {
-                            color_grading.preset = ColorGradingPreset::Custom;
-                        }
-                    });
-                });
+fn transform_vector(matrix: &Mat4, vector: &Vec3) -> Vec3 {
+    (matrix * Vec4::new(vector.x, vector.y, vector.z, 0.0)).xyz()
+}
 
-                ui.collapsing("Model", |ui| {
-                    ui.horizontal(|ui| {
-                        ui.label("Rotation Speed:");
-                        ui.add(
-                            egui::Slider::new(&mut self.rotation_speed, 0.0..=2.0)
-                                .fixed_decimals(2),
-                        );
-                    });
+fn inverse_transform_point(matrix: &Mat4, point: &Vec3) -> Vec3 {
+    matrix
+        .try_inverse()
+        .map(|inverse| transform_point(&inverse, point))
+        .unwrap_or(*point)
+}
 
-                    if ui.button("Reset Camera").clicked() {
-                        self.reset_camera(world);
-                    }
-                });
+fn inverse_transform_vector(matrix: &Mat4, vector: &Vec3) -> Vec3 {
+    matrix
+        .try_inverse()
+        .map(|inverse| transform_vector(&inverse, vector))
+        .unwrap_or(*vector)
+}
 
-                self.animation_ui(world, ui);
+fn matrix_rotation(matrix: &Mat4) -> Quat {
+    let mut rotation = nalgebra_glm::mat4_to_mat3(matrix);
+    for mut column in rotation.column_iter_mut() {
+        let length = column.norm();
+        if length > f32::EPSILON {
+            column /= length;
+        }
+    }
+    nalgebra_glm::mat3_to_quat(&rotation)
+}
 
-                ui.collapsing("Debug", |ui| {
-                    ui.horizontal(|ui| {
-                        ui.label("PBR Debug:");
-                        egui::ComboBox::from_id_salt("pbr_debug")
-                            .selected_text(world.resources.graphics.pbr_debug_mode.name())
-                            .show_ui(ui, |ui| {
-                                for mode in PbrDebugMode::ALL {
-                                    ui.selectable_value(
-                                        &mut world.resources.graphics.pbr_debug_mode,
-                                        *mode,
-                                        mode.name(),
-                                    );
-                                }
-                            });
-                    });
+fn euler_degrees_to_quat(degrees: [f32; 3]) -> Quat {
+    nalgebra_glm::quat_angle_axis(degrees[2].to_radians(), &Vec3::z())
+        * nalgebra_glm::quat_angle_axis(degrees[1].to_radians(), &Vec3::y())
+        * nalgebra_glm::quat_angle_axis(degrees[0].to_radians(), &Vec3::x())
+}
 
-                    ui.horizontal(|ui| {
-                        ui.label("Texture Stripes:");
-                        ui.checkbox(
-                            &mut world.resources.graphics.texture_debug_stripes,
-                            "Enabled",
-                        );
-                    });
+fn perpendicular_basis(axis: &Vec3) -> (Vec3, Vec3) {
+    let reference = if axis.x.abs() < 0.9 {
+        Vec3::x()
+    } else {
+        Vec3::y()
+    };
+    let tangent = nalgebra_glm::normalize(&axis.cross(&reference));
+    let bitangent = axis.cross(&tangent);
+    (tangent, bitangent)
+}
 
-                    if world.resources.graphics.texture_debug_stripes {
-                        ui.horizontal(|ui| {
-                            ui.label("Stripe Speed:");
-                            ui.add(
-                                egui::Slider::new(
-                                    &mut world.resources.graphics.texture_debug_stripes_speed,
-                                    0.0..=500.0,
-                                )
-                                .suffix(" px/s"),
-                            );
-                        });
-                    }
+fn distance_to_polyline(point: egui::Pos2, points: &[egui::Pos2]) -> f32 {
+    match points {
+        [] => f32::MAX,
+        [single] => point.distance(*single),
+        _ => points
+            .windows(2)
+            .map(|segment| distance_to_segment(point, segment[0], segment[1]))
+            .fold(f32::MAX, f32::min),
+    }
+}
 
-                    ui.checkbox(&mut world.resources.graphics.show_grid, "Show Grid");
-                });
-            });
+fn distance_to_segment(point: egui::Pos2, start: egui::Pos2, end: egui::Pos2) -> f32 {
+    let segment = end - start;
+    let length_squared = segment.length_sq();
+    if length_squared <= f32::EPSILON {
+        return point.distance(start);
     }
+    let t = ((point - start).dot(segment) / length_squared).clamp(0.0, 1.0);
+    point.distance(start + segment * t)
 }
 
-impl ViewerState {
-    fn drop_indicator_ui(&self, ui_context: &egui::Context) {
-        egui::Area::new(egui::Id::new("drop_indicator"))
-            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
-            .show(ui_context, |ui| {
-                let frame = egui::Frame::default()
-                    .fill(egui::Color32::from_rgba_premultiplied(30, 30, 30, 220))
-                    .corner_radius(12.0)
-                    .stroke(egui::Stroke::new(
-                        2.0,
-                        egui::Color32::from_rgb(100, 150, 250),
-                    ))
-                    .inner_margin(40.0);
+fn calculate_bounds_recursive(
+    world: &World,
+    entity: Entity,
+    parent_transform: &Mat4,
+    min: &mut Vec3,
+    max: &mut Vec3,
+    has_bounds: &mut bool,
+) {
+    let global_matrix = parent_transform * local_transform_matrix(world, entity);
 
-                frame.show(ui, |ui| {
-                    if let Some(file_type) = &self.drag_file_type {
-                        match file_type.as_str() {
-                            "HDR" => {
-                                ui.heading("Drop HDR file to use as skybox");
-                            }
-                            "glTF" => {
-                                ui.heading("Drop glTF/GLB file to load model");
-                            }
-                            "FBX" => {
-                                ui.heading("Drop FBX file to add animations");
-                            }
-                            _ => {
-                                ui.heading("Unsupported file type");
-                            }
-                        }
-                    }
-                });
-            });
+    if let Some(bounding_volume) = world.get_bounding_volume(entity) {
+        let transformed_obb = bounding_volume.obb.transform(&global_matrix);
+        let corners = transformed_obb.get_corners();
+        for corner in &corners {
+            *min = nalgebra_glm::min2(min, corner);
+            *max = nalgebra_glm::max2(max, corner);
+        }
+        *has_bounds = true;
     }
 
-    fn animation_ui(&mut self, world: &mut World, ui: &mut egui::Ui) {
-        let animation_entity = self.model_entities.first().copied();
-        let Some(entity) = animation_entity else {
-            return;
+    let children: Vec<Entity> = world
+        .resources
+        .children_cache
+        .get(&entity)
+        .cloned()
+        .unwrap_or_default();
+
+    for child in children {
+        calculate_bounds_recursive(world, child, &global_matrix, min, max, has_bounds);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn asset_entry_ui(ui: &mut egui::Ui, entry: &AssetEntry) -> bool {
+    let mut clicked = false;
+
+    ui.horizontal(|ui| {
+        let type_label = match entry.asset_type {
+            AssetType::Model => "[M]",
+            AssetType::Animation => "[A]",
+            AssetType::Skybox => "[S]",
         };
 
-        if !world.entity_has_animation_player(entity) {
-            return;
+        ui.label(type_label);
+
+        let response = ui.add(egui::Button::new(&entry.name).sense(egui::Sense::click_and_drag()));
+        if response.clicked() {
+            clicked = true;
+        }
+        if entry.asset_type == AssetType::Model {
+            response.dnd_set_drag_payload(entry.path.clone());
         }
 
-        let mut clip_to_play = None;
-        let mut clear_animations = false;
+        let mut details = vec![format_bytes(entry.file_size)];
+        if let Some(triangle_count) = entry.statistics.triangle_count {
+            details.push(format!("{} tris", triangle_count));
+        }
+        if let Some(texture_memory) = entry.statistics.texture_memory {
+            details.push(format!("{} tex", format_bytes(texture_memory)));
+        }
+        ui.weak(details.join(" | "));
+    });
 
-        ui.collapsing("Animation", |ui| {
-            if let Some(player) = world.get_animation_player_mut(entity) {
-                if player.clips.is_empty() {
-                    ui.label("No animations (drop FBX to add)");
-                    return;
-                }
+    if !entry.tags.is_empty() || !entry.categories.is_empty() {
+        ui.indent(entry.path.to_string_lossy(), |ui| {
+            if !entry.categories.is_empty() {
+                ui.label(format!("Categories: {}", entry.categories.join(", ")));
+            }
+            if !entry.tags.is_empty() {
+                ui.label(format!("Tags: {}", entry.tags.join(", ")));
+            }
+        });
+    }
 
-                ui.label(format!("{} clip(s) loaded", player.clips.len()));
+    clicked
+}
 
-                ui.horizontal(|ui| {
-                    ui.label("Clip:");
-                    egui::ComboBox::from_id_salt("animation_clip")
-                        .selected_text(
-                            player
-                                .current_clip
-                                .and_then(|index| player.clips.get(index))
-                                .map(|clip| clip.name.as_str())
-                                .unwrap_or("None"),
-                        )
-                        .show_ui(ui, |ui| {
-                            for (index, clip) in player.clips.iter().enumerate() {
-                                let is_selected = player.current_clip == Some(index);
-                                if ui.selectable_label(is_selected, &clip.name).clicked() {
-                                    clip_to_play = Some(index);
-                                }
-                            }
-                        });
-                });
+#[cfg(not(target_arch = "wasm32"))]
+fn duplicate_groups_ui(
+    ui: &mut egui::Ui,
+    duplicate_groups: &[DuplicateGroup],
+) -> Option<(PathBuf, AssetType)> {
+    let mut asset_to_load = None;
+
+    if duplicate_groups.is_empty() {
+        ui.label("No duplicates found (use Find Duplicates after scanning)");
+        return None;
+    }
 
-                if let Some(clip_index) = player.current_clip
-                    && let Some(clip) = player.clips.get(clip_index)
-                {
-                    ui.label(format!("Duration: {:.2}s", clip.duration));
+    let wasted: u64 = duplicate_groups
+        .iter()
+        .filter(|group| group.kind == DuplicateKind::Exact)
+        .map(|group| group.file_size * (group.paths.len() as u64 - 1))
+        .sum();
+    ui.label(format!(
+        "{} group(s), {} reclaimable from exact duplicates",
+        duplicate_groups.len(),
+        format_bytes(wasted)
+    ));
+
+    ui.separator();
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        for (group_index, group) in duplicate_groups.iter().enumerate() {
+            let first_name = group
+                .paths
+                .first()
+                .and_then(|path| path.file_name())
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            egui::CollapsingHeader::new(format!(
+                "{} x{} ({}, {})",
+                first_name,
+                group.paths.len(),
+                group.kind.name(),
+                format_bytes(group.file_size)
+            ))
+            .id_salt(("duplicate_group", group_index))
+            .default_open(false)
+            .show(ui, |ui| {
+                for path in &group.paths {
+                    let asset_type = path
+                        .extension()
+                        .and_then(|e| e.to_str())
+                        .and_then(AssetType::from_extension);
 
-                    ui.horizontal(|ui| {
-                        ui.label("Time:");
-                        ui.add(
-                            egui::Slider::new(&mut player.time, 0.0..=clip.duration)
-                                .fixed_decimals(2)
-                                .suffix("s"),
-                        );
-                    });
+                    if ui
+                        .button(path.to_string_lossy())
+                        .on_hover_text("Load this asset")
+                        .clicked()
+                        && let Some(asset_type) = asset_type
+                    {
+                        asset_to_load = Some((path.clone(), asset_type));
+                    }
                 }
+            });
+        }
+    });
 
-                ui.horizontal(|ui| {
-                    ui.label("Speed:");
-                    ui.add(
-                        egui::DragValue::new(&mut player.speed)
-                            .speed(0.1)
-                            .range(-5.0..=5.0)
-                            .fixed_decimals(1),
-                    );
-                });
-
-                ui.checkbox(&mut player.looping, "Loop");
+    asset_to_load
+}
 
-                ui.horizontal(|ui| {
-                    if player.playing {
-                        if ui.button("Pause").clicked() {
-                            player.pause();
-                        }
-                    } else if ui.button("Play").clicked() {
-                        player.resume();
-                    }
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Default)]
+struct GlbWriter {
+    binary: Vec<u8>,
+    buffer_views: Vec<serde_json::Value>,
+    accessors: Vec<serde_json::Value>,
+}
 
-                    if ui.button("Stop").clicked() {
-                        player.stop();
-                    }
-                });
+#[cfg(not(target_arch = "wasm32"))]
+impl GlbWriter {
+    const ARRAY_BUFFER: u32 = 34962;
+    const ELEMENT_ARRAY_BUFFER: u32 = 34963;
 
-                ui.separator();
+    fn buffer_view(&mut self, bytes: &[u8], target: Option<u32>) -> usize {
+        while !self.binary.len().is_multiple_of(4) {
+            self.binary.push(0);
+        }
+        let mut view = serde_json::json!({
+            "buffer": 0,
+            "byteOffset": self.binary.len(),
+            "byteLength": bytes.len(),
+        });
+        if let Some(target) = target {
+            view["target"] = serde_json::json!(target);
+        }
+        self.binary.extend_from_slice(bytes);
+        self.buffer_views.push(view);
+        self.buffer_views.len() - 1
+    }
 
-                if ui.button("Clear Animations").clicked() {
-                    clear_animations = true;
+    fn float_accessor(
+        &mut self,
+        values: &[f32],
+        accessor_type: &str,
+        components: usize,
+        target: Option<u32>,
+        bounds: bool,
+    ) -> usize {
+        let bytes: Vec<u8> = values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        let view = self.buffer_view(&bytes, target);
+        let mut accessor = serde_json::json!({
+            "bufferView": view,
+            "componentType": 5126,
+            "count": values.len() / components,
+            "type": accessor_type,
+        });
+        if bounds {
+            let mut min = vec![f32::MAX; components];
+            let mut max = vec![f32::MIN; components];
+            for element in values.chunks_exact(components) {
+                for (component, value) in element.iter().enumerate() {
+                    min[component] = min[component].min(*value);
+                    max[component] = max[component].max(*value);
                 }
             }
-        });
+            accessor["min"] = serde_json::json!(min);
+            accessor["max"] = serde_json::json!(max);
+        }
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
 
-        if let Some(index) = clip_to_play
-            && let Some(player) = world.get_animation_player_mut(entity)
+    fn integer_accessor(
+        &mut self,
+        bytes: &[u8],
+        component_type: u32,
+        count: usize,
+        accessor_type: &str,
+        target: u32,
+    ) -> usize {
+        let view = self.buffer_view(bytes, Some(target));
+        self.accessors.push(serde_json::json!({
+            "bufferView": view,
+            "componentType": component_type,
+            "count": count,
+            "type": accessor_type,
+        }));
+        self.accessors.len() - 1
+    }
+
+    fn mesh_primitive(&mut self, mesh: &Mesh) -> serde_json::Value {
+        let vertices = &mesh.vertices;
+        let positions: Vec<f32> = vertices.iter().flat_map(|vertex| vertex.position).collect();
+        let normals: Vec<f32> = vertices.iter().flat_map(|vertex| vertex.normal).collect();
+        let tex_coords: Vec<f32> = vertices
+            .iter()
+            .flat_map(|vertex| vertex.tex_coords)
+            .collect();
+        let tangents: Vec<f32> = vertices.iter().flat_map(|vertex| vertex.tangent).collect();
+
+        let mut attributes = serde_json::json!({
+            "POSITION": self.float_accessor(&positions, "VEC3", 3, Some(Self::ARRAY_BUFFER), true),
+            "NORMAL": self.float_accessor(&normals, "VEC3", 3, Some(Self::ARRAY_BUFFER), false),
+            "TEXCOORD_0": self.float_accessor(&tex_coords, "VEC2", 2, Some(Self::ARRAY_BUFFER), false),
+        });
+        if vertices.iter().any(|vertex| vertex.tangent != [0.0; 4]) {
+            attributes["TANGENT"] = serde_json::json!(self.float_accessor(
+                &tangents,
+                "VEC4",
+                4,
+                Some(Self::ARRAY_BUFFER),
+                false
+            ));
+        }
+        if vertices
+            .iter()
+            .any(|vertex| vertex.tex_coords_1 != [0.0; 2])
         {
-            player.play(index);
+            let tex_coords_1: Vec<f32> = vertices
+                .iter()
+                .flat_map(|vertex| vertex.tex_coords_1)
+                .collect();
+            attributes["TEXCOORD_1"] = serde_json::json!(self.float_accessor(
+                &tex_coords_1,
+                "VEC2",
+                2,
+                Some(Self::ARRAY_BUFFER),
+                false
+            ));
+        }
+        if let Some(skin_data) = &mesh.skin_data {
+            let joints: Vec<u8> = skin_data
+                .joint_indices
+                .iter()
+                .flatten()
+                .flat_map(|joint| (*joint as u16).to_le_bytes())
+                .collect();
+            let weights: Vec<f32> = skin_data.joint_weights.iter().flatten().copied().collect();
+            attributes["JOINTS_0"] = serde_json::json!(self.integer_accessor(
+                &joints,
+                5123,
+                skin_data.joint_indices.len(),
+                "VEC4",
+                Self::ARRAY_BUFFER
+            ));
+            attributes["WEIGHTS_0"] = serde_json::json!(self.float_accessor(
+                &weights,
+                "VEC4",
+                4,
+                Some(Self::ARRAY_BUFFER),
+                false
+            ));
         }
 
-        if clear_animations && let Some(player) = world.get_animation_player_mut(entity) {
-            player.clips.clear();
-            player.current_clip = None;
-            player.playing = false;
-            player.time = 0.0;
+        let mut primitive = serde_json::json!({ "attributes": attributes });
+        if !mesh.indices.is_empty() {
+            let indices: Vec<u8> = mesh
+                .indices
+                .iter()
+                .flat_map(|index| index.to_le_bytes())
+                .collect();
+            primitive["indices"] = serde_json::json!(self.integer_accessor(
+                &indices,
+                5125,
+                mesh.indices.len(),
+                "SCALAR",
+                Self::ELEMENT_ARRAY_BUFFER
+            ));
         }
+        primitive
     }
 
-    fn atmosphere_switch_system(&mut self, world: &mut World) {
-        let right_pressed = world
-            .resources
-            .input
-            .keyboard
-            .is_key_pressed(KeyCode::ArrowRight);
-        let left_pressed = world
-            .resources
-            .input
-            .keyboard
-            .is_key_pressed(KeyCode::ArrowLeft);
+    fn finish(mut self, mut document: serde_json::Value) -> Vec<u8> {
+        while !self.binary.len().is_multiple_of(4) {
+            self.binary.push(0);
+        }
+        if !self.binary.is_empty() {
+            document["buffers"] = serde_json::json!([{ "byteLength": self.binary.len() }]);
+            document["bufferViews"] = serde_json::json!(self.buffer_views);
+            document["accessors"] = serde_json::json!(self.accessors);
+        }
 
-        if right_pressed && !self.right_arrow_was_pressed {
-            world.resources.graphics.atmosphere = world.resources.graphics.atmosphere.next();
-            self.selected_custom_skybox = None;
+        let mut json = document.to_string().into_bytes();
+        while !json.len().is_multiple_of(4) {
+            json.push(b' ');
         }
-        if left_pressed && !self.left_arrow_was_pressed {
-            world.resources.graphics.atmosphere = world.resources.graphics.atmosphere.previous();
-            self.selected_custom_skybox = None;
+
+        let binary_chunk_length = if self.binary.is_empty() {
+            0
+        } else {
+            8 + self.binary.len()
+        };
+        let total_length = 12 + 8 + json.len() + binary_chunk_length;
+
+        let mut glb = Vec::with_capacity(total_length);
+        glb.extend_from_slice(b"glTF");
+        glb.extend_from_slice(&2u32.to_le_bytes());
+        glb.extend_from_slice(&(total_length as u32).to_le_bytes());
+        glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        glb.extend_from_slice(b"JSON");
+        glb.extend_from_slice(&json);
+        if !self.binary.is_empty() {
+            glb.extend_from_slice(&(self.binary.len() as u32).to_le_bytes());
+            glb.extend_from_slice(b"BIN\0");
+            glb.extend_from_slice(&self.binary);
         }
+        glb
+    }
+}
 
-        self.right_arrow_was_pressed = right_pressed;
-        self.left_arrow_was_pressed = left_pressed;
+#[cfg(not(target_arch = "wasm32"))]
+fn encode_png(width: u32, height: u32, rgba: &[u8]) -> Result<Vec<u8>, png::EncodingError> {
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(rgba)?;
+    writer.finish()?;
+    Ok(png)
+}
 
-        let current_atmosphere = world.resources.graphics.atmosphere;
-        if current_atmosphere != self.previous_atmosphere {
-            if current_atmosphere.is_procedural() {
-                capture_procedural_atmosphere_ibl(world, current_atmosphere, 0.0);
+#[cfg(not(target_arch = "wasm32"))]
+fn downsample_rgba(width: u32, height: u32, rgba: &[u8], factor: u32) -> (u32, u32, Vec<u8>) {
+    if factor <= 1 {
+        return (width, height, rgba.to_vec());
+    }
+
+    // Colors are averaged in linear light; averaging sRGB values darkens edges.
+    let to_linear: Vec<f32> = (0..=255u8)
+        .map(|value| srgb_to_linear(value as f32 / 255.0))
+        .collect();
+    let (output_width, output_height) = (width / factor, height / factor);
+    let mut output = Vec::with_capacity((output_width * output_height * 4) as usize);
+    for y in 0..output_height {
+        for x in 0..output_width {
+            let mut color = [0.0f32; 3];
+            let mut alpha = 0.0f32;
+            for sample_y in y * factor..(y + 1) * factor {
+                for sample_x in x * factor..(x + 1) * factor {
+                    let offset = ((sample_y * width + sample_x) * 4) as usize;
+                    let Some(pixel) = rgba.get(offset..offset + 4) else {
+                        continue;
+                    };
+                    let weight = pixel[3] as f32 / 255.0;
+                    for (channel, value) in color.iter_mut().zip(pixel) {
+                        *channel += to_linear[*value as usize] * weight;
+                    }
+                    alpha += weight;
+                }
             }
-            self.previous_atmosphere = current_atmosphere;
+            let samples = (factor * factor) as f32;
+            for channel in color {
+                output.push(if alpha > 0.0 {
+                    (linear_to_srgb(channel / alpha) * 255.0).round() as u8
+                } else {
+                    0
+                });
+            }
+            output.push((alpha / samples * 255.0).round() as u8);
         }
     }
+    (output_width, output_height, output)
+}
 
-    fn hint_hide_system(&mut self, world: &mut World) {
-        let uptime = world.resources.window.timing.uptime_milliseconds;
+#[cfg(not(target_arch = "wasm32"))]
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
 
-        if let Some(hint_entity) = self.hint_text_entity
-            && uptime > self.hint_hide_time
-        {
-            world.despawn_entities(&[hint_entity]);
-            self.hint_text_entity = None;
-        }
+#[cfg(not(target_arch = "wasm32"))]
+fn linear_to_srgb(value: f32) -> f32 {
+    let value = value.clamp(0.0, 1.0);
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
     }
+}
 
-    fn keyboard_shortcuts_system(&mut self, world: &mut World) {
-        let r_pressed = world.resources.input.keyboard.is_key_pressed(KeyCode::KeyR);
+#[cfg(not(target_arch = "wasm32"))]
+const SEQUENCE_WRITE_QUEUE: usize = 4;
 
-        if let Some(gui_state) = &world.resources.user_interface.state
-            && gui_state.egui_ctx().wants_keyboard_input()
-        {
-            self.r_was_pressed = r_pressed;
-            return;
+#[cfg(not(target_arch = "wasm32"))]
+struct SequenceVideo {
+    input: PathBuf,
+    output: PathBuf,
+    frame_rate: u32,
+    keep_frames: bool,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_sequence(
+    frames: std::sync::mpsc::Receiver<(PathBuf, nightshade::ecs::world::CapturedFrame)>,
+    events: std::sync::mpsc::Sender<SequenceEvent>,
+    cancel: &std::sync::atomic::AtomicBool,
+    directory: PathBuf,
+    video: Option<SequenceVideo>,
+) {
+    let cancelled = || cancel.load(std::sync::atomic::Ordering::Relaxed);
+    let mut written = Vec::new();
+    for (path, frame) in frames {
+        if cancelled() {
+            break;
         }
+        let result = encode_png(frame.width, frame.height, &frame.rgba)
+            .map_err(|error| error.to_string())
+            .and_then(|png| std::fs::write(&path, png).map_err(|error| error.to_string()));
+        let event = match result {
+            Ok(()) => {
+                written.push(path);
+                SequenceEvent::FrameWritten
+            }
+            Err(error) => SequenceEvent::FrameFailed(format!("{}: {}", path.display(), error)),
+        };
+        let _ = events.send(event);
+    }
 
-        if r_pressed && !self.r_was_pressed {
-            self.reset_camera(world);
-            world.resources.user_interface.enabled = true;
+    let result = match video {
+        _ if cancelled() => Err("Cancelled".to_string()),
+        None => Ok(directory),
+        Some(video) => {
+            let _ = events.send(SequenceEvent::Encoding);
+            let result = encode_video(&video, cancel);
+            if result.is_ok() && !video.keep_frames {
+                for path in &written {
+                    let _ = std::fs::remove_file(path);
+                }
+            }
+            result
+        }
+    };
+    let _ = events.send(SequenceEvent::Finished(result));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn encode_video(
+    video: &SequenceVideo,
+    cancel: &std::sync::atomic::AtomicBool,
+) -> Result<PathBuf, String> {
+    let mut child = std::process::Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error", "-nostats"])
+        .args(["-framerate", &video.frame_rate.to_string()])
+        .arg("-i")
+        .arg(&video.input)
+        .args(["-c:v", "libx264", "-pix_fmt", "yuv420p", "-vf"])
+        .arg("pad=ceil(iw/2)*2:ceil(ih/2)*2")
+        .arg(&video.output)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|error| format!("Failed to run ffmpeg: {}", error))?;
+
+    let status = loop {
+        if cancel.load(std::sync::atomic::Ordering::Relaxed) {
+            let _ = child.kill();
+            let _ = child.wait();
+            let _ = std::fs::remove_file(&video.output);
+            return Err("Cancelled".to_string());
+        }
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) => std::thread::sleep(std::time::Duration::from_millis(50)),
+            Err(error) => return Err(format!("Failed to wait for ffmpeg: {}", error)),
         }
+    };
 
-        self.r_was_pressed = r_pressed;
+    if status.success() {
+        return Ok(video.output.clone());
     }
+    let mut message = String::new();
+    if let Some(mut stderr) = child.stderr.take() {
+        use std::io::Read;
+        let _ = stderr.read_to_string(&mut message);
+    }
+    Err(format!("ffmpeg exited with {}: {}", status, message.trim()))
+}
 
-    #[cfg(not(target_arch = "wasm32"))]
-    fn asset_cycle_system(&mut self, world: &mut World) {
-        if self.asset_library.entries.is_empty() {
-            return;
-        }
+#[cfg(not(target_arch = "wasm32"))]
+fn sequence_frame_path(directory: &std::path::Path, prefix: &str, frame: u32) -> PathBuf {
+    directory.join(format!("{}_{:05}.png", prefix, frame))
+}
 
-        let up_pressed = world
-            .resources
-            .input
-            .keyboard
-            .is_key_pressed(KeyCode::ArrowUp);
-        let down_pressed = world
-            .resources
-            .input
-            .keyboard
-            .is_key_pressed(KeyCode::ArrowDown);
+#[cfg(not(target_arch = "wasm32"))]
+fn bookmark_key(path: &std::path::Path) -> String {
+    path.canonicalize()
+        .unwrap_or_else(|_| path.to_path_buf())
+        .display()
+        .to_string()
+}
 
-        let entry_count = self.asset_library.entries.len();
-        let mut new_index = None;
+#[cfg(not(target_arch = "wasm32"))]
+fn config_directory() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|directory| directory.join("gltf-viewer"))
+}
 
-        if down_pressed && !self.down_arrow_was_pressed {
-            new_index = Some(match self.asset_library.selected_index {
-                Some(index) => (index + 1) % entry_count,
-                None => 0,
-            });
+#[cfg(not(target_arch = "wasm32"))]
+fn strip_bone_namespace(bone: &str) -> &str {
+    bone.rsplit([':', '|']).next().unwrap_or(bone)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn format_bytes(bytes: u64) -> String {
+    const KILOBYTE: f64 = 1024.0;
+    const MEGABYTE: f64 = KILOBYTE * 1024.0;
+    const GIGABYTE: f64 = MEGABYTE * 1024.0;
+
+    let bytes_f64 = bytes as f64;
+    if bytes_f64 >= GIGABYTE {
+        format!("{:.2} GB", bytes_f64 / GIGABYTE)
+    } else if bytes_f64 >= MEGABYTE {
+        format!("{:.1} MB", bytes_f64 / MEGABYTE)
+    } else if bytes_f64 >= KILOBYTE {
+        format!("{:.1} KB", bytes_f64 / KILOBYTE)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_asset_statistics(path: &std::path::Path, asset_type: AssetType) -> AssetStatistics {
+    match asset_type {
+        AssetType::Model => GltfDocument::read_json(path)
+            .map(|(document, binary_offset)| AssetStatistics {
+                triangle_count: Some(document.triangle_count()),
+                texture_memory: Some(document.texture_memory(path, binary_offset)),
+            })
+            .unwrap_or_default(),
+        AssetType::Skybox => AssetStatistics {
+            triangle_count: None,
+            texture_memory: read_hdr_dimensions(path)
+                .map(|(width, height)| width as u64 * height as u64 * 8),
+        },
+        AssetType::Animation => AssetStatistics::default(),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_hdr_dimensions(path: &std::path::Path) -> Option<(u32, u32)> {
+    use std::io::BufRead;
+
+    let file = std::fs::File::open(path).ok()?;
+    let mut reader = std::io::BufReader::new(file);
+    let mut line = String::new();
+    let mut header_ended = false;
+
+    for _ in 0..64 {
+        line.clear();
+        if reader.read_line(&mut line).ok()? == 0 {
+            return None;
         }
 
-        if up_pressed && !self.up_arrow_was_pressed {
-            new_index = Some(match self.asset_library.selected_index {
-                Some(index) => {
-                    if index == 0 {
-                        entry_count - 1
-                    } else {
-                        index - 1
-                    }
-                }
-                None => entry_count - 1,
-            });
+        let trimmed = line.trim();
+        if !header_ended {
+            header_ended = trimmed.is_empty();
+            continue;
+        }
+
+        let parts: Vec<&str> = trimmed.split_whitespace().collect();
+        if parts.len() != 4 {
+            return None;
         }
+        let height = parts[1].parse().ok()?;
+        let width = parts[3].parse().ok()?;
+        return Some((width, height));
+    }
 
-        self.up_arrow_was_pressed = up_pressed;
-        self.down_arrow_was_pressed = down_pressed;
+    None
+}
 
-        if let Some(index) = new_index {
-            self.asset_library.selected_index = Some(index);
-            let entry = &self.asset_library.entries[index];
-            let path = entry.path.clone();
-            let asset_type = entry.asset_type;
+#[cfg(not(target_arch = "wasm32"))]
+struct GltfDocument {
+    json: serde_json::Value,
+    buffers: Vec<Option<Vec<u8>>>,
+    directory: PathBuf,
+}
 
-            match asset_type {
-                AssetType::Model => self.load_gltf_from_path(world, &path),
-                AssetType::Animation => self.load_fbx(world, &path),
-                AssetType::Skybox => self.load_hdr_skybox(world, &path),
-            }
-        }
+#[cfg(not(target_arch = "wasm32"))]
+impl GltfDocument {
+    fn read(path: &std::path::Path) -> Option<Self> {
+        let data = std::fs::read(path).ok()?;
+        Self::from_bytes(data, path)
     }
 
-    fn load_hdr_skybox(&mut self, world: &mut World, path: &std::path::Path) {
-        let name = path
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("Custom HDR")
-            .to_string();
+    /// Reads only the JSON chunk, leaving buffers unloaded. Also returns the file
+    /// offset of the GLB binary chunk so image headers can be read in place.
+    fn read_json(path: &std::path::Path) -> Option<(Self, Option<u64>)> {
+        use std::io::Read;
+
+        let directory = path.parent().map(|p| p.to_path_buf()).unwrap_or_default();
+        let mut file = std::fs::File::open(path).ok()?;
+        let mut header = [0u8; 20];
+        let (json_bytes, binary_offset) =
+            if file.read_exact(&mut header).is_ok() && header.starts_with(b"glTF") {
+                let json_length = u32::from_le_bytes(header[12..16].try_into().ok()?) as usize;
+                let mut json_bytes = vec![0; json_length];
+                file.read_exact(&mut json_bytes).ok()?;
+                (json_bytes, Some(20 + json_length as u64 + 8))
+            } else {
+                (std::fs::read(path).ok()?, None)
+            };
 
-        let already_loaded = self.custom_skyboxes.iter().position(|s| s.path == path);
+        let json = serde_json::from_slice(&json_bytes).ok()?;
+        Some((
+            Self {
+                json,
+                buffers: Vec::new(),
+                directory,
+            },
+            binary_offset,
+        ))
+    }
+
+    fn from_bytes(data: Vec<u8>, path: &std::path::Path) -> Option<Self> {
+        let directory = path.parent().map(|p| p.to_path_buf()).unwrap_or_default();
+
+        let (json_bytes, binary_chunk) = if data.starts_with(b"glTF") {
+            let json_length = u32::from_le_bytes(data.get(12..16)?.try_into().ok()?) as usize;
+            let json_start = 20;
+            let json_end = json_start + json_length;
+            let json_bytes = data.get(json_start..json_end)?.to_vec();
+
+            let binary_chunk = data.get(json_end..json_end + 8).and_then(|header| {
+                let length = u32::from_le_bytes(header[0..4].try_into().ok()?) as usize;
+                data.get(json_end + 8..json_end + 8 + length)
+                    .map(|chunk| chunk.to_vec())
+            });
 
-        if let Some(index) = already_loaded {
-            load_hdr_skybox_from_path(world, path.to_path_buf());
-            world.resources.graphics.atmosphere = Atmosphere::Hdr;
-            self.selected_custom_skybox = Some(index);
+            (json_bytes, binary_chunk)
         } else {
-            load_hdr_skybox_from_path(world, path.to_path_buf());
-            world.resources.graphics.atmosphere = Atmosphere::Hdr;
+            (data, None)
+        };
 
-            self.custom_skyboxes.push(CustomSkybox {
-                name,
-                path: path.to_path_buf(),
-            });
-            self.selected_custom_skybox = Some(self.custom_skyboxes.len() - 1);
-        }
+        let json: serde_json::Value = serde_json::from_slice(&json_bytes).ok()?;
+
+        let mut binary_chunk = binary_chunk;
+        let buffers = json
+            .get("buffers")
+            .and_then(|v| v.as_array())
+            .map(|buffers| {
+                buffers
+                    .iter()
+                    .map(|buffer| match buffer.get("uri").and_then(|v| v.as_str()) {
+                        Some(uri) if uri.starts_with("data:") => decode_data_uri(uri),
+                        Some(uri) => std::fs::read(directory.join(uri)).ok(),
+                        None => binary_chunk.take(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(Self {
+            json,
+            buffers,
+            directory,
+        })
     }
 
-    fn load_hdr_skybox_from_bytes(&mut self, world: &mut World, name: &str, data: &[u8]) {
-        let file_name = std::path::Path::new(name)
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("Custom HDR")
-            .to_string();
+    fn array(&self, name: &str) -> &[serde_json::Value] {
+        self.json
+            .get(name)
+            .and_then(|v| v.as_array())
+            .map(|v| v.as_slice())
+            .unwrap_or(&[])
+    }
 
-        let already_exists = self
-            .custom_skyboxes
-            .iter()
-            .position(|s| s.name == file_name);
+    fn buffer_view_bytes(&self, index: usize) -> Option<&[u8]> {
+        let buffer_view = self.array("bufferViews").get(index)?;
+        let buffer_index = buffer_view.get("buffer")?.as_u64()? as usize;
+        let offset = buffer_view
+            .get("byteOffset")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as usize;
+        let length = buffer_view.get("byteLength")?.as_u64()? as usize;
+        let buffer = self.buffers.get(buffer_index)?.as_ref()?;
+        buffer.get(offset..offset + length)
+    }
 
-        load_hdr_skybox(world, data.to_vec());
-        world.resources.graphics.atmosphere = Atmosphere::Hdr;
+    fn accessor_count(&self, index: u64) -> u64 {
+        self.array("accessors")
+            .get(index as usize)
+            .and_then(|accessor| accessor.get("count"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0)
+    }
 
-        if let Some(index) = already_exists {
-            self.selected_custom_skybox = Some(index);
-        } else {
-            self.custom_skyboxes.push(CustomSkybox {
-                name: file_name,
-                path: PathBuf::new(),
-            });
-            self.selected_custom_skybox = Some(self.custom_skyboxes.len() - 1);
-        }
+    fn triangle_count(&self) -> u64 {
+        self.array("meshes")
+            .iter()
+            .flat_map(|mesh| self.primitive_triangle_counts(mesh))
+            .sum()
     }
 
-    fn load_gltf_from_path(&mut self, world: &mut World, path: &std::path::Path) {
-        match nightshade::ecs::prefab::import_gltf_from_path(path) {
-            Ok(result) => {
-                self.clear_scene(world);
-                self.process_gltf_result(world, result);
-            }
-            Err(error) => {
-                tracing::error!("Failed to load glTF file: {}", error);
-            }
-        }
+    /// Triangle counts of each primitive of every named mesh.
+    fn mesh_primitive_triangles(&self) -> std::collections::HashMap<String, Vec<u64>> {
+        self.array("meshes")
+            .iter()
+            .filter_map(|mesh| {
+                let name = mesh.get("name")?.as_str()?;
+                Some((name.to_string(), self.primitive_triangle_counts(mesh)))
+            })
+            .collect()
     }
 
-    fn load_gltf_from_bytes(&mut self, world: &mut World, data: &[u8]) {
-        match nightshade::ecs::prefab::import_gltf_from_bytes(data) {
-            Ok(result) => {
-                self.process_gltf_result(world, result);
-            }
-            Err(error) => {
-                tracing::error!("Failed to load glTF from bytes: {}", error);
-            }
-        }
+    fn primitive_triangle_counts(&self, mesh: &serde_json::Value) -> Vec<u64> {
+        let primitives = mesh
+            .get("primitives")
+            .and_then(|v| v.as_array())
+            .map(|v| v.as_slice())
+            .unwrap_or(&[]);
+
+        primitives
+            .iter()
+            .map(|primitive| {
+                let count = match primitive.get("indices").and_then(|v| v.as_u64()) {
+                    Some(indices) => self.accessor_count(indices),
+                    None => primitive
+                        .get("attributes")
+                        .and_then(|attributes| attributes.get("POSITION"))
+                        .and_then(|v| v.as_u64())
+                        .map(|position| self.accessor_count(position))
+                        .unwrap_or(0),
+                };
+
+                let mode = primitive.get("mode").and_then(|v| v.as_u64()).unwrap_or(4);
+                match mode {
+                    4 => count / 3,
+                    5 | 6 => count.saturating_sub(2),
+                    _ => 0,
+                }
+            })
+            .collect()
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    fn load_fbx(&mut self, world: &mut World, path: &std::path::Path) {
-        match nightshade::ecs::prefab::import_fbx_from_path(path) {
-            Ok(result) => {
-                let has_meshes = !result.prefabs.is_empty() || !result.meshes.is_empty();
+    fn image_bytes(&self, index: usize) -> Option<std::borrow::Cow<'_, [u8]>> {
+        let image = self.array("images").get(index)?;
 
-                if has_meshes {
-                    self.clear_scene(world);
+        if let Some(buffer_view) = image.get("bufferView").and_then(|v| v.as_u64()) {
+            return self
+                .buffer_view_bytes(buffer_view as usize)
+                .map(std::borrow::Cow::Borrowed);
+        }
 
-                    for (name, (rgba_data, width, height)) in result.textures {
-                        world.queue_command(WorldCommand::LoadTexture {
-                            name,
-                            rgba_data,
-                            width,
-                            height,
-                        });
-                    }
+        let uri = image.get("uri")?.as_str()?;
+        if uri.starts_with("data:") {
+            return decode_data_uri(uri).map(std::borrow::Cow::Owned);
+        }
+        std::fs::read(self.directory.join(uri))
+            .ok()
+            .map(std::borrow::Cow::Owned)
+    }
 
-                    for (name, mesh) in result.meshes {
-                        mesh_cache_insert(&mut world.resources.mesh_cache, name, mesh);
+    /// Element data of every mesh accessor and the bytes of every image, sorted so that
+    /// files which only differ in buffer layout, naming or embedding compare equal.
+    fn content_blobs(&self) -> Vec<Vec<u8>> {
+        let mut blobs = Vec::new();
+
+        for mesh in self.array("meshes") {
+            let primitives = mesh
+                .get("primitives")
+                .and_then(|v| v.as_array())
+                .map(|v| v.as_slice())
+                .unwrap_or(&[]);
+
+            for primitive in primitives {
+                let attributes = primitive
+                    .get("attributes")
+                    .and_then(|v| v.as_object())
+                    .into_iter()
+                    .flat_map(|attributes| attributes.values());
+                let indices = primitive.get("indices").into_iter();
+
+                for accessor_index in attributes.chain(indices).filter_map(|v| v.as_u64()) {
+                    if let Some(blob) = self.accessor_blob(accessor_index as usize) {
+                        blobs.push(blob);
                     }
+                }
+            }
+        }
 
-                    for prefab in result.prefabs {
-                        let entity = nightshade::ecs::prefab::spawn_prefab_with_skins(
-                            world,
-                            &prefab,
-                            &result.animations,
-                            &result.skins,
-                            nalgebra_glm::vec3(0.0, 0.0, 0.0),
-                        );
-                        self.model_entities.push(entity);
-                    }
+        for index in 0..self.array("images").len() {
+            if let Some(bytes) = self.image_bytes(index) {
+                blobs.push(bytes.into_owned());
+            }
+        }
 
-                    self.loaded = true;
-                    self.center_and_fit_model(world);
-                } else if !result.animations.is_empty() {
-                    let Some(entity) = self.model_entities.first().copied() else {
-                        tracing::warn!(
-                            "No model loaded - load a model first before adding FBX animations"
-                        );
-                        return;
-                    };
+        blobs.sort_unstable();
+        blobs
+    }
 
-                    if !world.entity_has_animation_player(entity) {
-                        tracing::warn!("Model does not have an AnimationPlayer component");
-                        return;
-                    }
+    /// The accessor's `count` elements read through its type and the view's stride, so
+    /// the same data packed at a different offset or stride gives the same blob. Sparse
+    /// substitutions are not applied.
+    fn accessor_blob(&self, index: usize) -> Option<Vec<u8>> {
+        let accessor = self.array("accessors").get(index)?;
+        let view_index = accessor.get("bufferView")?.as_u64()? as usize;
+        let bytes = self.buffer_view_bytes(view_index)?;
+        let count = accessor.get("count")?.as_u64()? as usize;
+        let component_type = accessor.get("componentType")?.as_u64()?;
+        let element_type = accessor.get("type")?.as_str()?;
+
+        let component_size = match component_type {
+            5120 | 5121 => 1,
+            5122 | 5123 => 2,
+            5125 | 5126 => 4,
+            _ => return None,
+        };
+        let component_count = match element_type {
+            "SCALAR" => 1,
+            "VEC2" => 2,
+            "VEC3" => 3,
+            "VEC4" | "MAT2" => 4,
+            "MAT3" => 9,
+            "MAT4" => 16,
+            _ => return None,
+        };
+        let element_size = component_size * component_count;
+        let stride = self.array("bufferViews")[view_index]
+            .get("byteStride")
+            .and_then(|v| v.as_u64())
+            .map_or(element_size, |stride| stride as usize);
+        let offset = accessor
+            .get("byteOffset")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as usize;
+
+        let mut blob = format!("{count}:{component_type}:{element_type}:").into_bytes();
+        for element in 0..count {
+            let start = offset + element * stride;
+            blob.extend_from_slice(bytes.get(start..start + element_size)?);
+        }
+        Some(blob)
+    }
 
-                    if let Some(player) = world.get_animation_player_mut(entity) {
-                        let count = result.animations.len();
-                        player.add_clips(result.animations);
-                        tracing::info!("Added {} animation(s) from FBX", count);
+    fn texture_memory(&self, path: &std::path::Path, binary_offset: Option<u64>) -> u64 {
+        (0..self.array("images").len())
+            .filter_map(|index| self.read_image_dimensions(index, path, binary_offset))
+            .map(|(width, height)| width as u64 * height as u64 * 4 * 4 / 3)
+            .sum()
+    }
 
-                        if player.current_clip.is_none() && !player.clips.is_empty() {
-                            player.play(0);
-                        }
+    /// Finds an image's dimensions, reading it from disk only as far as its header goes.
+    fn read_image_dimensions(
+        &self,
+        index: usize,
+        path: &std::path::Path,
+        binary_offset: Option<u64>,
+    ) -> Option<(u32, u32)> {
+        use std::io::{Read, Seek};
+
+        let image = self.array("images").get(index)?;
+        let (file_path, offset, length) = match image.get("bufferView").and_then(|v| v.as_u64()) {
+            Some(view_index) => {
+                let view = self.array("bufferViews").get(view_index as usize)?;
+                let buffer = self
+                    .array("buffers")
+                    .get(view.get("buffer")?.as_u64()? as usize)?;
+                let view_offset = view.get("byteOffset").and_then(|v| v.as_u64()).unwrap_or(0);
+                let length = view.get("byteLength")?.as_u64()?;
+                match buffer.get("uri").and_then(|v| v.as_str()) {
+                    Some(uri) if uri.starts_with("data:") => {
+                        let buffer = decode_data_uri(uri)?;
+                        let start = view_offset as usize;
+                        return image_dimensions(buffer.get(start..start + length as usize)?);
                     }
-                } else {
-                    tracing::warn!("FBX file contains no meshes or animations");
+                    Some(uri) => (self.directory.join(uri), view_offset, Some(length)),
+                    None => (
+                        path.to_path_buf(),
+                        binary_offset? + view_offset,
+                        Some(length),
+                    ),
                 }
             }
-            Err(error) => {
-                tracing::error!("Failed to load FBX file: {}", error);
+            None => {
+                let uri = image.get("uri")?.as_str()?;
+                if uri.starts_with("data:") {
+                    return image_dimensions(decode_data_uri(uri)?.as_slice());
+                }
+                (self.directory.join(uri), 0, None)
             }
+        };
+
+        let mut file = std::io::BufReader::new(std::fs::File::open(file_path).ok()?);
+        file.seek(std::io::SeekFrom::Start(offset)).ok()?;
+        match length {
+            Some(length) => image_dimensions(file.take(length)),
+            None => image_dimensions(file),
         }
     }
+}
 
-    fn process_gltf_result(
-        &mut self,
-        world: &mut World,
-        result: nightshade::ecs::prefab::GltfLoadResult,
-    ) {
-        for (name, (rgba_data, width, height)) in result.textures {
-            world.queue_command(WorldCommand::LoadTexture {
-                name,
-                rgba_data,
-                width,
-                height,
+/// Local rest transforms of every named node in the imported skeleton.
+#[cfg(not(target_arch = "wasm32"))]
+fn prefab_rest_pose(
+    prefabs: &[nightshade::ecs::prefab::Prefab],
+) -> std::collections::HashMap<String, NodePose> {
+    let mut rest_pose = std::collections::HashMap::new();
+    let mut stack: Vec<&nightshade::ecs::prefab::PrefabNode> = prefabs
+        .iter()
+        .flat_map(|prefab| prefab.root_nodes.iter())
+        .collect();
+    while let Some(node) = stack.pop() {
+        if let Some(name) = &node.components.name {
+            let transform = &node.local_transform;
+            rest_pose.entry(name.0.clone()).or_insert(NodePose {
+                translation: transform.translation,
+                rotation: transform.rotation,
+                scale: transform.scale,
             });
         }
+        stack.extend(node.children.iter());
+    }
+    rest_pose
+}
 
-        for (name, mesh) in result.meshes {
-            mesh_cache_insert(&mut world.resources.mesh_cache, name, mesh);
-        }
+/// 64-bit FNV-1a, which unlike `DefaultHasher` is stable across Rust releases and
+/// can name files on disk.
+#[cfg(not(target_arch = "wasm32"))]
+fn hash_bytes(bytes: &[u8]) -> u64 {
+    extend_hash(0xcbf2_9ce4_8422_2325, bytes)
+}
 
-        for prefab in result.prefabs {
-            let entity = nightshade::ecs::prefab::spawn_prefab_with_skins(
-                world,
-                &prefab,
-                &result.animations,
-                &result.skins,
-                nalgebra_glm::vec3(0.0, 0.0, 0.0),
-            );
-            self.model_entities.push(entity);
+#[cfg(not(target_arch = "wasm32"))]
+fn extend_hash(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn hash_file(path: &std::path::Path) -> std::io::Result<u64> {
+    use std::io::Read;
+
+    let mut file = std::fs::File::open(path)?;
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut hash = hash_bytes(&[]);
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(hash);
         }
+        hash = extend_hash(hash, &buffer[..read]);
+    }
+}
 
-        self.loaded = true;
-        self.center_and_fit_model(world);
+#[cfg(not(target_arch = "wasm32"))]
+fn files_equal(a: &std::path::Path, b: &std::path::Path) -> bool {
+    use std::io::Read;
+
+    let (Ok(mut a), Ok(mut b)) = (std::fs::File::open(a), std::fs::File::open(b)) else {
+        return false;
+    };
+    let (mut buffer_a, mut buffer_b) = (vec![0u8; 64 * 1024], vec![0u8; 64 * 1024]);
+    loop {
+        let read = match a.read(&mut buffer_a) {
+            Ok(read) => read,
+            Err(_) => return false,
+        };
+        if read == 0 {
+            return matches!(b.read(&mut buffer_b), Ok(0));
+        }
+        if b.read_exact(&mut buffer_b[..read]).is_err() || buffer_a[..read] != buffer_b[..read] {
+            return false;
+        }
     }
+}
 
-    fn clear_scene(&mut self, world: &mut World) {
-        let entities: Vec<Entity> = self.model_entities.drain(..).collect();
-        for entity in entities {
-            despawn_recursive_immediate(world, entity);
+/// Splits `members` into classes whose items are equal to the class's first item.
+#[cfg(not(target_arch = "wasm32"))]
+fn partition_equal<T>(members: Vec<T>, equal: impl Fn(&T, &T) -> bool) -> Vec<Vec<T>> {
+    let mut classes: Vec<Vec<T>> = Vec::new();
+    for member in members {
+        match classes.iter_mut().find(|class| equal(&class[0], &member)) {
+            Some(class) => class.push(member),
+            None => classes.push(vec![member]),
         }
-        self.loaded = false;
     }
+    classes
+}
 
-    fn center_and_fit_model(&mut self, world: &mut World) {
-        let mut min = Vec3::new(f32::MAX, f32::MAX, f32::MAX);
-        let mut max = Vec3::new(f32::MIN, f32::MIN, f32::MIN);
-        let mut has_bounds = false;
+/// Decodes a base64 `data:` URI, as used for buffers and images embedded in glTF JSON.
+#[cfg(not(target_arch = "wasm32"))]
+fn decode_data_uri(uri: &str) -> Option<Vec<u8>> {
+    let (header, data) = uri.strip_prefix("data:")?.split_once(',')?;
+    if !header.ends_with(";base64") {
+        return None;
+    }
 
-        for entity in &self.model_entities {
-            calculate_bounds_recursive(
-                world,
-                *entity,
-                &Mat4::identity(),
-                &mut min,
-                &mut max,
-                &mut has_bounds,
-            );
+    let mut bytes = Vec::with_capacity(data.len() / 4 * 3);
+    let (mut accumulator, mut bits) = (0u32, 0);
+    for character in data.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        let value = match character {
+            b'A'..=b'Z' => character - b'A',
+            b'a'..=b'z' => character - b'a' + 26,
+            b'0'..=b'9' => character - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' => break,
+            _ => return None,
+        };
+        accumulator = (accumulator << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((accumulator >> bits) as u8);
+            accumulator &= (1 << bits) - 1;
         }
+    }
+    Some(bytes)
+}
 
-        if !has_bounds {
-            return;
-        }
+/// Groups files that are byte-for-byte identical, and models that share the same
+/// mesh and image data. Hashes only narrow the candidates; every match is confirmed
+/// by comparing sizes and bytes.
+#[cfg(not(target_arch = "wasm32"))]
+fn find_duplicate_groups(files: Vec<(PathBuf, u64, AssetType)>) -> Vec<DuplicateGroup> {
+    let mut size_groups: std::collections::HashMap<u64, Vec<usize>> =
+        std::collections::HashMap::new();
+    for (index, (_, file_size, _)) in files.iter().enumerate() {
+        size_groups.entry(*file_size).or_default().push(index);
+    }
 
-        let center = (min + max) * 0.5;
-        let size = max - min;
-        let max_dimension = size.x.max(size.y).max(size.z);
+    let mut duplicate_groups = Vec::new();
+    let mut exact_class: Vec<usize> = (0..files.len()).collect();
 
-        if max_dimension <= 0.0 {
-            return;
+    for indices in size_groups.into_values() {
+        if indices.len() < 2 {
+            continue;
         }
-
-        let scale = 2.0 / max_dimension;
-
-        for entity in &self.model_entities {
-            if let Some(transform) = world.get_local_transform_mut(*entity) {
-                transform.translation = (transform.translation - center) * scale;
-                transform.scale *= scale;
+        let mut hash_groups: std::collections::HashMap<u64, Vec<usize>> =
+            std::collections::HashMap::new();
+        for index in indices {
+            match hash_file(&files[index].0) {
+                Ok(hash) => hash_groups.entry(hash).or_default().push(index),
+                Err(error) => {
+                    tracing::warn!("Failed to read {}: {}", files[index].0.display(), error)
+                }
             }
-            world.mark_local_transform_dirty(*entity);
         }
 
-        self.reset_camera(world);
+        for candidates in hash_groups.into_values() {
+            if candidates.len() < 2 {
+                continue;
+            }
+            for class in partition_equal(candidates, |a, b| files_equal(&files[*a].0, &files[*b].0))
+            {
+                if class.len() < 2 {
+                    continue;
+                }
+                for index in &class {
+                    exact_class[*index] = class[0];
+                }
+                duplicate_groups.push(DuplicateGroup {
+                    kind: DuplicateKind::Exact,
+                    file_size: files[class[0]].1,
+                    paths: class.iter().map(|index| files[*index].0.clone()).collect(),
+                });
+            }
+        }
     }
 
-    fn reset_camera(&mut self, world: &mut World) {
-        if let Some(camera_entity) = self.camera_entity
-            && let Some(pan_orbit) = world.get_pan_orbit_camera_mut(camera_entity)
-        {
-            pan_orbit.target_focus = Vec3::new(0.0, 0.0, 0.0);
-            pan_orbit.target_radius = 3.0;
-            pan_orbit.target_yaw = 0.0;
-            pan_orbit.target_pitch = 0.3;
+    let mut fingerprint_groups: std::collections::HashMap<u64, Vec<usize>> =
+        std::collections::HashMap::new();
+    for (index, (path, _, asset_type)) in files.iter().enumerate() {
+        if *asset_type != AssetType::Model || exact_class[index] != index {
+            continue;
+        }
+        let Some(blobs) = GltfDocument::read(path).map(|document| document.content_blobs()) else {
+            continue;
+        };
+        if blobs.is_empty() {
+            continue;
         }
+        let fingerprint = blobs.iter().fold(hash_bytes(&[]), |hash, blob| {
+            extend_hash(hash, &hash_bytes(blob).to_le_bytes())
+        });
+        fingerprint_groups
+            .entry(fingerprint)
+            .or_default()
+            .push(index);
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    fn scan_directory(&mut self, path: &std::path::Path) {
-        self.asset_library.entries.clear();
-
-        for entry in walkdir::WalkDir::new(path)
-            .follow_links(true)
+    for candidates in fingerprint_groups.into_values() {
+        if candidates.len() < 2 {
+            continue;
+        }
+        let documents: Vec<(usize, Vec<Vec<u8>>)> = candidates
             .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            let entry_path = entry.path();
-            if !entry_path.is_file() {
+            .filter_map(|index| {
+                let document = GltfDocument::read(&files[index].0)?;
+                Some((index, document.content_blobs()))
+            })
+            .collect();
+        for class in partition_equal(documents, |a, b| a.1 == b.1) {
+            if class.len() < 2 {
                 continue;
             }
+            let exact_class = &exact_class;
+            let members: Vec<usize> = class
+                .iter()
+                .flat_map(|(representative, _)| {
+                    (0..files.len()).filter(move |index| exact_class[*index] == *representative)
+                })
+                .collect();
+            duplicate_groups.push(DuplicateGroup {
+                kind: DuplicateKind::SameContent,
+                file_size: members
+                    .iter()
+                    .map(|index| files[*index].1)
+                    .max()
+                    .unwrap_or(0),
+                paths: members
+                    .iter()
+                    .map(|index| files[*index].0.clone())
+                    .collect(),
+            });
+        }
+    }
 
-            let extension = match entry_path.extension().and_then(|e| e.to_str()) {
-                Some(ext) => ext.to_lowercase(),
-                None => continue,
-            };
+    duplicate_groups.sort_by(|a, b| {
+        let a_wasted = a.file_size * (a.paths.len() as u64 - 1);
+        let b_wasted = b.file_size * (b.paths.len() as u64 - 1);
+        b_wasted.cmp(&a_wasted)
+    });
 
-            let asset_type = match AssetType::from_extension(&extension) {
-                Some(t) => t,
-                None => continue,
-            };
+    for group in &mut duplicate_groups {
+        group.paths.sort();
+    }
 
-            let file_stem = entry_path
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("Unknown")
-                .to_string();
+    duplicate_groups
+}
 
-            let (base_name, tags, categories) = self
-                .parse_polyhaven_info(entry_path)
-                .unwrap_or_else(|| (file_stem, Vec::new(), Vec::new()));
+#[cfg(not(target_arch = "wasm32"))]
+fn image_dimensions(mut reader: impl std::io::Read) -> Option<(u32, u32)> {
+    let mut header = [0u8; 24];
+    reader.read_exact(&mut header[..2]).ok()?;
+    if header.starts_with(&[0xFF, 0xD8]) {
+        return jpeg_dimensions(reader);
+    }
 
-            let display_name = format!("{}.{}", base_name, extension);
+    reader.read_exact(&mut header[2..]).ok()?;
+    if header.starts_with(b"\x89PNG\r\n\x1a\n") {
+        let width = u32::from_be_bytes(header[16..20].try_into().ok()?);
+        let height = u32::from_be_bytes(header[20..24].try_into().ok()?);
+        return Some((width, height));
+    }
 
-            self.asset_library.entries.push(AssetEntry {
-                path: entry_path.to_path_buf(),
-                name: display_name,
-                extension: extension.clone(),
-                asset_type,
-                tags,
-                categories,
-            });
+    None
+}
+
+/// Walks the JPEG segments after the start-of-image marker, skipping each by its length
+/// field so metadata of any size before the frame header is never held in memory.
+#[cfg(not(target_arch = "wasm32"))]
+fn jpeg_dimensions(mut reader: impl std::io::Read) -> Option<(u32, u32)> {
+    let mut byte = [0u8; 1];
+    loop {
+        reader.read_exact(&mut byte).ok()?;
+        if byte[0] != 0xFF {
+            continue;
+        }
+
+        while byte[0] == 0xFF {
+            reader.read_exact(&mut byte).ok()?;
+        }
+        let marker = byte[0];
+        if marker == 0x00 || marker == 0x01 || (0xD0..=0xD7).contains(&marker) {
+            continue;
+        }
+        if matches!(marker, 0xD9 | 0xDA) {
+            return None;
         }
 
-        if self.asset_library.sort_ascending {
-            self.asset_library
-                .entries
-                .sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-        } else {
-            self.asset_library
-                .entries
-                .sort_by(|a, b| b.name.to_lowercase().cmp(&a.name.to_lowercase()));
+        let mut length = [0u8; 2];
+        reader.read_exact(&mut length).ok()?;
+        let length = u16::from_be_bytes(length) as u64;
+        let is_start_of_frame =
+            (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC);
+
+        if is_start_of_frame {
+            let mut frame = [0u8; 5];
+            reader.read_exact(&mut frame).ok()?;
+            let height = u16::from_be_bytes([frame[1], frame[2]]) as u32;
+            let width = u16::from_be_bytes([frame[3], frame[4]]) as u32;
+            return Some((width, height));
         }
+
+        std::io::copy(
+            &mut std::io::Read::take(&mut reader, length.saturating_sub(2)),
+            &mut std::io::sink(),
+        )
+        .ok()?;
     }
+}
 
-    #[cfg(not(target_arch = "wasm32"))]
-    fn parse_polyhaven_info(
-        &self,
-        asset_path: &std::path::Path,
-    ) -> Option<(String, Vec<String>, Vec<String>)> {
-        let parent = asset_path.parent()?;
-        let info_path = parent.join("info.json");
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod viewer_tests {
+    use super::*;
 
-        if !info_path.exists() {
-            return None;
+    const PNG_HEADER_BASE64: &str = "iVBORw0KGgoAAAANSUhEUgAAAAIAAAAD";
+
+    fn assert_close(actual: Vec3, expected: Vec3) {
+        assert!(
+            nalgebra_glm::distance(&actual, &expected) < 1e-4,
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    fn png_header(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        bytes.extend_from_slice(&width.to_be_bytes());
+        bytes.extend_from_slice(&height.to_be_bytes());
+        bytes
+    }
+
+    fn jpeg_segment(marker: u8, payload: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0xFF, marker];
+        bytes.extend_from_slice(&(payload.len() as u16 + 2).to_be_bytes());
+        bytes.extend_from_slice(payload);
+        bytes
+    }
+
+    #[test]
+    fn png_dimensions_come_from_the_header() {
+        assert_eq!(
+            image_dimensions(png_header(640, 480).as_slice()),
+            Some((640, 480))
+        );
+        assert_eq!(image_dimensions(&png_header(640, 480)[..20]), None);
+    }
+
+    #[test]
+    fn jpeg_frame_after_large_metadata_is_found() {
+        let mut jpeg = vec![0xFF, 0xD8];
+        jpeg.extend(jpeg_segment(0xE0, b"JFIF\0"));
+        for _ in 0..3 {
+            jpeg.extend(jpeg_segment(0xE1, &[0xFF; 65533]));
         }
+        jpeg.extend(jpeg_segment(0xC2, &[8, 0x04, 0x38, 0x07, 0x80, 3]));
+        assert!(jpeg.len() > 64 * 1024);
+        assert_eq!(image_dimensions(jpeg.as_slice()), Some((1920, 1080)));
+    }
 
-        let content = std::fs::read_to_string(&info_path).ok()?;
-        let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+    #[test]
+    fn jpeg_without_frame_before_scan_has_no_dimensions() {
+        let mut jpeg = vec![0xFF, 0xD8];
+        jpeg.extend(jpeg_segment(0xC4, &[0; 4]));
+        jpeg.extend(jpeg_segment(0xDA, &[0; 4]));
+        jpeg.extend(jpeg_segment(0xC0, &[8, 0, 1, 0, 1, 3]));
+        assert_eq!(image_dimensions(jpeg.as_slice()), None);
+        assert_eq!(image_dimensions(&jpeg[..6]), None);
+    }
 
-        let name = json
-            .get("name")
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string())?;
+    #[test]
+    fn texture_memory_reads_embedded_images() {
+        let json = format!(
+            r#"{{
+                "buffers": [{{ "byteLength": 32, "uri": "data:application/octet-stream;base64,{PNG_HEADER_BASE64}AAAAAAAAAAA=" }}],
+                "bufferViews": [{{ "buffer": 0, "byteLength": 24 }}],
+                "images": [
+                    {{ "uri": "data:image/png;base64,{PNG_HEADER_BASE64}" }},
+                    {{ "bufferView": 0, "mimeType": "image/png" }}
+                ]
+            }}"#
+        );
+        let path = std::path::Path::new("scene.gltf");
+        let document = GltfDocument::from_bytes(json.into_bytes(), path).unwrap();
+        assert_eq!(document.texture_memory(path, None), 2 * 2 * 3 * 4 * 4 / 3);
+    }
 
-        let tags = json
-            .get("tags")
-            .and_then(|v| v.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                    .collect()
-            })
-            .unwrap_or_default();
+    #[test]
+    fn key_conflicts_respect_context_and_modifiers() {
+        let bindings = KeyBindings::default();
+        let key_a = KeyBinding {
+            key: KeyCode::KeyA,
+            ctrl: false,
+        };
+        assert!(bindings.conflicts(KeyAction::FrameAll, &key_a).is_empty());
+        assert!(bindings.conflicts(KeyAction::FlyLeft, &key_a).is_empty());
+        assert_eq!(
+            bindings.conflicts(KeyAction::PlayPause, &key_a),
+            vec![KeyAction::FrameAll, KeyAction::FlyLeft]
+        );
 
-        let categories = json
-            .get("categories")
-            .and_then(|v| v.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                    .collect()
-            })
-            .unwrap_or_default();
+        let numpad_one = |ctrl| KeyBinding {
+            key: KeyCode::Numpad1,
+            ctrl,
+        };
+        assert!(
+            bindings
+                .conflicts(KeyAction::ViewFront, &numpad_one(false))
+                .is_empty()
+        );
+        assert_eq!(
+            bindings.conflicts(KeyAction::ViewFront, &numpad_one(true)),
+            vec![KeyAction::ViewBack]
+        );
+    }
 
-        Some((name, tags, categories))
+    #[test]
+    fn key_presses_fire_once_while_held() {
+        let mut bindings = KeyBindings::default();
+        bindings.update(|key| key == KeyCode::Space);
+        assert!(bindings.pressed(KeyAction::PlayPause));
+        assert!(bindings.held(KeyAction::PlayPause));
+
+        bindings.update(|key| key == KeyCode::Space);
+        assert!(!bindings.pressed(KeyAction::PlayPause));
+        assert!(bindings.held(KeyAction::PlayPause));
+
+        bindings.update(|key| matches!(key, KeyCode::Numpad1 | KeyCode::ControlLeft));
+        assert!(!bindings.held(KeyAction::PlayPause));
+        assert!(bindings.pressed(KeyAction::ViewBack));
+        assert!(!bindings.held(KeyAction::ViewFront));
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    fn asset_library_ui(&mut self, world: &mut World, ui_context: &egui::Context) {
-        if !self.asset_library.show_window {
-            return;
-        }
+    #[test]
+    fn camera_pose_interpolation_takes_the_short_way_around() {
+        let from = CameraPose {
+            focus: Vec3::zeros(),
+            radius: 2.0,
+            yaw: 170f32.to_radians(),
+            pitch: 0.0,
+        };
+        let to = CameraPose {
+            focus: Vec3::new(2.0, 4.0, -2.0),
+            radius: 4.0,
+            yaw: -170f32.to_radians(),
+            pitch: 0.5,
+        };
 
-        let mut asset_to_load: Option<(PathBuf, AssetType)> = None;
-        let mut directory_to_scan: Option<PathBuf> = None;
-        let mut should_close = false;
+        let halfway = from.interpolate(&to, 0.5);
+        assert_close(halfway.focus, Vec3::new(1.0, 2.0, -1.0));
+        assert!((halfway.radius - 3.0).abs() < 1e-5);
+        assert!((halfway.yaw - std::f32::consts::PI).abs() < 1e-4);
+        assert!((halfway.pitch - 0.25).abs() < 1e-5);
 
-        egui::Window::new("Asset Library")
-            .default_width(400.0)
-            .default_height(500.0)
-            .show(ui_context, |ui| {
-                ui.horizontal(|ui| {
-                    if ui.button("Scan Directory").clicked()
-                        && let Some(path) = rfd::FileDialog::new().pick_folder()
-                    {
-                        directory_to_scan = Some(path);
-                    }
+        let end = from.interpolate(&to, 1.0);
+        assert!((end.yaw.sin() - to.yaw.sin()).abs() < 1e-4);
+        assert!((end.yaw.cos() - to.yaw.cos()).abs() < 1e-4);
+        assert!((from.interpolate(&to, 0.0).yaw - from.yaw).abs() < 1e-6);
+    }
 
-                    if ui.button("Clear").clicked() {
-                        self.asset_library.entries.clear();
-                    }
+    #[test]
+    fn view_presets_face_opposite_pairs() {
+        let (front_yaw, _) = ViewPreset::Front.yaw_pitch();
+        let (back_yaw, _) = ViewPreset::Back.yaw_pitch();
+        let (left_yaw, _) = ViewPreset::Left.yaw_pitch();
+        let (right_yaw, _) = ViewPreset::Right.yaw_pitch();
+        assert!(((back_yaw - front_yaw).abs() - std::f32::consts::PI).abs() < 1e-6);
+        assert!(((right_yaw - left_yaw).abs() - std::f32::consts::PI).abs() < 1e-6);
+
+        let (_, top_pitch) = ViewPreset::Top.yaw_pitch();
+        let (_, bottom_pitch) = ViewPreset::Bottom.yaw_pitch();
+        assert_eq!(top_pitch, -bottom_pitch);
+        assert!(top_pitch > 1.5 && top_pitch < std::f32::consts::FRAC_PI_2);
+
+        let (isometric_yaw, isometric_pitch) = ViewPreset::Isometric.yaw_pitch();
+        assert!((isometric_yaw - 45f32.to_radians()).abs() < 1e-6);
+        assert!((isometric_pitch.to_degrees() - 35.264).abs() < 1e-3);
+
+        for (index, preset) in ViewPreset::ALL.iter().enumerate() {
+            for other in &ViewPreset::ALL[index + 1..] {
+                assert_ne!(
+                    preset.yaw_pitch(),
+                    other.yaw_pitch(),
+                    "{preset:?} {other:?}"
+                );
+            }
+        }
+    }
 
-                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                        if ui.button("X").clicked() {
-                            should_close = true;
-                        }
-                    });
-                });
+    #[test]
+    fn other_roots_follow_an_edit_about_the_first_root() {
+        let identity = (Vec3::zeros(), Quat::identity(), Vec3::new(1.0, 1.0, 1.0));
+        let quarter_turn =
+            nalgebra_glm::quat_angle_axis(std::f32::consts::FRAC_PI_2, &Vec3::new(0.0, 1.0, 0.0));
+        let after = (
+            Vec3::new(1.0, 0.0, 0.0),
+            quarter_turn,
+            Vec3::new(2.0, 2.0, 2.0),
+        );
+        let root = (
+            Vec3::new(1.0, 0.0, 0.0),
+            Quat::identity(),
+            Vec3::new(1.0, 1.0, 1.0),
+        );
 
-                ui.separator();
+        let (translation, rotation, scale) = follow_root_edit(identity, after, root);
+        assert_close(translation, Vec3::new(1.0, 0.0, -2.0));
+        assert!(nalgebra_glm::quat_dot(&rotation, &quarter_turn).abs() > 1.0 - 1e-5);
+        assert_close(scale, Vec3::new(2.0, 2.0, 2.0));
 
-                ui.horizontal(|ui| {
-                    ui.label("Search:");
-                    ui.text_edit_singleline(&mut self.asset_library.search_query);
-                });
+        let (translation, _, scale) = follow_root_edit(identity, identity, root);
+        assert_close(translation, root.0);
+        assert_close(scale, root.2);
+    }
 
-                ui.horizontal(|ui| {
-                    ui.label("Filter:");
-                    egui::ComboBox::from_id_salt("asset_type_filter")
-                        .selected_text(
-                            self.asset_library
-                                .filter_type
-                                .map(|t| t.name())
-                                .unwrap_or("All"),
-                        )
-                        .show_ui(ui, |ui| {
-                            if ui
-                                .selectable_label(self.asset_library.filter_type.is_none(), "All")
-                                .clicked()
-                            {
-                                self.asset_library.filter_type = None;
-                            }
-                            for asset_type in
-                                [AssetType::Model, AssetType::Animation, AssetType::Skybox]
-                            {
-                                if ui
-                                    .selectable_label(
-                                        self.asset_library.filter_type == Some(asset_type),
-                                        asset_type.name(),
-                                    )
-                                    .clicked()
-                                {
-                                    self.asset_library.filter_type = Some(asset_type);
-                                }
-                            }
-                        });
+    #[test]
+    fn zero_scale_pivot_leaves_other_roots_unscaled() {
+        let flat = (Vec3::zeros(), Quat::identity(), Vec3::new(0.0, 1.0, 1.0));
+        let root = (
+            Vec3::new(0.0, 2.0, 0.0),
+            Quat::identity(),
+            Vec3::new(3.0, 3.0, 3.0),
+        );
+        let (translation, _, scale) = follow_root_edit(flat, flat, root);
+        assert_close(translation, root.0);
+        assert_close(scale, root.2);
+    }
 
-                    if ui
-                        .button(if self.asset_library.sort_ascending {
-                            "A-Z"
-                        } else {
-                            "Z-A"
-                        })
-                        .clicked()
-                    {
-                        self.asset_library.sort_ascending = !self.asset_library.sort_ascending;
-                        if self.asset_library.sort_ascending {
-                            self.asset_library
-                                .entries
-                                .sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-                        } else {
-                            self.asset_library
-                                .entries
-                                .sort_by(|a, b| b.name.to_lowercase().cmp(&a.name.to_lowercase()));
-                        }
-                    }
-                });
+    #[test]
+    fn primitive_starts_match_whole_and_split_meshes() {
+        let gltf_meshes = std::collections::HashMap::from([("Body".to_string(), vec![4, 10, 2])]);
 
-                ui.separator();
+        assert_eq!(
+            primitive_starts(&gltf_meshes, "Body", 16),
+            Some(vec![(0, 0), (4, 1), (14, 2)])
+        );
+        assert_eq!(
+            primitive_starts(&gltf_meshes, "Body_1", 10),
+            Some(vec![(0, 1)])
+        );
+        assert_eq!(primitive_starts(&gltf_meshes, "Body", 15), None);
+        assert_eq!(primitive_starts(&gltf_meshes, "Body_1", 4), None);
+        assert_eq!(primitive_starts(&gltf_meshes, "Body_3", 2), None);
+        assert_eq!(primitive_starts(&gltf_meshes, "Head", 4), None);
+    }
 
-                ui.horizontal(|ui| {
-                    ui.label("Show:");
-                    ui.checkbox(&mut self.asset_library.show_glb, "glb");
-                    ui.checkbox(&mut self.asset_library.show_gltf, "gltf");
-                    ui.checkbox(&mut self.asset_library.show_fbx, "fbx");
-                    ui.checkbox(&mut self.asset_library.show_hdr, "hdr");
-                });
+    fn scratch_directory(name: &str) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("gltf-viewer-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+        directory
+    }
 
-                ui.separator();
+    fn triangle_glb(mesh_name: &str, padding: usize) -> Vec<u8> {
+        let mut writer = GlbWriter::default();
+        if padding > 0 {
+            writer.buffer_view(&vec![0; padding], None);
+        }
+        let positions = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+        let position =
+            writer.float_accessor(&positions, "VEC3", 3, Some(GlbWriter::ARRAY_BUFFER), true);
+        writer.finish(serde_json::json!({
+            "asset": { "version": "2.0" },
+            "meshes": [{
+                "name": mesh_name,
+                "primitives": [{ "attributes": { "POSITION": position } }],
+            }],
+        }))
+    }
 
-                let search_query_lower = self.asset_library.search_query.to_lowercase();
+    #[test]
+    fn glb_writer_produces_aligned_chunks() {
+        let mut writer = GlbWriter::default();
+        writer.buffer_view(&[1, 2, 3], None);
+        let positions = [0.0, -1.0, 2.0, 3.0, 1.0, -2.0];
+        let accessor =
+            writer.float_accessor(&positions, "VEC3", 3, Some(GlbWriter::ARRAY_BUFFER), true);
+        let glb = writer.finish(serde_json::json!({ "asset": { "version": "2.0" } }));
+
+        assert_eq!(&glb[..4], b"glTF");
+        assert_eq!(u32::from_le_bytes(glb[4..8].try_into().unwrap()), 2);
+        assert_eq!(
+            u32::from_le_bytes(glb[8..12].try_into().unwrap()) as usize,
+            glb.len()
+        );
+        let json_length = u32::from_le_bytes(glb[12..16].try_into().unwrap()) as usize;
+        assert_eq!(&glb[16..20], b"JSON");
+        assert!(json_length.is_multiple_of(4));
+        let binary_start = 20 + json_length;
+        let binary_length =
+            u32::from_le_bytes(glb[binary_start..binary_start + 4].try_into().unwrap()) as usize;
+        assert_eq!(&glb[binary_start + 4..binary_start + 8], b"BIN\0");
+        assert_eq!(binary_start + 8 + binary_length, glb.len());
+
+        let document = GltfDocument::from_bytes(glb, std::path::Path::new("scene.glb")).unwrap();
+        assert_eq!(document.array("bufferViews")[1]["byteOffset"], 4);
+        let accessor_json = &document.array("accessors")[accessor];
+        assert_eq!(accessor_json["count"], 2);
+        assert_eq!(accessor_json["min"], serde_json::json!([0.0, -1.0, -2.0]));
+        assert_eq!(accessor_json["max"], serde_json::json!([3.0, 1.0, 2.0]));
+        let blob = document.accessor_blob(accessor).unwrap();
+        let values: Vec<u8> = positions
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        assert!(blob.ends_with(&values));
+    }
 
-                egui::ScrollArea::vertical().show(ui, |ui| {
-                    for entry in &self.asset_library.entries {
-                        let show_ext = match entry.extension.as_str() {
-                            "glb" => self.asset_library.show_glb,
-                            "gltf" => self.asset_library.show_gltf,
-                            "fbx" => self.asset_library.show_fbx,
-                            "hdr" => self.asset_library.show_hdr,
-                            _ => true,
-                        };
-                        if !show_ext {
-                            continue;
-                        }
+    #[test]
+    fn empty_glb_has_no_binary_chunk() {
+        let glb = GlbWriter::default().finish(serde_json::json!({ "asset": { "version": "2.0" } }));
+        let json_length = u32::from_le_bytes(glb[12..16].try_into().unwrap()) as usize;
+        assert_eq!(glb.len(), 20 + json_length);
+        let document = GltfDocument::from_bytes(glb, std::path::Path::new("scene.glb")).unwrap();
+        assert!(document.array("buffers").is_empty());
+    }
 
-                        if let Some(filter) = self.asset_library.filter_type
-                            && entry.asset_type != filter
-                        {
-                            continue;
-                        }
+    #[test]
+    fn downsampling_averages_in_linear_light() {
+        let rgba = [
+            255, 255, 255, 255, 0, 0, 0, 255, //
+            0, 0, 0, 255, 255, 255, 255, 255,
+        ];
+        assert_eq!(downsample_rgba(2, 2, &rgba, 1), (2, 2, rgba.to_vec()));
+        assert_eq!(
+            downsample_rgba(2, 2, &rgba, 2),
+            (1, 1, vec![188, 188, 188, 255])
+        );
+    }
 
-                        if !search_query_lower.is_empty() {
-                            let name_matches =
-                                entry.name.to_lowercase().contains(&search_query_lower);
-                            let tag_matches = entry
-                                .tags
-                                .iter()
-                                .any(|t| t.to_lowercase().contains(&search_query_lower));
-                            let category_matches = entry
-                                .categories
-                                .iter()
-                                .any(|c| c.to_lowercase().contains(&search_query_lower));
+    #[test]
+    fn downsampling_ignores_transparent_colors() {
+        let mut rgba = vec![0; 5 * 3 * 4];
+        rgba[..4].copy_from_slice(&[255, 0, 0, 255]);
+        let (width, height, output) = downsample_rgba(5, 3, &rgba, 2);
+        assert_eq!((width, height), (2, 1));
+        assert_eq!(output[..4], [255, 0, 0, 64]);
+        assert_eq!(output[4..], [0, 0, 0, 0]);
+    }
 
-                            if !name_matches && !tag_matches && !category_matches {
-                                continue;
-                            }
-                        }
+    #[test]
+    fn encoded_png_round_trips() {
+        let rgba: Vec<u8> = (0..3 * 2 * 4).map(|value| value as u8 * 10).collect();
+        let png = encode_png(3, 2, &rgba).unwrap();
+        assert_eq!(image_dimensions(png.as_slice()), Some((3, 2)));
+
+        let mut reader = png::Decoder::new(std::io::Cursor::new(png))
+            .read_info()
+            .unwrap();
+        let mut decoded = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut decoded).unwrap();
+        assert_eq!(info.color_type, png::ColorType::Rgba);
+        assert_eq!(&decoded[..info.buffer_size()], rgba.as_slice());
+        assert!(encode_png(3, 2, &rgba[..8]).is_err());
+    }
 
-                        ui.horizontal(|ui| {
-                            let type_label = match entry.asset_type {
-                                AssetType::Model => "[M]",
-                                AssetType::Animation => "[A]",
-                                AssetType::Skybox => "[S]",
-                            };
+    #[test]
+    fn data_uris_decode_base64_only() {
+        assert_eq!(
+            decode_data_uri("data:application/octet-stream;base64,AAEC\nAw=="),
+            Some(vec![0, 1, 2, 3])
+        );
+        assert_eq!(decode_data_uri("data:text/plain,AAECAw=="), None);
+        assert_eq!(decode_data_uri("data:;base64,AA*C"), None);
+        assert_eq!(decode_data_uri("buffer.bin"), None);
+    }
 
-                            ui.label(type_label);
+    #[test]
+    fn accessor_blob_ignores_layout() {
+        let values = [1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let packed: Vec<u8> = values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        let interleaved: Vec<u8> = packed
+            .chunks_exact(12)
+            .flat_map(|element| element.iter().copied().chain([0xAB; 4]))
+            .collect();
+
+        let mut writer = GlbWriter::default();
+        writer.buffer_view(&[0; 8], None);
+        let packed_view = writer.buffer_view(&packed, None);
+        let interleaved_view = writer.buffer_view(&interleaved, None);
+        writer.buffer_views[interleaved_view]["byteStride"] = serde_json::json!(16);
+        let accessor = |view: usize, count: usize, offset: usize| {
+            serde_json::json!({
+                "bufferView": view,
+                "byteOffset": offset,
+                "componentType": 5126,
+                "count": count,
+                "type": "VEC3",
+            })
+        };
+        writer.accessors = vec![
+            accessor(packed_view, 2, 0),
+            accessor(interleaved_view, 2, 0),
+            accessor(interleaved_view, 1, 0),
+            accessor(interleaved_view, 2, 8),
+        ];
+        let path = std::path::Path::new("scene.glb");
+        let document =
+            GltfDocument::from_bytes(writer.finish(serde_json::json!({})), path).unwrap();
+
+        let packed_blob = document.accessor_blob(0);
+        assert!(packed_blob.is_some());
+        assert_eq!(packed_blob, document.accessor_blob(1));
+        assert_ne!(packed_blob, document.accessor_blob(2));
+        assert_eq!(document.accessor_blob(3), None);
+    }
 
-                            if ui.button(&entry.name).clicked() {
-                                asset_to_load = Some((entry.path.clone(), entry.asset_type));
-                            }
-                        });
+    #[test]
+    fn duplicate_groups_separate_exact_copies_from_same_content() {
+        let directory = scratch_directory("duplicates");
+        let files = [
+            ("a.glb", triangle_glb("Triangle", 0)),
+            ("b.glb", triangle_glb("Triangle", 0)),
+            ("c.glb", triangle_glb("Renamed", 4)),
+            ("d.hdr", vec![1; 64]),
+            ("e.hdr", vec![2; 64]),
+        ];
+        let files: Vec<(PathBuf, u64, AssetType)> = files
+            .into_iter()
+            .map(|(name, bytes)| {
+                let path = directory.join(name);
+                std::fs::write(&path, &bytes).unwrap();
+                let asset_type = if name.ends_with(".glb") {
+                    AssetType::Model
+                } else {
+                    AssetType::Skybox
+                };
+                (path, bytes.len() as u64, asset_type)
+            })
+            .collect();
+
+        let groups = find_duplicate_groups(files);
+        let _ = std::fs::remove_dir_all(&directory);
+
+        let names = |kind: DuplicateKind| -> Vec<Vec<String>> {
+            groups
+                .iter()
+                .filter(|group| group.kind == kind)
+                .map(|group| {
+                    group
+                        .paths
+                        .iter()
+                        .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+                        .collect()
+                })
+                .collect()
+        };
+        assert_eq!(names(DuplicateKind::Exact), vec![vec!["a.glb", "b.glb"]]);
+        assert_eq!(
+            names(DuplicateKind::SameContent),
+            vec![vec!["a.glb", "b.glb", "c.glb"]]
+        );
+    }
+}
 
-                        if !entry.tags.is_empty() || !entry.categories.is_empty() {
-                            ui.indent(entry.path.to_string_lossy(), |ui| {
-                                if !entry.categories.is_empty() {
-                                    ui.label(format!(
-                                        "Categories: {}",
-                                        entry.categories.join(", ")
-                                    ));
-                                }
-                                if !entry.tags.is_empty() {
-                                    ui.label(format!("Tags: {}", entry.tags.join(", ")));
-                                }
-                            });
-                        }
-                    }
-                });
-            });
+#[cfg(all(test, feature = "openxr"))]
+mod tests {
+    use super::*;
 
-        if should_close {
-            self.asset_library.show_window = false;
+    fn hand(position: [f32; 3], squeeze: f32) -> XrHandInput {
+        XrHandInput {
+            tracked: true,
+            position,
+            squeeze,
+            ..Default::default()
         }
+    }
 
-        if let Some(path) = directory_to_scan {
-            self.scan_directory(&path);
-        }
+    fn poses(input: &XrFrameInput) -> [(Vec3, Quat); 2] {
+        [
+            (input.left.position(), input.left.orientation()),
+            (input.right.position(), input.right.orientation()),
+        ]
+    }
 
-        if let Some((path, asset_type)) = asset_to_load {
-            match asset_type {
-                AssetType::Model => self.load_gltf_from_path(world, &path),
-                AssetType::Animation => self.load_fbx(world, &path),
-                AssetType::Skybox => self.load_hdr_skybox(world, &path),
-            }
+    fn grab_from(input: &XrFrameInput) -> XrGrab {
+        XrGrab {
+            hands: [input.left.grabbing(), input.right.grabbing()],
+            start: poses(input),
+            roots: Vec::new(),
+            model_scale: 1.0,
         }
     }
-}
 
-fn calculate_bounds_recursive(
-    world: &World,
-    entity: Entity,
-    parent_transform: &Mat4,
-    min: &mut Vec3,
-    max: &mut Vec3,
-    has_bounds: &mut bool,
-) {
-    let local_matrix = world
-        .get_local_transform(entity)
-        .map(|t| {
-            nalgebra_glm::translation(&t.translation)
-                * nalgebra_glm::quat_to_mat4(&t.rotation)
-                * nalgebra_glm::scaling(&t.scale)
-        })
-        .unwrap_or_else(Mat4::identity);
+    fn assert_close(actual: Vec3, expected: Vec3) {
+        assert!(
+            nalgebra_glm::distance(&actual, &expected) < 1e-4,
+            "{actual:?} != {expected:?}"
+        );
+    }
 
-    let global_matrix = parent_transform * local_matrix;
+    #[test]
+    fn mock_source_repeats_last_frame() {
+        let mut source = XrInputSource::Mock {
+            frames: vec![
+                XrFrameInput::default(),
+                XrFrameInput {
+                    right: hand([0.0, 1.0, 0.0], 1.0),
+                    ..Default::default()
+                },
+            ],
+            index: 0,
+        };
+        assert!(!source.next_frame(None).unwrap().right.grabbing());
+        assert!(source.next_frame(None).unwrap().right.grabbing());
+        assert!(source.next_frame(None).unwrap().right.grabbing());
+    }
 
-    if let Some(bounding_volume) = world.get_bounding_volume(entity) {
-        let transformed_obb = bounding_volume.obb.transform(&global_matrix);
-        let corners = transformed_obb.get_corners();
-        for corner in &corners {
-            *min = nalgebra_glm::min2(min, corner);
-            *max = nalgebra_glm::max2(max, corner);
-        }
-        *has_bounds = true;
+    #[test]
+    fn one_hand_grab_follows_the_hand() {
+        let mut source = XrInputSource::Mock {
+            frames: vec![
+                XrFrameInput {
+                    right: hand([0.0, 1.0, 0.0], 1.0),
+                    ..Default::default()
+                },
+                XrFrameInput {
+                    right: hand([0.5, 1.2, 0.0], 1.0),
+                    ..Default::default()
+                },
+            ],
+            index: 0,
+        };
+        let grab = grab_from(&source.next_frame(None).unwrap());
+        assert_eq!(grab.hands, [false, true]);
+
+        let motion = grab
+            .motion(poses(&source.next_frame(None).unwrap()))
+            .unwrap();
+        let (translation, _, scale) = motion.apply(
+            Vec3::new(0.0, 0.0, -1.0),
+            Quat::identity(),
+            Vec3::new(1.0, 1.0, 1.0),
+        );
+        assert_close(translation, Vec3::new(0.5, 0.2, -1.0));
+        assert_close(scale, Vec3::new(1.0, 1.0, 1.0));
     }
 
-    let children: Vec<Entity> = world
-        .resources
-        .children_cache
-        .get(&entity)
-        .cloned()
-        .unwrap_or_default();
+    #[test]
+    fn two_hand_grab_scales_about_the_midpoint() {
+        let mut source = XrInputSource::Mock {
+            frames: vec![
+                XrFrameInput {
+                    left: hand([-0.1, 1.0, 0.0], 1.0),
+                    right: hand([0.1, 1.0, 0.0], 1.0),
+                    ..Default::default()
+                },
+                XrFrameInput {
+                    left: hand([-0.2, 1.0, 0.0], 1.0),
+                    right: hand([0.2, 1.0, 0.0], 1.0),
+                    ..Default::default()
+                },
+            ],
+            index: 0,
+        };
+        let grab = grab_from(&source.next_frame(None).unwrap());
+        let motion = grab
+            .motion(poses(&source.next_frame(None).unwrap()))
+            .unwrap();
+        assert!((motion.scale - 2.0).abs() < 1e-4);
+
+        let (translation, _, scale) = motion.apply(
+            Vec3::new(0.0, 1.0, -0.5),
+            Quat::identity(),
+            Vec3::new(1.0, 1.0, 1.0),
+        );
+        assert_close(translation, Vec3::new(0.0, 1.0, -1.0));
+        assert_close(scale, Vec3::new(2.0, 2.0, 2.0));
+    }
 
-    for child in children {
-        calculate_bounds_recursive(world, child, &global_matrix, min, max, has_bounds);
+    #[test]
+    fn released_grab_has_no_motion() {
+        let grab = grab_from(&XrFrameInput::default());
+        assert!(grab.motion(poses(&XrFrameInput::default())).is_none());
+    }
+
+    #[test]
+    fn floor_placement_rests_model_in_front_of_head() {
+        let min = Vec3::new(-1.0, -0.5, -1.0);
+        let max = Vec3::new(1.0, 1.5, 1.0);
+        let offset =
+            floor_placement_offset(min, max, Some((Vec3::new(0.0, 1.6, 0.0), Quat::identity())));
+        assert_close(min + offset, Vec3::new(-1.0, 0.0, -3.0));
+        assert_close((min + max) * 0.5 + offset, Vec3::new(0.0, 1.0, -2.0));
     }
 }